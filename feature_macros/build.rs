@@ -1,42 +1,147 @@
-extern crate rustc_version;
-use rustc_version::{version_meta, Channel};
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Capabilities are detected by test-compiling a snippet for the target.
+///
+/// Each one which compiles is emitted as a bare `cfg` of the same name,
+/// and the full set is exported as `DEP_FEATURE_MACROS_CAPABILITIES` so
+/// dependents (which expand our macros) can emit identical `cfg`s.
+const PROBES: &[(&str, &str)] = &[
+    (
+        // `core::hint::unreachable_unchecked`, stable since 1.27
+        "HAS_UNREACHABLE_UNCHECKED",
+        r#"
+        #![no_std]
+        pub unsafe fn probe() -> ! { ::core::hint::unreachable_unchecked() }
+        "#,
+    ),
+    (
+        // `u128` and `i128`, stable since 1.26
+        "HAS_U128",
+        r#"
+        #![no_std]
+        pub fn probe(a: u128, b: i128) -> u128 { a.wrapping_add(b as u128) }
+        "#,
+    ),
+    (
+        // `const fn` with arithmetic in its body, stable since 1.31
+        "HAS_CONST_FN",
+        r#"
+        #![no_std]
+        pub const fn probe(a: usize) -> usize { a * 2 + 1 }
+        pub const VALUE: usize = probe(3);
+        "#,
+    ),
+    (
+        // the target's `core::arch` module, probed through `_mm_prefetch`
+        // on x86 (stable since 1.27) and `core::arch::asm!` elsewhere
+        // (stable since 1.59), as those are what the code uses it for
+        "HAS_CORE_ARCH",
+        r#"
+        #![no_std]
+        #[cfg(target_arch = "x86_64")]
+        pub use core::arch::x86_64::_mm_prefetch as probe;
+        #[cfg(target_arch = "x86")]
+        pub use core::arch::x86::_mm_prefetch as probe;
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "x86")))]
+        pub fn probe() { unsafe { ::core::arch::asm!("nop") } }
+        "#,
+    ),
+    (
+        // inline assembly through `core::arch::asm!`, stable since 1.59
+        "HAS_ASM",
+        r#"
+        #![no_std]
+        pub fn probe() { unsafe { ::core::arch::asm!("nop", options(nomem, nostack)) } }
+        "#,
+    ),
+    (
+        // `#![feature(core_intrinsics)]` is only accepted on nightly
+        "HAS_CORE_INTRINSICS",
+        r#"
+        #![no_std]
+        #![allow(internal_features)]
+        #![feature(core_intrinsics)]
+        pub use core::intrinsics::copy_nonoverlapping as probe;
+        "#,
+    ),
+    (
+        // branch hinting, in the form `intrinsics::hint_likely` calls it
+        "HAS_LIKELY_INTRINSICS",
+        r#"
+        #![no_std]
+        #![allow(internal_features)]
+        #![feature(core_intrinsics)]
+        pub fn probe(b: bool) -> bool {
+            ::core::intrinsics::likely(b) || ::core::intrinsics::unlikely(b)
+        }
+        "#,
+    ),
+    (
+        // cache prefetching, in the form `prefetch::Locality` impls call it
+        "HAS_PREFETCH_INTRINSICS",
+        r#"
+        #![no_std]
+        #![allow(internal_features)]
+        #![feature(core_intrinsics)]
+        #![allow(unused_unsafe)]
+        pub fn probe(ptr: *const u8) {
+            unsafe { ::core::intrinsics::prefetch_read_data::<u8, 3>(ptr) }
+            unsafe { ::core::intrinsics::prefetch_write_data::<u8, 3>(ptr) }
+        }
+        "#,
+    ),
+];
+
+/// compiles `source` as a library for the target being built,
+/// returns if compilation succeeded.
+fn probe(out_dir: &Path, name: &str, source: &str) -> bool {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let target = env::var("TARGET").expect("cargo always sets TARGET");
+
+    let source_path = out_dir.join(format!("probe_{}.rs", name));
+    File::create(&source_path)
+        .and_then(|mut file| file.write_all(source.as_bytes()))
+        .expect("could not write capability probe");
+
+    Command::new(rustc)
+        .arg("--crate-type=lib")
+        .arg("--emit=metadata")
+        .arg("--edition=2015")
+        .arg("--target")
+        .arg(target)
+        .arg("--crate-name")
+        .arg(format!("probe_{}", name.to_lowercase()))
+        .arg("--out-dir")
+        .arg(out_dir)
+        .arg(&source_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
 
 fn main() {
-    let version_info = match version_meta() {
-        Ok(v) => v,
-        Err(e) => panic!("could not identify rustc version. error:'{:?}'", e),
-    };
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=RUSTC");
 
-    if version_info.semver.major != 1 {
-        panic!("presently we only support version 1.X of rustc");
-    }
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("cargo always sets OUT_DIR"));
 
-    match version_info.channel {
-        Channel::Dev => {
-            println!(r#"cargo:rustc-cfg=feature="RUSTC_DEV""#);
-        }
-        Channel::Nightly => {
-            println!(r#"cargo:rustc-cfg=feature="RUSTC_NIGHTLY""#);
-        }
-        Channel::Beta => {
-            println!(r#"cargo:rustc-cfg=feature="RUSTC_BETA""#);
+    let mut detected = Vec::new();
+    for &(name, source) in PROBES {
+        println!("cargo:rustc-check-cfg=cfg({})", name);
+        if probe(&out_dir, name, source) {
+            println!("cargo:rustc-cfg={}", name);
+            detected.push(name);
         }
-        Channel::Stable => {
-            println!(r#"cargo:rustc-cfg=feature="RUSTC_STABLE""#);
-        }
-    };
-
-    if version_info.semver.major == 1 && version_info.semver.minor >= 27 {
-        // special configuration variable for compiler version 1.27
-        // this is when `core::hint::unchecked_unreachable` was
-        // stablized.
-        println!("cargo:rustc-cfg=RUSTC_VERSION_GE_1_27");
     }
 
-    if version_info.semver.major == 1 && version_info.semver.minor >= 27 {
-        // special configuration variable for compiler version 1.26
-        // this is when `u128` was
-        // stablized.
-        println!("cargo:rustc-cfg=RUSTC_VERSION_GE_1_26");
-    }
+    // consumed by dependents' build scripts as `DEP_FEATURE_MACROS_*`
+    let known = PROBES.iter().map(|&(name, _)| name).collect::<Vec<_>>();
+    println!("cargo:KNOWN={}", known.join(","));
+    println!("cargo:CAPABILITIES={}", detected.join(","));
 }
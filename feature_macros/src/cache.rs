@@ -0,0 +1,510 @@
+#[cfg(not(feature = "std"))]
+use core::ops::{Deref, DerefMut};
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(all(not(feature = "std"), HAS_CORE_ARCH, target_arch = "x86"))]
+use core::arch::x86 as arch;
+#[cfg(all(not(feature = "std"), HAS_CORE_ARCH, target_arch = "x86_64"))]
+use core::arch::x86_64 as arch;
+#[cfg(all(feature = "std", HAS_CORE_ARCH, target_arch = "x86"))]
+use std::arch::x86 as arch;
+#[cfg(all(feature = "std", HAS_CORE_ARCH, target_arch = "x86_64"))]
+use std::arch::x86_64 as arch;
+
+#[cfg(all(not(feature = "std"), HAS_ASM, not(miri), target_arch = "aarch64"))]
+use core::arch::asm;
+#[cfg(all(feature = "std", HAS_ASM, not(miri), target_arch = "aarch64"))]
+use std::arch::asm;
+
+/// the line size assumed when it cannot be detected, and by code which
+/// must not wait on detection, such as prefetch strides.
+///
+/// Apple's aarch64 cores and POWER use 128 byte lines, nearly
+/// everything else uses 64.
+#[cfg(any(
+    all(target_arch = "aarch64", target_vendor = "apple"),
+    target_arch = "powerpc64"
+))]
+pub const FALLBACK_LINE_SIZE: usize = 128;
+#[cfg(not(any(
+    all(target_arch = "aarch64", target_vendor = "apple"),
+    target_arch = "powerpc64"
+)))]
+pub const FALLBACK_LINE_SIZE: usize = 64;
+
+/// the cache assumed when it cannot be detected.
+pub const FALLBACK: Cache = Cache {
+    line_size: FALLBACK_LINE_SIZE,
+    l1: 32 * 1024,
+    l2: 256 * 1024,
+    l3: 0,
+};
+
+/// Cache describes the data caches of the running CPU, all sizes are
+/// in bytes. A level which is not present is `0`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Cache {
+    line_size: usize,
+    l1: usize,
+    l2: usize,
+    l3: usize,
+}
+impl Cache {
+    /// size of an L1 data cache line
+    #[inline(always)]
+    pub fn line_size(&self) -> usize {
+        self.line_size
+    }
+
+    /// size of the L1 data cache, per core
+    #[inline(always)]
+    pub fn l1(&self) -> usize {
+        self.l1
+    }
+
+    /// size of the L2 cache
+    #[inline(always)]
+    pub fn l2(&self) -> usize {
+        self.l2
+    }
+
+    /// size of the L3 cache, or `0` when there is none
+    #[inline(always)]
+    pub fn l3(&self) -> usize {
+        self.l3
+    }
+
+    /// returns the log2 of the number of `entry_size` byte entries
+    /// a table may have while fitting within half of the cache at
+    /// `level` (1 to 3), clamped to `min_log..=max_log`.
+    ///
+    /// A hash table consulted on every byte should be sized against
+    /// level 1, a match finder's chain table against level 2.
+    pub fn table_log(&self, level: u32, entry_size: usize, min_log: u32, max_log: u32) -> u32 {
+        debug_assert!(min_log <= max_log);
+        let size = match level {
+            1 => self.l1,
+            2 => self.l2,
+            _ => self.l3,
+        };
+        let entries = size / 2 / entry_size.max(1);
+        let log = if entries == 0 { 0 } else { floor_log2(entries) };
+        log.max(min_log).min(max_log)
+    }
+
+    /// returns a block size whose working set fits within half of
+    /// L2, rounded down to a power of two and clamped to `min..=max`.
+    pub fn block_size(&self, min: usize, max: usize) -> usize {
+        debug_assert!(min <= max);
+        let half = self.l2 / 2;
+        let size = if half == 0 {
+            min
+        } else {
+            1usize << floor_log2(half)
+        };
+        size.max(min).min(max)
+    }
+
+    /// fills every level which was not detected from `FALLBACK`
+    #[allow(dead_code)]
+    fn or_fallback(self) -> Cache {
+        Cache {
+            line_size: if self.line_size.is_power_of_two() {
+                self.line_size
+            } else {
+                FALLBACK.line_size
+            },
+            l1: if self.l1 == 0 { FALLBACK.l1 } else { self.l1 },
+            l2: if self.l2 == 0 { FALLBACK.l2 } else { self.l2 },
+            l3: self.l3,
+        }
+    }
+
+    /// records a data or unified cache of `level`
+    #[allow(dead_code)]
+    fn with_level(mut self, level: u32, line_size: usize, size: usize) -> Cache {
+        match level {
+            1 => {
+                self.l1 = size;
+                self.line_size = line_size;
+            }
+            2 => self.l2 = size,
+            3 => self.l3 = size,
+            _ => {}
+        };
+        self
+    }
+
+    #[allow(dead_code)]
+    const EMPTY: Cache = Cache {
+        line_size: 0,
+        l1: 0,
+        l2: 0,
+        l3: 0,
+    };
+}
+
+#[inline(always)]
+fn floor_log2(x: usize) -> u32 {
+    debug_assert!(x > 0);
+    (0usize.count_zeros() - 1) - x.leading_zeros()
+}
+
+/// the detected values, `LINE_SIZE` is written last and is non-zero
+/// once the others are valid.
+static LINE_SIZE: AtomicUsize = AtomicUsize::new(0);
+static L1: AtomicUsize = AtomicUsize::new(0);
+static L2: AtomicUsize = AtomicUsize::new(0);
+static L3: AtomicUsize = AtomicUsize::new(0);
+
+/// returns the caches of the running CPU.
+///
+/// Detection happens on the first call, later calls only load the
+/// cached values.
+#[inline]
+pub fn cache() -> Cache {
+    let line_size = LINE_SIZE.load(Ordering::Acquire);
+    if line_size != 0 {
+        return Cache {
+            line_size,
+            l1: L1.load(Ordering::Relaxed),
+            l2: L2.load(Ordering::Relaxed),
+            l3: L3.load(Ordering::Relaxed),
+        };
+    }
+    let detected = detect();
+    L1.store(detected.l1, Ordering::Relaxed);
+    L2.store(detected.l2, Ordering::Relaxed);
+    L3.store(detected.l3, Ordering::Relaxed);
+    LINE_SIZE.store(detected.line_size, Ordering::Release);
+    detected
+}
+
+/// returns the size of a data cache line on the running CPU
+#[inline]
+pub fn line_size() -> usize {
+    cache().line_size()
+}
+
+/// performs detection without consulting the cache.
+///
+/// On x86 this uses `cpuid` (so it works without `std`), otherwise under
+/// `std` on Linux it reads `/sys/devices/system/cpu`. Anything which is
+/// not detected is taken from `FALLBACK`.
+#[allow(unreachable_code)]
+pub fn detect() -> Cache {
+    #[cfg(all(
+        HAS_CORE_ARCH,
+        not(miri),
+        any(target_arch = "x86", target_arch = "x86_64")
+    ))]
+    {
+        if let Option::Some(cache) = detect_cpuid() {
+            return cache.or_fallback();
+        }
+    }
+
+    #[cfg(all(feature = "std", not(miri), target_os = "linux"))]
+    {
+        if let Option::Some(cache) = detect_sysfs() {
+            return cache.or_fallback();
+        }
+    }
+
+    #[cfg(all(HAS_ASM, not(miri), target_arch = "aarch64"))]
+    {
+        return Cache {
+            line_size: aarch64_line_size(),
+            ..FALLBACK
+        }
+        .or_fallback();
+    }
+
+    FALLBACK
+}
+
+/// decodes a leaf 4 (Intel) or `0x8000_001D` (AMD) cache descriptor,
+/// returning the level, line size and total size of a data or
+/// unified cache.
+#[allow(dead_code)]
+fn decode_cache_leaf(eax: u32, ebx: u32, ecx: u32) -> Option<(u32, usize, usize)> {
+    // 1 is data, 2 is instruction, 3 is unified
+    let kind = eax & 0x1F;
+    if kind != 1 && kind != 3 {
+        return Option::None;
+    }
+    let level = (eax >> 5) & 0x7;
+    let line_size = ((ebx & 0xFFF) + 1) as usize;
+    let partitions = (((ebx >> 12) & 0x3FF) + 1) as usize;
+    let ways = ((ebx >> 22) + 1) as usize;
+    let sets = (ecx as usize) + 1;
+    Option::Some((level, line_size, ways * partitions * line_size * sets))
+}
+
+#[cfg(all(
+    HAS_CORE_ARCH,
+    not(miri),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+#[allow(unused_unsafe)]
+fn detect_cpuid() -> Option<Cache> {
+    /// walks the sub-leaves of `leaf` until the null descriptor
+    fn walk(leaf: u32) -> Option<Cache> {
+        let mut cache = Cache::EMPTY;
+        let mut found = false;
+        for subleaf in 0..16 {
+            let regs = unsafe { arch::__cpuid_count(leaf, subleaf) };
+            if regs.eax & 0x1F == 0 {
+                break;
+            }
+            if let Option::Some((level, line_size, size)) =
+                decode_cache_leaf(regs.eax, regs.ebx, regs.ecx)
+            {
+                cache = cache.with_level(level, line_size, size);
+                found = true;
+            }
+        }
+        if found {
+            Option::Some(cache)
+        } else {
+            Option::None
+        }
+    }
+
+    // CPUs before the 586 fault on `cpuid`, every x86_64 CPU has it
+    #[cfg(target_arch = "x86")]
+    {
+        if !arch::has_cpuid() {
+            return Option::None;
+        }
+    }
+
+    let max_leaf = unsafe { arch::__cpuid(0) }.eax;
+    if max_leaf >= 4 {
+        if let Option::Some(cache) = walk(4) {
+            return Option::Some(cache);
+        }
+    }
+    // AMD leaves leaf 4 empty, and reports with topology extensions
+    let max_extended = unsafe { arch::__cpuid(0x8000_0000) }.eax;
+    if max_extended >= 0x8000_001D && unsafe { arch::__cpuid(0x8000_0001) }.ecx & (1 << 22) != 0 {
+        return walk(0x8000_001D);
+    }
+    Option::None
+}
+
+/// parses sizes as sysfs reports them, such as `48K` or `2M`
+#[cfg(feature = "std")]
+#[allow(dead_code)]
+fn parse_sysfs_size(value: &str) -> Option<usize> {
+    let value = value.trim();
+    let (digits, scale) = match value.as_bytes().last() {
+        Option::Some(b'K') => (&value[..value.len() - 1], 1024),
+        Option::Some(b'M') => (&value[..value.len() - 1], 1024 * 1024),
+        Option::Some(b'G') => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    digits.parse::<usize>().ok().map(|x| x * scale)
+}
+
+#[cfg(all(feature = "std", not(miri), target_os = "linux"))]
+fn detect_sysfs() -> Option<Cache> {
+    use std::fs::read_to_string;
+
+    let mut cache = Cache::EMPTY;
+    let mut found = false;
+    for index in 0..16 {
+        let dir = format!("/sys/devices/system/cpu/cpu0/cache/index{}", index);
+        let read = |name: &str| read_to_string(format!("{}/{}", dir, name)).ok();
+        let kind = match read("type") {
+            Option::Some(kind) => kind,
+            Option::None => break,
+        };
+        if kind.trim() != "Data" && kind.trim() != "Unified" {
+            continue;
+        }
+        let level = read("level").and_then(|x| x.trim().parse::<u32>().ok());
+        let line_size = read("coherency_line_size").and_then(|x| x.trim().parse::<usize>().ok());
+        let size = read("size").and_then(|x| parse_sysfs_size(&x));
+        if let (Option::Some(level), Option::Some(line_size), Option::Some(size)) =
+            (level, line_size, size)
+        {
+            cache = cache.with_level(level, line_size, size);
+            found = true;
+        }
+    }
+    if found {
+        Option::Some(cache)
+    } else {
+        Option::None
+    }
+}
+
+/// `CTR_EL0` is readable from userspace, `DminLine` is the log2 of the
+/// smallest data cache line in words.
+#[cfg(all(HAS_ASM, not(miri), target_arch = "aarch64"))]
+fn aarch64_line_size() -> usize {
+    let ctr: u64;
+    unsafe { asm!("mrs {0}, ctr_el0", out(reg) ctr, options(nomem, nostack, preserves_flags)) };
+    4usize << ((ctr >> 16) & 0xF)
+}
+
+/// CachePadded aligns and pads a value to its own cache line(s), so
+/// values written by different threads do not share a line.
+///
+/// 128 bytes is used where the hardware fetches lines in pairs (x86_64's
+/// adjacent line prefetcher) or the lines are 128 bytes.
+#[cfg_attr(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    ),
+    repr(align(128))
+)]
+#[cfg_attr(
+    not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    )),
+    repr(align(64))
+)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct CachePadded<T> {
+    value: T,
+}
+impl<T> CachePadded<T> {
+    #[cfg(HAS_CONST_FN)]
+    #[inline(always)]
+    pub const fn new(value: T) -> CachePadded<T> {
+        CachePadded { value }
+    }
+
+    #[cfg(not(HAS_CONST_FN))]
+    #[inline(always)]
+    pub fn new(value: T) -> CachePadded<T> {
+        CachePadded { value }
+    }
+
+    #[inline(always)]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+impl<T> DerefMut for CachePadded<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+impl<T> From<T> for CachePadded<T> {
+    #[inline(always)]
+    fn from(value: T) -> CachePadded<T> {
+        CachePadded::new(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::mem::{align_of, size_of};
+    use super::{cache, decode_cache_leaf, detect, line_size, CachePadded, FALLBACK};
+
+    #[test]
+    fn detection_is_sane() {
+        let dut = detect();
+        assert!(dut.line_size().is_power_of_two());
+        assert!(dut.line_size() >= 16 && dut.line_size() <= 256);
+        // undetected levels are filled in, but VMs and containers may
+        // report sizes which are not increasing
+        assert!(dut.l1() > 0);
+        assert!(dut.l2() > 0);
+
+        // served from the cache the second time
+        assert_eq!(cache(), dut);
+        assert_eq!(cache(), dut);
+        assert_eq!(line_size(), dut.line_size());
+    }
+
+    #[test]
+    fn decodes_cache_leaf() {
+        // 48KiB 12-way L1D with 64 byte lines and 64 sets
+        let eax = (1 << 5) | 1;
+        let ebx = (11 << 22) | 63;
+        assert_eq!(decode_cache_leaf(eax, ebx, 63), Some((1, 64, 48 * 1024)));
+        // 2MiB 16-way unified L2
+        let eax = (2 << 5) | 3;
+        let ebx = (15 << 22) | 63;
+        assert_eq!(
+            decode_cache_leaf(eax, ebx, 2047),
+            Some((2, 64, 2 * 1024 * 1024))
+        );
+        // instruction caches are ignored
+        assert_eq!(decode_cache_leaf((1 << 5) | 2, ebx, 63), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn parses_sysfs_sizes() {
+        use super::parse_sysfs_size;
+
+        assert_eq!(parse_sysfs_size("48K\n"), Some(48 * 1024));
+        assert_eq!(parse_sysfs_size("2M"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_sysfs_size("512"), Some(512));
+        assert_eq!(parse_sysfs_size("K"), None);
+    }
+
+    #[test]
+    fn sizes_tables_and_blocks() {
+        // half of a 32KiB L1 holds 4096 `u32` entries
+        assert_eq!(FALLBACK.table_log(1, 4, 8, 20), 12);
+        assert_eq!(FALLBACK.table_log(1, 5, 8, 20), 11);
+        assert_eq!(FALLBACK.table_log(2, 4, 8, 20), 15);
+        assert_eq!(FALLBACK.table_log(1, 4, 14, 20), 14);
+        assert_eq!(FALLBACK.table_log(1, 4, 8, 10), 10);
+        // there is no L3
+        assert_eq!(FALLBACK.table_log(3, 4, 8, 20), 8);
+        let log = cache().table_log(1, 4, 0, 32);
+        assert!((4usize << log) <= cache().l1());
+
+        assert_eq!(FALLBACK.block_size(1024, 1 << 22), 128 * 1024);
+        assert_eq!(FALLBACK.block_size(1024, 64 * 1024), 64 * 1024);
+        let block = cache().block_size(1 << 16, 1 << 22);
+        assert!(block.is_power_of_two());
+        assert!(block * 2 <= cache().l2() || block == 1 << 16);
+    }
+
+    #[test]
+    fn cache_padded_fills_lines() {
+        assert!(align_of::<CachePadded<u8>>() >= FALLBACK.line_size());
+        assert!(size_of::<CachePadded<u8>>() >= FALLBACK.line_size());
+        let mut dut = CachePadded::new(5u32);
+        *dut += 1;
+        assert_eq!(*dut, 6);
+        assert_eq!(dut.into_inner(), 6);
+
+        let pair = [CachePadded::new(0u8), CachePadded::new(0u8)];
+        let distance = (&pair[1] as *const _ as usize) - (&pair[0] as *const _ as usize);
+        assert!(distance >= FALLBACK.line_size());
+    }
+
+    #[cfg(HAS_CONST_FN)]
+    #[test]
+    fn cache_padded_is_const() {
+        static PADDED: CachePadded<u32> = CachePadded::new(7);
+        assert_eq!(*PADDED, 7);
+    }
+}
@@ -0,0 +1,450 @@
+#[cfg(not(feature = "std"))]
+use core::ptr::null_mut;
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use std::ptr::null_mut;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+#[cfg(all(not(feature = "std"), HAS_CORE_ARCH, target_arch = "x86"))]
+use core::arch::x86 as arch;
+#[cfg(all(not(feature = "std"), HAS_CORE_ARCH, target_arch = "x86_64"))]
+use core::arch::x86_64 as arch;
+#[cfg(all(feature = "std", HAS_CORE_ARCH, target_arch = "x86"))]
+use std::arch::x86 as arch;
+#[cfg(all(feature = "std", HAS_CORE_ARCH, target_arch = "x86_64"))]
+use std::arch::x86_64 as arch;
+
+/// Feature is a CPU extension which kernels may dispatch on
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Feature {
+    /// x86 SSE2, part of the x86_64 baseline
+    Sse2,
+    /// x86 SSSE3
+    Ssse3,
+    /// x86 SSE4.1
+    Sse41,
+    /// x86 SSE4.2
+    Sse42,
+    /// x86 AVX2, only reported when the OS saves the `ymm` registers
+    Avx2,
+    /// x86 AVX-512 Foundation, only reported when the OS saves the `zmm` registers
+    Avx512f,
+    /// x86 BMI2
+    Bmi2,
+    /// x86 carry-less multiplication
+    Pclmulqdq,
+    /// ARM NEON (ASIMD), part of the aarch64 baseline
+    Neon,
+    /// ARM CRC32 instructions
+    Crc,
+}
+impl Feature {
+    #[inline(always)]
+    fn bit(self) -> usize {
+        1usize << (self as usize)
+    }
+}
+
+/// marks the cache as populated, it is above every `Feature` bit
+const INITIALIZED: usize = 1usize << 15;
+
+static CACHE: AtomicUsize = AtomicUsize::new(0);
+
+/// Features is the set of extensions supported by the running CPU
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Features {
+    bits: usize,
+}
+impl Features {
+    /// returns if `feature` is supported
+    #[inline(always)]
+    pub fn has(&self, feature: Feature) -> bool {
+        (self.bits & feature.bit()) != 0
+    }
+
+    #[inline(always)]
+    fn with(self, feature: Feature, supported: bool) -> Features {
+        if supported {
+            Features {
+                bits: self.bits | feature.bit(),
+            }
+        } else {
+            self
+        }
+    }
+}
+
+/// returns the features of the running CPU.
+///
+/// Detection happens on the first call, later calls are a single
+/// relaxed atomic load. Racing first calls both detect, which is
+/// harmless as they store the same value.
+#[inline]
+pub fn features() -> Features {
+    let cached = CACHE.load(Ordering::Relaxed);
+    if cached & INITIALIZED != 0 {
+        return Features {
+            bits: cached & !INITIALIZED,
+        };
+    }
+    let detected = detect();
+    CACHE.store(detected.bits | INITIALIZED, Ordering::Relaxed);
+    detected
+}
+
+/// returns if the running CPU supports `feature`
+#[inline]
+pub fn has(feature: Feature) -> bool {
+    features().has(feature)
+}
+
+/// performs detection without consulting the cache
+pub fn detect() -> Features {
+    #[cfg(all(
+        HAS_CORE_ARCH,
+        not(miri),
+        any(target_arch = "x86", target_arch = "x86_64")
+    ))]
+    {
+        detect_x86()
+    }
+
+    #[cfg(all(not(miri), target_arch = "aarch64"))]
+    {
+        detect_aarch64()
+    }
+
+    #[cfg(not(all(
+        not(miri),
+        any(
+            all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64")),
+            target_arch = "aarch64"
+        )
+    )))]
+    {
+        detect_static()
+    }
+}
+
+/// the features the compiler was told the target always has, used
+/// where runtime detection is not possible
+#[allow(dead_code)]
+fn detect_static() -> Features {
+    Features::default()
+        .with(Feature::Sse2, cfg!(target_feature = "sse2"))
+        .with(Feature::Ssse3, cfg!(target_feature = "ssse3"))
+        .with(Feature::Sse41, cfg!(target_feature = "sse4.1"))
+        .with(Feature::Sse42, cfg!(target_feature = "sse4.2"))
+        .with(Feature::Avx2, cfg!(target_feature = "avx2"))
+        .with(Feature::Avx512f, cfg!(target_feature = "avx512f"))
+        .with(Feature::Bmi2, cfg!(target_feature = "bmi2"))
+        .with(Feature::Pclmulqdq, cfg!(target_feature = "pclmulqdq"))
+        .with(Feature::Neon, cfg!(target_feature = "neon"))
+        .with(Feature::Crc, cfg!(target_feature = "crc"))
+}
+
+/// uses `cpuid` directly, so it works without `std`
+#[cfg(all(
+    HAS_CORE_ARCH,
+    not(miri),
+    any(target_arch = "x86", target_arch = "x86_64")
+))]
+#[allow(unused_unsafe)]
+fn detect_x86() -> Features {
+    #[inline(always)]
+    fn bit(register: u32, index: u32) -> bool {
+        (register & (1u32 << index)) != 0
+    }
+
+    // CPUs before the 586 fault on `cpuid`, every x86_64 CPU has it
+    #[cfg(target_arch = "x86")]
+    {
+        if !arch::has_cpuid() {
+            return detect_static();
+        }
+    }
+
+    let max_leaf = unsafe { arch::__cpuid(0) }.eax;
+    let leaf1 = unsafe { arch::__cpuid(1) };
+    let leaf7_ebx = if max_leaf >= 7 {
+        unsafe { arch::__cpuid_count(7, 0) }.ebx
+    } else {
+        0
+    };
+
+    // AVX state must be enabled by the OS, not just present on the CPU
+    let xcr0 = if bit(leaf1.ecx, 27) {
+        unsafe { arch::_xgetbv(0) }
+    } else {
+        0
+    };
+    let os_avx = (xcr0 & 0b110) == 0b110;
+    let os_avx512 = os_avx && (xcr0 & 0b1110_0000) == 0b1110_0000;
+
+    Features::default()
+        .with(Feature::Sse2, bit(leaf1.edx, 26))
+        .with(Feature::Ssse3, bit(leaf1.ecx, 9))
+        .with(Feature::Sse41, bit(leaf1.ecx, 19))
+        .with(Feature::Sse42, bit(leaf1.ecx, 20))
+        .with(Feature::Pclmulqdq, bit(leaf1.ecx, 1))
+        .with(
+            Feature::Avx2,
+            os_avx && bit(leaf1.ecx, 28) && bit(leaf7_ebx, 5),
+        )
+        .with(Feature::Avx512f, os_avx512 && bit(leaf7_ebx, 16))
+        .with(Feature::Bmi2, bit(leaf7_ebx, 8))
+}
+
+#[cfg(all(not(miri), target_arch = "aarch64"))]
+fn detect_aarch64() -> Features {
+    #[cfg(feature = "std")]
+    let crc = std::arch::is_aarch64_feature_detected!("crc");
+    #[cfg(not(feature = "std"))]
+    let crc = cfg!(target_feature = "crc");
+
+    // NEON is part of the aarch64 baseline
+    Features::default()
+        .with(Feature::Neon, true)
+        .with(Feature::Crc, crc)
+}
+
+/// Dispatch holds the function pointer `multiversion!` resolved,
+/// as a pointer rather than an integer so provenance is kept.
+///
+/// This is an implementation detail of `multiversion!`.
+#[doc(hidden)]
+pub struct Dispatch {
+    entry: AtomicPtr<()>,
+}
+impl Dispatch {
+    /// an unresolved `Dispatch`, usable in a `static` on compilers
+    /// without `const fn`, as `ATOMIC_USIZE_INIT` was
+    #[doc(hidden)]
+    #[allow(clippy::declare_interior_mutable_const)]
+    pub const INIT: Dispatch = Dispatch {
+        entry: AtomicPtr::new(null_mut()),
+    };
+
+    #[doc(hidden)]
+    #[cfg(HAS_CONST_FN)]
+    #[inline(always)]
+    pub const fn new() -> Dispatch {
+        Dispatch::INIT
+    }
+
+    #[doc(hidden)]
+    #[cfg(not(HAS_CONST_FN))]
+    #[inline(always)]
+    pub fn new() -> Dispatch {
+        Dispatch::INIT
+    }
+
+    /// the resolved entry point, or null if it has not been resolved
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn get(&self) -> *mut () {
+        self.entry.load(Ordering::Relaxed)
+    }
+
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn set(&self, entry: *mut ()) {
+        self.entry.store(entry, Ordering::Relaxed)
+    }
+}
+
+/// multiversion declares a function whose body is compiled once per
+/// listed `#[target_feature]` set, plus a portable fallback.
+///
+/// The first call checks `cpu::has` for each version in the order
+/// listed and stores the first supported one, later calls jump straight
+/// to it through the stored pointer. Arguments must be plain identifiers
+/// and the function may not be generic. The body is compiled as a safe
+/// function, so unsafe operations within it need their own `unsafe {}`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate feature_macros;
+///
+/// multiversion! {
+///     /// sums the input, letting LLVM vectorize it for each target
+///     pub fn sum(data: &[u32]) -> u32 => [
+///         #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+///         "avx2" => Avx2,
+///         #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+///         "sse4.1" => Sse41,
+///     ] {
+///         data.iter().fold(0u32, |acc, x| acc.wrapping_add(*x))
+///     }
+/// }
+///
+/// fn main() {
+///     assert_eq!(sum(&[1, 2, 3]), 6);
+/// }
+/// ```
+///
+/// A version needing several features lists them as `"avx2,bmi2" => Avx2 & Bmi2`.
+#[macro_export]
+macro_rules! multiversion {
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident ( $($arg:ident : $ty:ty),* $(,)? ) => [
+            $( $(#[$vcfg:meta])* $feature:literal => $($flag:ident)&+ ),* $(,)?
+        ]
+        $body:block
+    ) => {
+        $crate::multiversion! {
+            $(#[$meta])*
+            $vis fn $name ( $($arg : $ty),* ) -> () => [
+                $( $(#[$vcfg])* $feature => $($flag)&+ ),*
+            ]
+            $body
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident ( $($arg:ident : $ty:ty),* $(,)? ) -> $ret:ty => [
+            $( $(#[$vcfg:meta])* $feature:literal => $($flag:ident)&+ ),* $(,)?
+        ]
+        $body:block
+    ) => {
+        $(#[$meta])*
+        #[inline]
+        $vis fn $name ( $($arg : $ty),* ) -> $ret {
+            type Entry = unsafe fn($($ty),*) -> $ret;
+            static SELECTED: $crate::cpu::Dispatch = $crate::cpu::Dispatch::INIT;
+
+            // a safe fn, so unsafe operations in the body still need an
+            // `unsafe {}` block, inlined into each version below
+            #[inline(always)]
+            fn body ( $($arg : $ty),* ) -> $ret $body
+
+            unsafe fn portable ( $($arg : $ty),* ) -> $ret {
+                body($($arg),*)
+            }
+
+            fn resolve() -> Entry {
+                $crate::multiversion!(@versions ( $($arg : $ty),* ) -> $ret, ( $($arg),* ), [
+                    $( $(#[$vcfg])* $feature => $($flag)&+ ),*
+                ]);
+                portable
+            }
+
+            let stored = SELECTED.get();
+            let entry: Entry = if stored.is_null() {
+                let entry = resolve();
+                SELECTED.set(entry as *mut ());
+                entry
+            } else {
+                // only ever stored from an `Entry` above
+                unsafe { $crate::mem::transmute::<*mut (), Entry>(stored) }
+            };
+            // `resolve` only selects versions the CPU supports
+            unsafe { entry($($arg),*) }
+        }
+    };
+    (@versions $params:tt -> $ret:ty, $call:tt, [
+        $( $(#[$vcfg:meta])* $feature:literal => $($flag:ident)&+ ),*
+    ]) => {
+        $(
+            $(#[$vcfg])*
+            {
+                #[target_feature(enable = $feature)]
+                unsafe fn version $params -> $ret {
+                    body $call
+                }
+
+                if $( $crate::cpu::has($crate::cpu::Feature::$flag) )&&+ {
+                    return version;
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{detect, features, has, Feature};
+
+    #[test]
+    fn cache_matches_detection() {
+        assert_eq!(features(), detect());
+        // second call is served from the cache
+        assert_eq!(features(), detect());
+        assert_eq!(has(Feature::Sse2), detect().has(Feature::Sse2));
+    }
+
+    #[test]
+    fn baseline_features_are_reported() {
+        if cfg!(target_arch = "x86_64") {
+            assert!(has(Feature::Sse2));
+        }
+        if cfg!(target_arch = "aarch64") {
+            assert!(has(Feature::Neon));
+        }
+        // everything the compiler may assume must also be detected
+        if cfg!(target_feature = "sse4.2") {
+            assert!(has(Feature::Sse42));
+        }
+        if cfg!(target_feature = "avx2") {
+            assert!(has(Feature::Avx2));
+        }
+    }
+
+    #[test]
+    fn implied_features_are_consistent() {
+        let features = features();
+        if features.has(Feature::Avx512f) {
+            assert!(features.has(Feature::Avx2));
+        }
+        if features.has(Feature::Sse42) {
+            assert!(features.has(Feature::Sse41));
+        }
+        if features.has(Feature::Sse41) {
+            assert!(features.has(Feature::Ssse3));
+        }
+    }
+
+    multiversion! {
+        fn sum(data: &[u32]) -> u32 => [
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            "avx2" => Avx2,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            "avx2,bmi2" => Avx2 & Bmi2,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            "sse4.1" => Sse41,
+        ] {
+            data.iter().fold(0u32, |acc, x| acc.wrapping_add(*x))
+        }
+    }
+
+    multiversion! {
+        fn fill(data: &mut [u8], value: u8) => [
+            #[cfg(target_arch = "aarch64")]
+            "neon" => Neon,
+        ] {
+            for x in data.iter_mut() {
+                *x = value;
+            }
+        }
+    }
+
+    #[test]
+    fn multiversion_dispatches() {
+        let mut data = [0u32; 1000];
+        for (index, x) in data.iter_mut().enumerate() {
+            *x = index as u32;
+        }
+        // first call resolves, the second uses the stored pointer
+        assert_eq!(sum(&data), 499500);
+        assert_eq!(sum(&data), 499500);
+        assert_eq!(sum(&[]), 0);
+
+        let mut buffer = [0u8; 33];
+        fill(&mut buffer, 7);
+        assert!(buffer.iter().all(|x| *x == 7));
+    }
+}
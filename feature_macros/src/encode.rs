@@ -0,0 +1,186 @@
+use super::error::Error;
+use super::mem::size_of;
+use super::numbers::PrimativeNumber;
+
+/// Encoder appends fixed-width values to a caller supplied buffer.
+///
+/// Every `put_*` either writes the whole value and advances, or returns
+/// `Error::OutputFull` (with the offset of the value which did not fit)
+/// and leaves the buffer untouched, so a header is never half written
+/// past the point the caller observes.
+pub struct Encoder<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+}
+impl<'a> Encoder<'a> {
+    #[inline]
+    pub fn new(buffer: &'a mut [u8]) -> Encoder<'a> {
+        Encoder {
+            buffer,
+            position: 0,
+        }
+    }
+
+    /// number of bytes written so far
+    #[inline(always)]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// number of bytes which may still be written
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.position
+    }
+
+    /// the bytes written so far
+    #[inline(always)]
+    pub fn written(&self) -> &[u8] {
+        &self.buffer[..self.position]
+    }
+
+    /// the bytes written so far, for patching fields such as checksums
+    /// which cover data written after them
+    #[inline(always)]
+    pub fn written_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer[..self.position]
+    }
+
+    /// returns the space for the next `length` bytes and advances past it
+    #[inline(always)]
+    fn reserve(&mut self, length: usize) -> Result<&mut [u8], Error> {
+        if self.remaining() < length {
+            return Err(Error::OutputFull {
+                offset: self.position,
+            });
+        }
+        let start = self.position;
+        self.position += length;
+        Ok(&mut self.buffer[start..start + length])
+    }
+
+    /// writes `value` as little endian
+    #[inline]
+    pub fn put_le<N: PrimativeNumber>(&mut self, value: N) -> Result<(), Error> {
+        let space = self.reserve(size_of::<N::Primative>())?;
+        value.write_value_le(space);
+        Ok(())
+    }
+
+    /// writes `value` as big endian
+    #[inline]
+    pub fn put_be<N: PrimativeNumber>(&mut self, value: N) -> Result<(), Error> {
+        let space = self.reserve(size_of::<N::Primative>())?;
+        value.write_value_be(space);
+        Ok(())
+    }
+
+    /// writes the low `width` bytes of `value` as little endian, for
+    /// fields such as LZ4's 3 byte block sizes or zstd's variable width
+    /// frame content size
+    ///
+    /// Returns `Error::LimitExceeded` if `width` is over 8 or `value`
+    /// does not fit in `width` bytes, without writing anything.
+    #[inline]
+    pub fn put_le_width(&mut self, value: u64, width: usize) -> Result<(), Error> {
+        if width > 8 || (width < 8 && value >> (width * 8) != 0) {
+            return Err(Error::LimitExceeded {
+                offset: self.position,
+            });
+        }
+        let space = self.reserve(width)?;
+        for (index, byte) in space.iter_mut().enumerate() {
+            *byte = (value >> (index * 8)) as u8;
+        }
+        Ok(())
+    }
+
+    /// copies `bytes` verbatim
+    #[inline]
+    pub fn put_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.reserve(bytes.len())?.copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::error::Error;
+    use super::super::numbers::{Num, PrimativeNumber};
+    use super::Encoder;
+
+    #[test]
+    fn encodes_fixed_width() {
+        let mut buffer = [0u8; 16];
+        let mut dut = Encoder::new(&mut buffer);
+        dut.put_le(Num::from(0x184D2204u32)).unwrap();
+        dut.put_be(Num::from(0x0102u16)).unwrap();
+        dut.put_le_width(0x030201, 3).unwrap();
+        dut.put_bytes(b"ab").unwrap();
+        dut.put_le(Num::from(-2i8)).unwrap();
+        assert_eq!(dut.position(), 12);
+        assert_eq!(dut.remaining(), 4);
+        assert_eq!(
+            dut.written(),
+            &[0x04, 0x22, 0x4D, 0x18, 0x01, 0x02, 0x01, 0x02, 0x03, b'a', b'b', 0xFE]
+        );
+
+        // a checksum covering the header is patched in afterwards
+        Num::from(0xAABBu16).write_value_le_at(dut.written_mut(), 4);
+        assert_eq!(&dut.written()[4..6], &[0xBB, 0xAA]);
+    }
+
+    #[test]
+    fn reports_full_output_without_writing() {
+        let mut buffer = [0u8; 6];
+        {
+            let mut dut = Encoder::new(&mut buffer);
+            dut.put_le(Num::from(u32::MAX)).unwrap();
+            assert_eq!(
+                dut.put_le(Num::from(u32::MAX)),
+                Err(Error::OutputFull { offset: 4 })
+            );
+            assert_eq!(
+                dut.put_bytes(&[1, 2, 3]),
+                Err(Error::OutputFull { offset: 4 })
+            );
+            assert_eq!(dut.position(), 4);
+            dut.put_be(Num::from(0x0102u16)).unwrap();
+            assert_eq!(dut.put_le_width(0, 1), Err(Error::OutputFull { offset: 6 }));
+        }
+        assert_eq!(buffer, [0xFF, 0xFF, 0xFF, 0xFF, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn rejects_widths_over_eight() {
+        let mut buffer = [0u8; 16];
+        let mut dut = Encoder::new(&mut buffer);
+        dut.put_le_width(u64::MAX, 8).unwrap();
+        assert_eq!(
+            dut.put_le_width(0, 9),
+            Err(Error::LimitExceeded { offset: 8 })
+        );
+        assert_eq!(dut.position(), 8);
+    }
+
+    #[test]
+    fn rejects_values_wider_than_width() {
+        let mut buffer = [0u8; 4];
+        {
+            let mut dut = Encoder::new(&mut buffer);
+            dut.put_le_width(0xFF_FFFF, 3).unwrap();
+            assert_eq!(
+                dut.put_le_width(0x100, 1),
+                Err(Error::LimitExceeded { offset: 3 })
+            );
+            assert_eq!(
+                dut.put_le_width(1, 0),
+                Err(Error::LimitExceeded { offset: 3 })
+            );
+            dut.put_le_width(0, 0).unwrap();
+            assert_eq!(dut.position(), 3);
+        }
+        assert_eq!(buffer, [0xFF, 0xFF, 0xFF, 0x00]);
+    }
+}
@@ -0,0 +1,314 @@
+#[cfg(not(feature = "std"))]
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use core::hash::Hash;
+#[cfg(not(feature = "std"))]
+use core::slice::{from_raw_parts, from_raw_parts_mut};
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use std::hash::Hash;
+#[cfg(feature = "std")]
+use std::slice::{from_raw_parts, from_raw_parts_mut};
+
+use super::error::Error;
+use super::mem::size_of;
+use super::numbers::{Num, PrimativeNumber};
+
+/// Plain types have an alignment of 1, no padding, and every bit
+/// pattern is a valid value. They may be viewed in place within a
+/// byte slice.
+///
+/// # Safety
+///
+/// Implementing this for a type which does not meet the above is
+/// undefined behavior. Use `view!` to declare structures, it only
+/// implements this when every field is `Plain`.
+pub unsafe trait Plain: Copy {}
+
+unsafe impl Plain for u8 {}
+unsafe impl Plain for i8 {}
+
+macro_rules! implement_plain_array {
+    ($($length: expr),*) => {
+        $(unsafe impl Plain for [u8; $length] {})*
+    };
+}
+implement_plain_array!(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 32);
+
+/// Wire names the byte array a primative is stored as.
+pub trait Wire: Copy {
+    type Bytes: Plain + Default + PartialEq + Eq + Hash + AsRef<[u8]> + AsMut<[u8]>;
+}
+
+/// Le is a little endian value stored as bytes, so it has no alignment
+/// requirement and may be a field of a `view!` structure.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Le<T: Wire> {
+    bytes: T::Bytes,
+}
+
+/// Be is a big endian value stored as bytes, so it has no alignment
+/// requirement and may be a field of a `view!` structure.
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Be<T: Wire> {
+    bytes: T::Bytes,
+}
+
+unsafe impl<T: Wire> Plain for Le<T> {}
+unsafe impl<T: Wire> Plain for Be<T> {}
+
+macro_rules! implement_endian {
+    ($wrapper: ident, $kind: ident, $read: ident, $write: ident) => {
+        impl $wrapper<$kind> {
+            /// decodes the stored value
+            #[inline(always)]
+            pub fn get(&self) -> Num<$kind> {
+                Num::<$kind>::$read(self.bytes.as_ref())
+            }
+
+            /// encodes `value` in place
+            #[inline(always)]
+            pub fn set(&mut self, value: Num<$kind>) {
+                value.$write(self.bytes.as_mut())
+            }
+        }
+        impl From<Num<$kind>> for $wrapper<$kind> {
+            #[inline(always)]
+            fn from(value: Num<$kind>) -> $wrapper<$kind> {
+                let mut output = $wrapper::<$kind>::default();
+                output.set(value);
+                output
+            }
+        }
+        impl From<$kind> for $wrapper<$kind> {
+            #[inline(always)]
+            fn from(value: $kind) -> $wrapper<$kind> {
+                $wrapper::<$kind>::from(Num::from(value))
+            }
+        }
+        impl From<$wrapper<$kind>> for Num<$kind> {
+            #[inline(always)]
+            fn from(value: $wrapper<$kind>) -> Num<$kind> {
+                value.get()
+            }
+        }
+        impl fmt::Debug for $wrapper<$kind> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_tuple(stringify!($wrapper))
+                    .field(&self.get())
+                    .finish()
+            }
+        }
+    };
+}
+
+macro_rules! implement_wire {
+    ($kind: ident, $length: expr) => {
+        impl Wire for $kind {
+            type Bytes = [u8; $length];
+        }
+        implement_endian!(Le, $kind, read_value_le, write_value_le);
+        implement_endian!(Be, $kind, read_value_be, write_value_be);
+    };
+}
+
+implement_wire!(u16, 2);
+implement_wire!(i16, 2);
+implement_wire!(u32, 4);
+implement_wire!(i32, 4);
+implement_wire!(u64, 8);
+implement_wire!(i64, 8);
+#[cfg(HAS_U128)]
+implement_wire!(u128, 16);
+#[cfg(HAS_U128)]
+implement_wire!(i128, 16);
+
+/// views the start of `bytes` as a `T` in place.
+///
+/// Returns `Error::Truncated` (relative to `bytes`) when it is too short.
+#[inline]
+pub fn view<T: Plain>(bytes: &[u8]) -> Result<&T, Error> {
+    if bytes.len() < size_of::<T>() {
+        return Err(Error::Truncated {
+            offset: bytes.len(),
+        });
+    }
+    // `Plain` guarantees an alignment of 1 and no invalid bit patterns
+    Ok(unsafe { &*(bytes.as_ptr() as *const T) })
+}
+
+/// mutable form of `view`, returns `Error::OutputFull` when `bytes` is
+/// too short as it is used to write structures out.
+#[inline]
+pub fn view_mut<T: Plain>(bytes: &mut [u8]) -> Result<&mut T, Error> {
+    if bytes.len() < size_of::<T>() {
+        return Err(Error::OutputFull {
+            offset: bytes.len(),
+        });
+    }
+    Ok(unsafe { &mut *(bytes.as_mut_ptr() as *mut T) })
+}
+
+/// the bytes a `Plain` value is stored as
+#[inline]
+pub fn as_bytes<T: Plain>(value: &T) -> &[u8] {
+    unsafe { from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+}
+
+/// mutable form of `as_bytes`
+#[inline]
+pub fn as_bytes_mut<T: Plain>(value: &mut T) -> &mut [u8] {
+    unsafe { from_raw_parts_mut(value as *mut T as *mut u8, size_of::<T>()) }
+}
+
+/// view declares a structure describing an on-disk layout, which can be
+/// read in place with `endian::view`.
+///
+/// Every field must be `Plain` (`u8`, `i8`, byte arrays, `Le<T>`, `Be<T>`
+/// or another `view!` structure), which is checked at compile time.
+///
+/// ```
+/// #[macro_use]
+/// extern crate feature_macros;
+///
+/// use feature_macros::endian::{view, Be, Le};
+/// use feature_macros::numbers::PrimativeNumber;
+///
+/// view! {
+///     /// a record with a mixed endian layout
+///     pub struct Record {
+///         pub tag: u8,
+///         pub length: Le<u32>,
+///         pub id: Be<u16>,
+///     }
+/// }
+///
+/// fn main() {
+///     let bytes = [7u8, 0x10, 0, 0, 0, 0x01, 0x02];
+///     let record = view::<Record>(&bytes).unwrap();
+///     assert_eq!(record.tag, 7);
+///     assert_eq!(record.length.get().inner(), 16);
+///     assert_eq!(record.id.get().inner(), 0x0102);
+///     assert_eq!(Record::SIZE, 7);
+/// }
+/// ```
+#[macro_export]
+macro_rules! view {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $( $(#[$field_meta:meta])* $field_vis:vis $field:ident : $ty:ty ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        $vis struct $name {
+            $( $(#[$field_meta])* $field_vis $field : $ty ),*
+        }
+        impl $name {
+            /// size of the structure in bytes
+            #[allow(dead_code)]
+            pub const SIZE: usize = $crate::mem::size_of::<$name>();
+        }
+        // `repr(C)` with only `Plain` fields has no padding and an
+        // alignment of 1, a non-`Plain` field fails this bound
+        unsafe impl $crate::endian::Plain for $name where $( $ty : $crate::endian::Plain ),* {}
+    };
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::error::Error;
+    use super::super::mem::{align_of, size_of};
+    use super::super::numbers::{Num, PrimativeNumber};
+    use super::{as_bytes, as_bytes_mut, view, view_mut, Be, Le};
+
+    view! {
+        struct Inner {
+            flags: u8,
+            size: Be<u16>,
+        }
+    }
+
+    view! {
+        /// nesting and every width
+        struct Header {
+            magic: Le<u32>,
+            inner: Inner,
+            wide: Le<u64>,
+            signed: Be<i32>,
+            raw: [u8; 3],
+        }
+    }
+
+    #[test]
+    fn wrappers_have_no_alignment() {
+        assert_eq!(align_of::<Le<u64>>(), 1);
+        assert_eq!(align_of::<Be<u32>>(), 1);
+        assert_eq!(size_of::<Le<u64>>(), 8);
+        assert_eq!(align_of::<Header>(), 1);
+        assert_eq!(Header::SIZE, 4 + 3 + 8 + 4 + 3);
+        assert_eq!(Inner::SIZE, 3);
+    }
+
+    #[test]
+    fn encodes_both_endians() {
+        let le = Le::<u32>::from(0x01020304u32);
+        let be = Be::<u32>::from(0x01020304u32);
+        assert_eq!(as_bytes(&le), &[4, 3, 2, 1]);
+        assert_eq!(as_bytes(&be), &[1, 2, 3, 4]);
+        assert_eq!(le.get(), Num::from(0x01020304u32));
+        assert_eq!(Num::<u32>::from(be), Num::from(0x01020304u32));
+        assert_eq!(Be::<i16>::from(-2i16).get(), Num::from(-2i16));
+    }
+
+    #[test]
+    fn views_in_place_at_any_alignment() {
+        let mut buffer = [0u8; 64];
+        for offset in 0..=(buffer.len() - Header::SIZE) {
+            {
+                let header = view_mut::<Header>(&mut buffer[offset..]).unwrap();
+                header.magic.set(Num::from(0x184D2204u32));
+                header.inner.flags = 0x60;
+                header.inner.size.set(Num::from(0xABCDu16));
+                header.wide = Le::from(u64::MAX - 1);
+                header.signed = Be::from(-7i32);
+                header.raw = [1, 2, 3];
+            }
+            let bytes = &buffer[offset..];
+            assert_eq!(&bytes[0..4], &[0x04, 0x22, 0x4D, 0x18]);
+            assert_eq!(&bytes[4..7], &[0x60, 0xAB, 0xCD]);
+
+            let header = view::<Header>(bytes).unwrap();
+            assert_eq!(header.magic.get().inner(), 0x184D2204);
+            assert_eq!(header.inner.size.get().inner(), 0xABCD);
+            assert_eq!(header.wide.get().inner(), u64::MAX - 1);
+            assert_eq!(header.signed.get().inner(), -7);
+            assert_eq!(header.raw, [1, 2, 3]);
+            assert_eq!(as_bytes(header), &bytes[..Header::SIZE]);
+        }
+    }
+
+    #[test]
+    fn short_input_is_reported() {
+        let mut buffer = [0u8; 6];
+        assert_eq!(
+            view::<Header>(&buffer).map(|x| x.inner.flags),
+            Err(Error::Truncated { offset: 6 })
+        );
+        assert_eq!(
+            view_mut::<Header>(&mut buffer).map(|x| x.inner.flags),
+            Err(Error::OutputFull { offset: 6 })
+        );
+        assert!(view::<Le<u32>>(&buffer[2..]).is_ok());
+
+        let mut value = Le::<u16>::default();
+        as_bytes_mut(&mut value).copy_from_slice(&[0x34, 0x12]);
+        assert_eq!(value.get().inner(), 0x1234);
+    }
+}
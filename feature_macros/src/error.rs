@@ -0,0 +1,154 @@
+#[cfg(not(feature = "std"))]
+use core::fmt;
+#[cfg(feature = "std")]
+use std::fmt;
+
+/// Error is returned by decoders when their input cannot be processed,
+/// and by encoders when their output cannot hold the result.
+///
+/// Every variant records the byte offset where processing stopped, so
+/// corruption can be located within large files.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Error {
+    /// the input did not start with a recognized magic number
+    BadMagic { offset: usize },
+    /// a stored checksum did not match the one computed from the data
+    ChecksumMismatch {
+        offset: usize,
+        expected: u64,
+        actual: u64,
+    },
+    /// the input ended before a complete structure could be read
+    Truncated { offset: usize },
+    /// a block's contents are internally inconsistent
+    CorruptBlock { offset: usize },
+    /// decoding would exceed a configured resource limit
+    LimitExceeded { offset: usize },
+    /// the input uses a feature this decoder does not implement
+    Unsupported { offset: usize },
+    /// the output buffer was too small, `offset` is where writing stopped
+    OutputFull { offset: usize },
+}
+impl Error {
+    /// the byte offset where decoding failed, or where encoding ran out of
+    /// output
+    pub fn offset(&self) -> usize {
+        match *self {
+            Error::BadMagic { offset }
+            | Error::ChecksumMismatch { offset, .. }
+            | Error::Truncated { offset }
+            | Error::CorruptBlock { offset }
+            | Error::LimitExceeded { offset }
+            | Error::Unsupported { offset }
+            | Error::OutputFull { offset } => offset,
+        }
+    }
+
+    /// shifts the offset by `base`.
+    ///
+    /// Parsers working on a sub-slice report offsets relative to that
+    /// slice, this converts them into offsets within the whole buffer.
+    pub fn rebase(self, base: usize) -> Error {
+        match self {
+            Error::BadMagic { offset } => Error::BadMagic {
+                offset: offset + base,
+            },
+            Error::ChecksumMismatch {
+                offset,
+                expected,
+                actual,
+            } => Error::ChecksumMismatch {
+                offset: offset + base,
+                expected,
+                actual,
+            },
+            Error::Truncated { offset } => Error::Truncated {
+                offset: offset + base,
+            },
+            Error::CorruptBlock { offset } => Error::CorruptBlock {
+                offset: offset + base,
+            },
+            Error::LimitExceeded { offset } => Error::LimitExceeded {
+                offset: offset + base,
+            },
+            Error::Unsupported { offset } => Error::Unsupported {
+                offset: offset + base,
+            },
+            Error::OutputFull { offset } => Error::OutputFull {
+                offset: offset + base,
+            },
+        }
+    }
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::BadMagic { offset } => write!(f, "bad magic number at offset {}", offset),
+            Error::ChecksumMismatch {
+                offset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "checksum mismatch at offset {}, expected {:#x} found {:#x}",
+                offset, expected, actual
+            ),
+            Error::Truncated { offset } => write!(f, "input truncated at offset {}", offset),
+            Error::CorruptBlock { offset } => write!(f, "corrupt block at offset {}", offset),
+            Error::LimitExceeded { offset } => {
+                write!(f, "resource limit exceeded at offset {}", offset)
+            }
+            Error::Unsupported { offset } => {
+                write!(f, "unsupported feature at offset {}", offset)
+            }
+            Error::OutputFull { offset } => write!(f, "output full at offset {}", offset),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+
+    use super::Error;
+
+    #[test]
+    fn rebase_shifts_every_variant() {
+        let errors = [
+            Error::BadMagic { offset: 1 },
+            Error::ChecksumMismatch {
+                offset: 1,
+                expected: 2,
+                actual: 3,
+            },
+            Error::Truncated { offset: 1 },
+            Error::CorruptBlock { offset: 1 },
+            Error::LimitExceeded { offset: 1 },
+            Error::Unsupported { offset: 1 },
+            Error::OutputFull { offset: 1 },
+        ];
+        for error in errors.iter() {
+            assert_eq!(error.offset(), 1);
+            assert_eq!(error.rebase(10).offset(), 11);
+        }
+    }
+
+    #[test]
+    fn display_includes_offset() {
+        let error = Error::ChecksumMismatch {
+            offset: 4096,
+            expected: 0xDEAD,
+            actual: 0xBEEF,
+        };
+        assert_eq!(
+            error.to_string(),
+            "checksum mismatch at offset 4096, expected 0xdead found 0xbeef"
+        );
+        assert_eq!(
+            Error::Truncated { offset: 7 }.to_string(),
+            "input truncated at offset 7"
+        );
+    }
+}
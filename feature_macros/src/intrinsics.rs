@@ -1,48 +1,50 @@
-#[cfg(not(feature = "std"))]
-use core::intrinsics::copy_nonoverlapping;
-#[cfg(all(
-    not(feature = "std"),
-    feature = "RUSTC_NIGHTLY",
-    feature = "branch_hints"
-))]
+#[cfg(all(not(feature = "std"), HAS_LIKELY_INTRINSICS, feature = "branch_hints"))]
 use core::intrinsics::{likely, unlikely};
+#[cfg(not(feature = "std"))]
+use core::ptr::copy_nonoverlapping;
 
-#[cfg(feature = "std")]
-use std::intrinsics::copy_nonoverlapping;
-#[cfg(all(feature = "std", feature = "RUSTC_NIGHTLY", feature = "branch_hints"))]
+#[cfg(all(feature = "std", HAS_LIKELY_INTRINSICS, feature = "branch_hints"))]
 use std::intrinsics::{likely, unlikely};
+#[cfg(feature = "std")]
+use std::ptr::copy_nonoverlapping;
 
+/// copies `len` items from `src` to `dst`
+///
+/// # Safety
+///
+/// The same requirements as `copy_nonoverlapping`, both regions
+/// must be valid for `len` items and they may not overlap.
 #[allow(dead_code)]
 #[inline(always)]
 pub unsafe fn memcp<T: Sized>(src: *const T, dst: *mut T, len: usize) {
     copy_nonoverlapping(src, dst, len);
 }
 
-/// this will emit a branch hint if you're on nightly & use the `branch_hints` feature
+/// this will emit a branch hint if your compiler supports it & you use the `branch_hints` feature
 #[allow(dead_code)]
 #[inline(always)]
 pub fn hint_likely(b: bool) -> bool {
-    #[cfg(all(feature = "RUSTC_NIGHTLY", feature = "branch_hints"))]
+    #[cfg(all(HAS_LIKELY_INTRINSICS, feature = "branch_hints"))]
     {
         likely(b)
     }
 
-    #[cfg(any(not(feature = "RUSTC_NIGHTLY"), not(feature = "branch_hints")))]
+    #[cfg(not(all(HAS_LIKELY_INTRINSICS, feature = "branch_hints")))]
     {
         b
     }
 }
 
-/// this will emit a branch hint if you're on nightly & use the `branch_hints` feature
+/// this will emit a branch hint if your compiler supports it & you use the `branch_hints` feature
 #[allow(dead_code)]
 #[inline(always)]
 pub fn hint_unlikely(b: bool) -> bool {
-    #[cfg(all(feature = "RUSTC_NIGHTLY", feature = "branch_hints"))]
+    #[cfg(all(HAS_LIKELY_INTRINSICS, feature = "branch_hints"))]
     {
         unlikely(b)
     }
 
-    #[cfg(any(not(feature = "RUSTC_NIGHTLY"), not(feature = "branch_hints")))]
+    #[cfg(not(all(HAS_LIKELY_INTRINSICS, feature = "branch_hints")))]
     {
         b
     }
@@ -1,5 +1,6 @@
 #![cfg_attr(not(feature = "std"), no_std)]
-#![cfg_attr(feature = "RUSTC_NIGHTLY", feature(core_intrinsics))]
+#![cfg_attr(HAS_CORE_INTRINSICS, allow(internal_features))]
+#![cfg_attr(HAS_CORE_INTRINSICS, feature(core_intrinsics))]
 
 /// inconceivable is a macro which closely parallels `std::unreachable`, or `std::panic`.
 ///
@@ -13,22 +14,26 @@
 /// offers the "best of both worlds", it provides a solid
 /// way of asserting/testing behavior in debug builds, and
 /// no cost in properly configured release builds.
+///
+/// As this expands within the calling crate, the caller must
+/// emit the `HAS_UNREACHABLE_UNCHECKED` cfg itself. See the
+/// `README.md` for how to forward it from a `build.rs`.
 #[macro_export]
 macro_rules! inconceivable {
     () => {
         {
-        #[cfg(all(not(feature="std"), feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27"))]
+        #[cfg(all(not(feature="std"), feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED))]
         {
             unsafe{ ::core::hint::unreachable_unchecked() }
         }
 
-        #[cfg(all(feature = "std", feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27"))]
+        #[cfg(all(feature = "std", feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED))]
         {
             unsafe{ ::std::hint::unreachable_unchecked() }
         }
 
 
-        #[cfg(not(all(feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27")))]
+        #[cfg(not(all(feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED)))]
         {
             unreachable!()
         }
@@ -36,18 +41,18 @@ macro_rules! inconceivable {
     };
     ($msg: expr) => {
         {
-        #[cfg(all(not(feature="std"), feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27"))]
+        #[cfg(all(not(feature="std"), feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED))]
         {
             unsafe{ ::core::hint::unreachable_unchecked() }
         }
 
-        #[cfg(all(feature = "std", feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27"))]
+        #[cfg(all(feature = "std", feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED))]
         {
             unsafe{ ::std::hint::unreachable_unchecked() }
         }
 
 
-        #[cfg(not(all(feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27")))]
+        #[cfg(not(all(feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED)))]
         {
             unreachable!($msg)
         }
@@ -55,18 +60,18 @@ macro_rules! inconceivable {
     };
     ($msg: expr,) => {
         {
-        #[cfg(all(not(feature="std"), feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27"))]
+        #[cfg(all(not(feature="std"), feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED))]
         {
             unsafe{ ::core::hint::unreachable_unchecked() }
         }
 
-        #[cfg(all(feature = "std", feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27"))]
+        #[cfg(all(feature = "std", feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED))]
         {
             unsafe{ ::std::hint::unreachable_unchecked() }
         }
 
 
-        #[cfg(not(all(feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27")))]
+        #[cfg(not(all(feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED)))]
         {
             unreachable!($msg)
         }
@@ -74,17 +79,17 @@ macro_rules! inconceivable {
     };
     ($fmt: expr, $($arg:tt)*) => {
         {
-        #[cfg(all(not(feature="std"), feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27"))]
+        #[cfg(all(not(feature="std"), feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED))]
         {
             unsafe{ ::core::hint::unreachable_unchecked() }
         }
 
-        #[cfg(all(feature = "std", feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27"))]
+        #[cfg(all(feature = "std", feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED))]
         {
             unsafe{ ::std::hint::unreachable_unchecked() }
         }
 
-        #[cfg(not(all(feature = "ub_unreachable", feature = "RUSTC_VERSION_GE_1_27")))]
+        #[cfg(not(all(feature = "ub_unreachable", HAS_UNREACHABLE_UNCHECKED)))]
         {
             unreachable!($fmt, $($arg)*)
         }
@@ -95,6 +100,17 @@ macro_rules! inconceivable {
 /// numbers concerns dereferencing values on different platforms
 pub mod numbers;
 
+/// encode writes fixed-width values into output buffers.
+pub mod encode;
+
+/// endian provides byte order typed integers and in place views of
+/// on-disk structures.
+#[macro_use]
+pub mod endian;
+
+/// varint encodes integers in as few bytes as their magnitude needs.
+pub mod varint;
+
 /// intrinsics handle a lot of the semantics of branch hinting
 pub mod intrinsics;
 
@@ -103,3 +119,62 @@ pub mod prefetch;
 
 /// mem handles the normal `std::`/`core::` imports.
 pub mod mem;
+
+/// error is the failure type shared by decoders.
+pub mod error;
+
+/// limits bounds what decoders may spend on untrusted input.
+pub mod limits;
+
+/// cpu detects instruction set extensions at runtime and
+/// dispatches between `#[target_feature]` compiled kernels.
+#[macro_use]
+pub mod cpu;
+
+/// cache reports the cache topology, for sizing tables and blocks.
+pub mod cache;
+
+#[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
+mod test {
+
+    use super::intrinsics::{hint_likely, hint_unlikely};
+
+    /// every compiler this workspace supports has these, if they are
+    /// missing the probes in `build.rs` are broken.
+    #[test]
+    fn stable_capabilities_are_detected() {
+        assert!(cfg!(HAS_UNREACHABLE_UNCHECKED));
+        assert!(cfg!(HAS_U128));
+        assert!(cfg!(HAS_CONST_FN));
+        if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+            assert!(cfg!(HAS_CORE_ARCH));
+        }
+        if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+            assert!(cfg!(HAS_ASM));
+        }
+    }
+
+    /// the intrinsic probes all require the `core_intrinsics` gate
+    #[test]
+    fn intrinsic_capabilities_are_consistent() {
+        if cfg!(HAS_LIKELY_INTRINSICS) || cfg!(HAS_PREFETCH_INTRINSICS) {
+            assert!(cfg!(HAS_CORE_INTRINSICS));
+        }
+    }
+
+    #[test]
+    fn branch_hints_preserve_value() {
+        assert!(hint_likely(true));
+        assert!(!hint_likely(false));
+        assert!(hint_unlikely(true));
+        assert!(!hint_unlikely(false));
+    }
+
+    #[cfg(not(feature = "ub_unreachable"))]
+    #[test]
+    #[should_panic(expected = "entered unreachable code: checked")]
+    fn inconceivable_is_unreachable_by_default() {
+        inconceivable!("checked");
+    }
+}
@@ -0,0 +1,153 @@
+use super::error::Error;
+
+/// Limits bounds the resources a decoder may spend on one input, so a
+/// small hostile input cannot claim a huge output, window or allocation.
+///
+/// Decoders which take a size from their input take `Limits` through a
+/// `*_with_limits` variant, the plain form applies `Limits::default()`.
+/// Claimed sizes are checked before anything is allocated for them, and
+/// exceeding any limit is reported as `Error::LimitExceeded` at the
+/// offset of the field (or data) which exceeded it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Limits {
+    /// the most bytes a decoder may output
+    pub max_output: u64,
+    /// the largest window (how far back matches reach) a frame may need
+    pub max_window: u64,
+    /// the most bytes a decoder may buffer, input and output together
+    pub max_memory: u64,
+    /// the most frames (or members) a stream may hold
+    pub max_frames: u64,
+    /// the most bytes of output per byte of input
+    pub max_ratio: u64,
+}
+impl Default for Limits {
+    /// 1GiB of output, zstd's default 128MiB window, 256MiB of memory,
+    /// 2^20 frames, and a ratio of 2048, which is above what any format
+    /// here can reach from valid data (deflate's is 1032)
+    fn default() -> Limits {
+        Limits {
+            max_output: 1 << 30,
+            max_window: 1 << 27,
+            max_memory: 1 << 28,
+            max_frames: 1 << 20,
+            max_ratio: 1 << 11,
+        }
+    }
+}
+impl Limits {
+    #[inline]
+    pub fn new() -> Limits {
+        Limits::default()
+    }
+
+    /// no limits at all, for trusted input
+    #[inline]
+    pub fn unlimited() -> Limits {
+        Limits {
+            max_output: u64::MAX,
+            max_window: u64::MAX,
+            max_memory: u64::MAX,
+            max_frames: u64::MAX,
+            max_ratio: u64::MAX,
+        }
+    }
+
+    /// checks an output of `length` bytes
+    #[inline]
+    pub fn check_output(&self, length: u64, offset: usize) -> Result<(), Error> {
+        check(length <= self.max_output, offset)
+    }
+
+    /// checks a window of `size` bytes
+    #[inline]
+    pub fn check_window(&self, size: u64, offset: usize) -> Result<(), Error> {
+        check(size <= self.max_window, offset)
+    }
+
+    /// checks buffering `size` bytes
+    #[inline]
+    pub fn check_memory(&self, size: u64, offset: usize) -> Result<(), Error> {
+        check(size <= self.max_memory, offset)
+    }
+
+    /// checks a stream of `count` frames
+    #[inline]
+    pub fn check_frames(&self, count: u64, offset: usize) -> Result<(), Error> {
+        check(count <= self.max_frames, offset)
+    }
+
+    /// checks `output` bytes decoded from `input` bytes
+    #[inline]
+    pub fn check_ratio(&self, output: u64, input: u64, offset: usize) -> Result<(), Error> {
+        check(output <= self.max_ratio.saturating_mul(input), offset)
+    }
+}
+
+#[inline(always)]
+fn check(within: bool, offset: usize) -> Result<(), Error> {
+    if within {
+        Ok(())
+    } else {
+        Err(Error::LimitExceeded { offset })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::error::Error;
+    use super::Limits;
+
+    #[test]
+    fn checks_each_limit() {
+        let dut = Limits {
+            max_output: 100,
+            max_window: 200,
+            max_memory: 300,
+            max_frames: 4,
+            max_ratio: 10,
+        };
+        assert_eq!(dut.check_output(100, 1), Ok(()));
+        assert_eq!(
+            dut.check_output(101, 1),
+            Err(Error::LimitExceeded { offset: 1 })
+        );
+        assert_eq!(dut.check_window(200, 2), Ok(()));
+        assert_eq!(
+            dut.check_window(201, 2),
+            Err(Error::LimitExceeded { offset: 2 })
+        );
+        assert_eq!(dut.check_memory(300, 3), Ok(()));
+        assert_eq!(
+            dut.check_memory(301, 3),
+            Err(Error::LimitExceeded { offset: 3 })
+        );
+        assert_eq!(dut.check_frames(4, 4), Ok(()));
+        assert_eq!(
+            dut.check_frames(5, 4),
+            Err(Error::LimitExceeded { offset: 4 })
+        );
+        assert_eq!(dut.check_ratio(50, 5, 5), Ok(()));
+        assert_eq!(
+            dut.check_ratio(51, 5, 5),
+            Err(Error::LimitExceeded { offset: 5 })
+        );
+        // nothing can come from nothing
+        assert_eq!(
+            dut.check_ratio(1, 0, 6),
+            Err(Error::LimitExceeded { offset: 6 })
+        );
+    }
+
+    #[test]
+    fn unlimited_accepts_anything() {
+        let dut = Limits::unlimited();
+        assert_eq!(dut.check_output(u64::MAX, 0), Ok(()));
+        assert_eq!(dut.check_window(u64::MAX, 0), Ok(()));
+        assert_eq!(dut.check_memory(u64::MAX, 0), Ok(()));
+        assert_eq!(dut.check_frames(u64::MAX, 0), Ok(()));
+        assert_eq!(dut.check_ratio(u64::MAX, 1, 0), Ok(()));
+        assert_eq!(Limits::new(), Limits::default());
+    }
+}
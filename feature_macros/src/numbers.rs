@@ -1,26 +1,35 @@
 #[cfg(not(feature = "std"))]
+use core::fmt;
+#[cfg(not(feature = "std"))]
 use core::hash::Hash;
 #[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
 use std::hash::Hash;
 
 #[cfg(not(feature = "std"))]
 use core::ops::{
-    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, ShlAssign, Shr,
-    ShrAssign,
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign,
+    Mul, MulAssign, Neg, Not, Rem, RemAssign, Shl, ShlAssign, Shr, ShrAssign, Sub, SubAssign,
 };
 #[cfg(not(feature = "std"))]
-use core::ptr::{read, read_unaligned};
+use core::ptr::{read, read_unaligned, write, write_unaligned};
 #[cfg(feature = "std")]
 use std::ops::{
-    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, ShlAssign, Shr,
-    ShrAssign,
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign,
+    Mul, MulAssign, Neg, Not, Rem, RemAssign, Shl, ShlAssign, Shr, ShrAssign, Sub, SubAssign,
 };
 #[cfg(feature = "std")]
-use std::ptr::{read, read_unaligned};
+use std::ptr::{read, read_unaligned, write, write_unaligned};
 
-use super::mem::{align_of, size_of, transmute};
+use super::error::Error;
+use super::mem::{align_of, size_of};
 
 /// Num wraps a primative value.
+///
+/// Arithmetic operators wrap on overflow, as `std::num::Wrapping` does.
+/// Shifts and rotates take a `u32`, as the primatives' own methods do,
+/// and shifts mask the count to the width rather than panicking.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct Num<T>
 where
@@ -32,8 +41,8 @@ where
         + Ord
         + Hash
         + Default
-        + Shr<i32, Output = T>
-        + Shl<i32, Output = T>
+        + Shr<u32, Output = T>
+        + Shl<u32, Output = T>
         + BitXor<T, Output = T>
         + BitAnd<T, Output = T>
         + BitOr<T, Output = T>
@@ -41,8 +50,8 @@ where
         + BitAndAssign
         + BitOrAssign
         + BitXorAssign
-        + ShrAssign<i32>
-        + ShlAssign<i32>,
+        + ShrAssign<u32>
+        + ShlAssign<u32>,
 {
     data: T,
 }
@@ -57,8 +66,8 @@ where
         + Ord
         + Hash
         + Default
-        + Shr<i32, Output = T>
-        + Shl<i32, Output = T>
+        + Shr<u32, Output = T>
+        + Shl<u32, Output = T>
         + BitXor<T, Output = T>
         + BitAnd<T, Output = T>
         + BitOr<T, Output = T>
@@ -66,12 +75,12 @@ where
         + BitAndAssign
         + BitOrAssign
         + BitXorAssign
-        + ShrAssign<i32>
-        + ShlAssign<i32>,
+        + ShrAssign<u32>
+        + ShlAssign<u32>,
 {
     #[inline(always)]
     fn from(data: T) -> Num<T> {
-        Num { data: data }
+        Num { data }
     }
 }
 impl<T> From<&T> for Num<T>
@@ -84,8 +93,8 @@ where
         + Ord
         + Hash
         + Default
-        + Shr<i32, Output = T>
-        + Shl<i32, Output = T>
+        + Shr<u32, Output = T>
+        + Shl<u32, Output = T>
         + BitXor<T, Output = T>
         + BitAnd<T, Output = T>
         + BitOr<T, Output = T>
@@ -93,8 +102,8 @@ where
         + BitAndAssign
         + BitOrAssign
         + BitXorAssign
-        + ShrAssign<i32>
-        + ShlAssign<i32>,
+        + ShrAssign<u32>
+        + ShlAssign<u32>,
 {
     #[inline(always)]
     fn from(data: &T) -> Num<T> {
@@ -112,8 +121,8 @@ where
         + Ord
         + Hash
         + Default
-        + Shr<i32, Output = T>
-        + Shl<i32, Output = T>
+        + Shr<u32, Output = T>
+        + Shl<u32, Output = T>
         + BitXor<T, Output = T>
         + BitAnd<T, Output = T>
         + BitOr<T, Output = T>
@@ -121,8 +130,8 @@ where
         + BitAndAssign
         + BitOrAssign
         + BitXorAssign
-        + ShrAssign<i32>
-        + ShlAssign<i32>,
+        + ShrAssign<u32>
+        + ShlAssign<u32>,
 {
     #[inline(always)]
     fn from(data: &Num<T>) -> Num<T> {
@@ -141,8 +150,8 @@ where
         + Ord
         + Hash
         + Default
-        + Shr<i32, Output = T>
-        + Shl<i32, Output = T>
+        + Shr<u32, Output = T>
+        + Shl<u32, Output = T>
         + BitXor<T, Output = T>
         + BitAnd<T, Output = T>
         + BitOr<T, Output = T>
@@ -150,8 +159,8 @@ where
         + BitAndAssign
         + BitOrAssign
         + BitXorAssign
-        + ShrAssign<i32>
-        + ShlAssign<i32>,
+        + ShrAssign<u32>
+        + ShlAssign<u32>,
 {
     #[inline(always)]
     fn from(data: &&T) -> Num<T> {
@@ -169,8 +178,8 @@ where
         + Ord
         + Hash
         + Default
-        + Shr<i32, Output = T>
-        + Shl<i32, Output = T>
+        + Shr<u32, Output = T>
+        + Shl<u32, Output = T>
         + BitXor<T, Output = T>
         + BitAnd<T, Output = T>
         + BitOr<T, Output = T>
@@ -178,8 +187,8 @@ where
         + BitAndAssign
         + BitOrAssign
         + BitXorAssign
-        + ShrAssign<i32>
-        + ShlAssign<i32>,
+        + ShrAssign<u32>
+        + ShlAssign<u32>,
 {
     #[inline(always)]
     fn from(data: &&Num<T>) -> Num<T> {
@@ -201,8 +210,8 @@ macro_rules! implement_num_core_op_trait {
                 + Ord
                 + Hash
                 + Default
-                + Shr<i32, Output = T>
-                + Shl<i32, Output = T>
+                + Shr<u32, Output = T>
+                + Shl<u32, Output = T>
                 + BitXor<T, Output = T>
                 + BitAnd<T, Output = T>
                 + BitOr<T, Output = T>
@@ -210,8 +219,8 @@ macro_rules! implement_num_core_op_trait {
                 + BitAndAssign
                 + BitOrAssign
                 + BitXorAssign
-                + ShrAssign<i32>
-                + ShlAssign<i32>,
+                + ShrAssign<u32>
+                + ShlAssign<u32>,
         {
             type Output = Self;
 
@@ -230,8 +239,8 @@ macro_rules! implement_num_core_op_trait {
                 + Ord
                 + Hash
                 + Default
-                + Shr<i32, Output = T>
-                + Shl<i32, Output = T>
+                + Shr<u32, Output = T>
+                + Shl<u32, Output = T>
                 + BitXor<T, Output = T>
                 + BitAnd<T, Output = T>
                 + BitOr<T, Output = T>
@@ -239,8 +248,8 @@ macro_rules! implement_num_core_op_trait {
                 + BitAndAssign
                 + BitOrAssign
                 + BitXorAssign
-                + ShrAssign<i32>
-                + ShlAssign<i32>,
+                + ShrAssign<u32>
+                + ShlAssign<u32>,
         {
             type Output = Self;
 
@@ -260,8 +269,8 @@ macro_rules! implement_num_core_op_trait {
                 + Ord
                 + Hash
                 + Default
-                + Shr<i32, Output = T>
-                + Shl<i32, Output = T>
+                + Shr<u32, Output = T>
+                + Shl<u32, Output = T>
                 + BitXor<T, Output = T>
                 + BitAnd<T, Output = T>
                 + BitOr<T, Output = T>
@@ -269,8 +278,8 @@ macro_rules! implement_num_core_op_trait {
                 + BitAndAssign
                 + BitOrAssign
                 + BitXorAssign
-                + ShrAssign<i32>
-                + ShlAssign<i32>,
+                + ShrAssign<u32>
+                + ShlAssign<u32>,
         {
             #[inline(always)]
             fn $func_asg(&mut self, other: Self) {
@@ -287,8 +296,8 @@ macro_rules! implement_num_core_op_trait {
                 + Ord
                 + Hash
                 + Default
-                + Shr<i32, Output = T>
-                + Shl<i32, Output = T>
+                + Shr<u32, Output = T>
+                + Shl<u32, Output = T>
                 + BitXor<T, Output = T>
                 + BitAnd<T, Output = T>
                 + BitOr<T, Output = T>
@@ -296,8 +305,8 @@ macro_rules! implement_num_core_op_trait {
                 + BitAndAssign
                 + BitOrAssign
                 + BitXorAssign
-                + ShrAssign<i32>
-                + ShlAssign<i32>,
+                + ShrAssign<u32>
+                + ShlAssign<u32>,
         {
             #[inline(always)]
             fn $func_asg(&mut self, other: T) {
@@ -307,120 +316,6 @@ macro_rules! implement_num_core_op_trait {
     };
 }
 
-impl<T> Shr<i32> for Num<T>
-where
-    T: Copy
-        + Clone
-        + PartialEq
-        + Eq
-        + PartialOrd
-        + Ord
-        + Hash
-        + Default
-        + Shr<i32, Output = T>
-        + Shl<i32, Output = T>
-        + BitXor<T, Output = T>
-        + BitAnd<T, Output = T>
-        + BitOr<T, Output = T>
-        + Not
-        + BitAndAssign
-        + BitOrAssign
-        + BitXorAssign
-        + ShrAssign<i32>
-        + ShlAssign<i32>,
-{
-    type Output = Self;
-
-    #[inline(always)]
-    fn shr(self, other: i32) -> Self {
-        Num::from(self.data.shr(other))
-    }
-}
-impl<T> ShrAssign<i32> for Num<T>
-where
-    T: Copy
-        + Clone
-        + PartialEq
-        + Eq
-        + PartialOrd
-        + Ord
-        + Hash
-        + Default
-        + Shr<i32, Output = T>
-        + Shl<i32, Output = T>
-        + BitXor<T, Output = T>
-        + BitAnd<T, Output = T>
-        + BitOr<T, Output = T>
-        + Not
-        + BitAndAssign
-        + BitOrAssign
-        + BitXorAssign
-        + ShrAssign<i32>
-        + ShlAssign<i32>,
-{
-    #[inline(always)]
-    fn shr_assign(&mut self, other: i32) {
-        self.data.shr_assign(other)
-    }
-}
-
-impl<T> Shl<i32> for Num<T>
-where
-    T: Copy
-        + Clone
-        + PartialEq
-        + Eq
-        + PartialOrd
-        + Ord
-        + Hash
-        + Default
-        + Shl<i32, Output = T>
-        + Shr<i32, Output = T>
-        + BitXor<T, Output = T>
-        + BitAnd<T, Output = T>
-        + BitOr<T, Output = T>
-        + Not
-        + BitAndAssign
-        + BitOrAssign
-        + BitXorAssign
-        + ShlAssign<i32>
-        + ShrAssign<i32>,
-{
-    type Output = Self;
-
-    #[inline(always)]
-    fn shl(self, other: i32) -> Self {
-        Num::from(self.data.shl(other))
-    }
-}
-impl<T> ShlAssign<i32> for Num<T>
-where
-    T: Copy
-        + Clone
-        + PartialEq
-        + Eq
-        + PartialOrd
-        + Ord
-        + Hash
-        + Default
-        + Shl<i32, Output = T>
-        + Shr<i32, Output = T>
-        + BitXor<T, Output = T>
-        + BitAnd<T, Output = T>
-        + BitOr<T, Output = T>
-        + Not
-        + BitAndAssign
-        + BitOrAssign
-        + BitXorAssign
-        + ShlAssign<i32>
-        + ShrAssign<i32>,
-{
-    #[inline(always)]
-    fn shl_assign(&mut self, other: i32) {
-        self.data.shl_assign(other)
-    }
-}
-
 implement_num_core_op_trait!(BitXor => bitxor; BitXorAssign => bitxor_assign);
 implement_num_core_op_trait!(BitAnd => bitand; BitAndAssign => bitand_assign);
 implement_num_core_op_trait!(BitOr => bitor; BitOrAssign => bitor_assign);
@@ -448,8 +343,8 @@ pub trait PrimativeNumber:
         + Ord
         + Hash
         + Default
-        + Shr<i32, Output = Self::Primative>
-        + Shl<i32, Output = Self::Primative>
+        + Shr<u32, Output = Self::Primative>
+        + Shl<u32, Output = Self::Primative>
         + BitXor<Self::Primative, Output = Self::Primative>
         + BitAnd<Self::Primative, Output = Self::Primative>
         + BitOr<Self::Primative, Output = Self::Primative>
@@ -457,8 +352,8 @@ pub trait PrimativeNumber:
         + BitAndAssign
         + BitOrAssign
         + BitXorAssign
-        + ShrAssign<i32>
-        + ShlAssign<i32>;
+        + ShrAssign<u32>
+        + ShlAssign<u32>;
 
     fn max() -> Self::Primative;
     fn min() -> Self::Primative;
@@ -472,6 +367,11 @@ pub trait PrimativeNumber:
     fn rotate_left(self, arg: u32) -> Self;
     fn rotate_right(self, arg: u32) -> Self;
 
+    fn leading_zeros(self) -> u32;
+    fn trailing_zeros(self) -> u32;
+    fn count_ones(self) -> u32;
+    fn count_zeros(self) -> u32;
+
     fn wrapping_add<A>(self, arg: A) -> Self
     where
         Self: From<A>;
@@ -484,6 +384,37 @@ pub trait PrimativeNumber:
     where
         Self: From<A>;
 
+    /// returns the full product as its `(low, high)` halves, for
+    /// example the two 64bit halves of a 64x64 bit multiply. Signed
+    /// values produce the halves of the two's complement product.
+    fn mul_wide<A>(self, arg: A) -> (Self, Self)
+    where
+        Self: From<A>;
+
+    fn checked_add<A>(self, arg: A) -> Option<Self>
+    where
+        Self: From<A>;
+
+    fn checked_sub<A>(self, arg: A) -> Option<Self>
+    where
+        Self: From<A>;
+
+    fn checked_mul<A>(self, arg: A) -> Option<Self>
+    where
+        Self: From<A>;
+
+    fn saturating_add<A>(self, arg: A) -> Self
+    where
+        Self: From<A>;
+
+    fn saturating_sub<A>(self, arg: A) -> Self
+    where
+        Self: From<A>;
+
+    fn saturating_mul<A>(self, arg: A) -> Self
+    where
+        Self: From<A>;
+
     /// This function will perform a byte swap if and only if the platform is little endian
     #[allow(dead_code)]
     #[inline]
@@ -543,6 +474,20 @@ pub trait PrimativeNumber:
                 Self::from(screw_alignment_load::<Self::Primative>(arg))
             }
         }
+
+        #[cfg(not(any(
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "powerpc",
+            target_arch = "mips",
+            target_arch = "x86",
+            target_arch = "powerpc64",
+            target_arch = "x86_64"
+        )))]
+        {
+            // unknown platforms are assumed to care
+            Self::from(alignment_matters_load::<Self::Primative>(arg))
+        }
     }
 
     /// reads a value into big endian.
@@ -564,54 +509,397 @@ pub trait PrimativeNumber:
     fn read_value_le(arg: &[u8]) -> Self {
         Self::read_value(arg).swap_if_platform_is_big_endian()
     }
+
+    /// reads a value starting `offset` bytes into `arg`.
+    ///
+    /// When the `unbounded` feature is enabled the range is only
+    /// checked with `debug_assert!`, otherwise this panics when
+    /// `arg` is too short.
+    #[allow(dead_code)]
+    #[inline(always)]
+    fn read_value_at(arg: &[u8], offset: usize) -> Self {
+        #[cfg(not(feature = "unbounded"))]
+        {
+            Self::read_value(&arg[offset..])
+        }
+
+        #[cfg(feature = "unbounded")]
+        {
+            debug_assert!(offset <= arg.len(), "out of bounds memory access");
+            Self::read_value(unsafe { arg.get_unchecked(offset..) })
+        }
+    }
+
+    /// big endian form of `read_value_at`
+    #[allow(dead_code)]
+    #[inline(always)]
+    fn read_value_be_at(arg: &[u8], offset: usize) -> Self {
+        Self::read_value_at(arg, offset).swap_if_platform_is_little_endian()
+    }
+
+    /// little endian form of `read_value_at`
+    #[allow(dead_code)]
+    #[inline(always)]
+    fn read_value_le_at(arg: &[u8], offset: usize) -> Self {
+        Self::read_value_at(arg, offset).swap_if_platform_is_big_endian()
+    }
+
+    /// non-panicking form of `read_value`.
+    ///
+    /// When `arg` is too short this returns `Error::Truncated` with
+    /// the offset (relative to `arg`) where the input ran out.
+    #[allow(dead_code)]
+    #[inline]
+    fn try_read_value(arg: &[u8]) -> Result<Self, Error> {
+        if arg.len() < size_of::<Self::Primative>() {
+            Err(Error::Truncated { offset: arg.len() })
+        } else {
+            Ok(Self::read_value(arg))
+        }
+    }
+
+    /// non-panicking form of `read_value_be`
+    #[allow(dead_code)]
+    #[inline]
+    fn try_read_value_be(arg: &[u8]) -> Result<Self, Error> {
+        Self::try_read_value(arg).map(|x| x.swap_if_platform_is_little_endian())
+    }
+
+    /// non-panicking form of `read_value_le`
+    #[allow(dead_code)]
+    #[inline]
+    fn try_read_value_le(arg: &[u8]) -> Result<Self, Error> {
+        Self::try_read_value(arg).map(|x| x.swap_if_platform_is_big_endian())
+    }
+
+    /// This is the store side of `read_value`, it writes the value in
+    /// the native endianess to the start of `arg`
+    #[allow(dead_code)]
+    #[inline]
+    fn write_value(self, arg: &mut [u8]) {
+        #[cfg(any(
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "powerpc",
+            target_arch = "mips"
+        ))]
+        {
+            alignment_matters_store::<Self::Primative>(arg, self.inner())
+        }
+
+        #[cfg(any(target_arch = "x86", target_arch = "powerpc64", target_arch = "x86_64"))]
+        {
+            if size_of::<Self::Primative>() >= 16 {
+                alignment_matters_store::<Self::Primative>(arg, self.inner())
+            } else {
+                screw_alignment_store::<Self::Primative>(arg, self.inner())
+            }
+        }
+
+        #[cfg(not(any(
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "powerpc",
+            target_arch = "mips",
+            target_arch = "x86",
+            target_arch = "powerpc64",
+            target_arch = "x86_64"
+        )))]
+        {
+            alignment_matters_store::<Self::Primative>(arg, self.inner())
+        }
+    }
+
+    /// writes the value as big endian.
+    ///
+    /// when targetting a platform with native little endian
+    /// format it will emit a byte swap
+    #[allow(dead_code)]
+    #[inline]
+    fn write_value_be(self, arg: &mut [u8]) {
+        self.swap_if_platform_is_little_endian().write_value(arg)
+    }
+
+    /// writes the value as little endian.
+    ///
+    /// when targetting a platform with native big endian
+    /// format it will emit a byte swap
+    #[allow(dead_code)]
+    #[inline]
+    fn write_value_le(self, arg: &mut [u8]) {
+        self.swap_if_platform_is_big_endian().write_value(arg)
+    }
+
+    /// writes the value starting `offset` bytes into `arg`.
+    ///
+    /// When the `unbounded` feature is enabled the range is only
+    /// checked with `debug_assert!`, otherwise this panics when
+    /// `arg` is too short.
+    #[allow(dead_code)]
+    #[inline(always)]
+    fn write_value_at(self, arg: &mut [u8], offset: usize) {
+        #[cfg(not(feature = "unbounded"))]
+        {
+            self.write_value(&mut arg[offset..])
+        }
+
+        #[cfg(feature = "unbounded")]
+        {
+            debug_assert!(offset <= arg.len(), "out of bounds memory access");
+            self.write_value(unsafe { arg.get_unchecked_mut(offset..) })
+        }
+    }
+
+    /// big endian form of `write_value_at`
+    #[allow(dead_code)]
+    #[inline(always)]
+    fn write_value_be_at(self, arg: &mut [u8], offset: usize) {
+        self.swap_if_platform_is_little_endian()
+            .write_value_at(arg, offset)
+    }
+
+    /// little endian form of `write_value_at`
+    #[allow(dead_code)]
+    #[inline(always)]
+    fn write_value_le_at(self, arg: &mut [u8], offset: usize) {
+        self.swap_if_platform_is_big_endian()
+            .write_value_at(arg, offset)
+    }
+
+    /// non-panicking form of `write_value`.
+    ///
+    /// When `arg` is too short this returns `Error::OutputFull` with
+    /// the offset (relative to `arg`) where the output ran out, and
+    /// nothing is written.
+    #[allow(dead_code)]
+    #[inline]
+    fn try_write_value(self, arg: &mut [u8]) -> Result<(), Error> {
+        if arg.len() < size_of::<Self::Primative>() {
+            Err(Error::OutputFull { offset: arg.len() })
+        } else {
+            self.write_value(arg);
+            Ok(())
+        }
+    }
+
+    /// non-panicking form of `write_value_be`
+    #[allow(dead_code)]
+    #[inline]
+    fn try_write_value_be(self, arg: &mut [u8]) -> Result<(), Error> {
+        self.swap_if_platform_is_little_endian()
+            .try_write_value(arg)
+    }
+
+    /// non-panicking form of `write_value_le`
+    #[allow(dead_code)]
+    #[inline]
+    fn try_write_value_le(self, arg: &mut [u8]) -> Result<(), Error> {
+        self.swap_if_platform_is_big_endian().try_write_value(arg)
+    }
 }
 
 /// a function for doing alignment checking
 #[allow(dead_code)]
 #[inline(always)]
 fn is_aligned<T: Copy>(arg: *const u8) -> bool {
-    let address: usize = unsafe { transmute(arg) };
-    (address & (align_of::<T>() - 1usize)) == 0
+    (arg as usize & (align_of::<T>() - 1usize)) == 0
 }
 
 /// general function for handling all the semantics of loading on platforms
 /// where alignment matters
+///
+/// `T` must be a type for which every bit pattern is valid, this is only
+/// ever invoked with the integer types `PrimativeNumber` is implemented for.
 #[allow(dead_code)]
 #[inline(always)]
 fn alignment_matters_load<T: Copy>(arg: &[u8]) -> T {
-    #[cfg(not(feature = "unbound"))]
+    #[cfg(not(feature = "unbounded"))]
     {
         if arg.len() < size_of::<T>() {
             panic!("out of bounds memory access");
         }
     }
+    #[cfg(feature = "unbounded")]
+    {
+        debug_assert!(arg.len() >= size_of::<T>(), "out of bounds memory access");
+    }
 
+    let ptr = arg.as_ptr();
     unsafe {
-        if is_aligned::<T>(arg.as_ptr()) {
-            read::<T>(transmute::<*const u8, *const T>(arg.as_ptr()))
+        if is_aligned::<T>(ptr) {
+            read::<T>(ptr as *const T)
         } else {
-            read_unaligned::<T>(transmute::<*const u8, *const T>(arg.as_ptr()))
+            read_unaligned::<T>(ptr as *const T)
         }
     }
 }
 
 /// general function for handling all the semantics of loading on platforms
 /// where alignment doesn't matter
+///
+/// A plain `read` of a misaligned pointer is UB on every platform, even
+/// those whose hardware tolerates it. `read_unaligned` compiles to the same
+/// single load instruction on these targets.
+///
+/// `T` must be a type for which every bit pattern is valid, this is only
+/// ever invoked with the integer types `PrimativeNumber` is implemented for.
 #[allow(dead_code)]
 #[inline(always)]
 fn screw_alignment_load<T: Copy>(arg: &[u8]) -> T {
-    #[cfg(not(feature = "unbound"))]
+    #[cfg(not(feature = "unbounded"))]
     {
         if arg.len() < size_of::<T>() {
             panic!("out of bounds memory access");
         }
     }
+    #[cfg(feature = "unbounded")]
+    {
+        debug_assert!(arg.len() >= size_of::<T>(), "out of bounds memory access");
+    }
+
+    unsafe { read_unaligned::<T>(arg.as_ptr() as *const T) }
+}
+
+/// the store side of `alignment_matters_load`
+#[allow(dead_code)]
+#[inline(always)]
+fn alignment_matters_store<T: Copy>(arg: &mut [u8], value: T) {
+    #[cfg(not(feature = "unbounded"))]
+    {
+        if arg.len() < size_of::<T>() {
+            panic!("out of bounds memory access");
+        }
+    }
+    #[cfg(feature = "unbounded")]
+    {
+        debug_assert!(arg.len() >= size_of::<T>(), "out of bounds memory access");
+    }
+
+    let ptr = arg.as_mut_ptr();
+    unsafe {
+        if is_aligned::<T>(ptr) {
+            write::<T>(ptr as *mut T, value)
+        } else {
+            write_unaligned::<T>(ptr as *mut T, value)
+        }
+    }
+}
+
+/// the store side of `screw_alignment_load`
+#[allow(dead_code)]
+#[inline(always)]
+fn screw_alignment_store<T: Copy>(arg: &mut [u8], value: T) {
+    #[cfg(not(feature = "unbounded"))]
+    {
+        if arg.len() < size_of::<T>() {
+            panic!("out of bounds memory access");
+        }
+    }
+    #[cfg(feature = "unbounded")]
+    {
+        debug_assert!(arg.len() >= size_of::<T>(), "out of bounds memory access");
+    }
+
+    unsafe { write_unaligned::<T>(arg.as_mut_ptr() as *mut T, value) }
+}
+
+/// the full width product of two unsigned values as `(low, high)`.
+///
+/// This is schoolbook multiplication on half width limbs, so no wider
+/// type is needed, which `u128` does not have.
+macro_rules! unsigned_mul_wide {
+    ($a: expr, $b: expr, $kind: ident) => {{
+        let half = (size_of::<$kind>() * 4) as u32;
+        let mask: $kind = (!0) >> half;
+        let (a, b): ($kind, $kind) = ($a, $b);
+        let (a_lo, a_hi) = (a & mask, a >> half);
+        let (b_lo, b_hi) = (b & mask, b >> half);
+
+        let lo_lo = a_lo * b_lo;
+        let lo_hi = a_lo * b_hi;
+        let hi_lo = a_hi * b_lo;
+        let hi_hi = a_hi * b_hi;
+
+        // each term is below `2^half`, so the sum cannot overflow
+        let middle = (lo_lo >> half) + (lo_hi & mask) + (hi_lo & mask);
+        let low = (lo_lo & mask) | (middle << half);
+        let high = hi_hi + (lo_hi >> half) + (hi_lo >> half) + (middle >> half);
+        (low, high)
+    }};
+}
+
+/// implements an arithmetic operator (and its assigning form) with
+/// the wrapping semantics of `std::num::Wrapping`
+macro_rules! implement_wrapping_op {
+    ($kind: ident, $trait_norm: ident => $func_norm: ident, $trait_asg: ident => $func_asg: ident, $wrapping: ident) => {
+        impl $trait_norm for Num<$kind> {
+            type Output = Self;
+
+            #[inline(always)]
+            fn $func_norm(self, other: Self) -> Self {
+                Num::from(self.data.$wrapping(other.data))
+            }
+        }
+        impl $trait_norm<$kind> for Num<$kind> {
+            type Output = Self;
+
+            #[inline(always)]
+            fn $func_norm(self, other: $kind) -> Self {
+                Num::from(self.data.$wrapping(other))
+            }
+        }
+        impl $trait_asg for Num<$kind> {
+            #[inline(always)]
+            fn $func_asg(&mut self, other: Self) {
+                self.data = self.data.$wrapping(other.data);
+            }
+        }
+        impl $trait_asg<$kind> for Num<$kind> {
+            #[inline(always)]
+            fn $func_asg(&mut self, other: $kind) {
+                self.data = self.data.$wrapping(other);
+            }
+        }
+    };
+}
 
-    unsafe { read::<T>(transmute::<*const u8, *const T>(arg.as_ptr())) }
+/// implements a shift (and its assigning form) that masks the count to
+/// the width of the primative, as `std::num::Wrapping` does
+macro_rules! implement_wrapping_shift {
+    ($kind: ident, $trait_norm: ident => $func_norm: ident, $trait_asg: ident => $func_asg: ident, $wrapping: ident) => {
+        impl $trait_norm<u32> for Num<$kind> {
+            type Output = Self;
+
+            #[inline(always)]
+            fn $func_norm(self, other: u32) -> Self {
+                Num::from(self.data.$wrapping(other))
+            }
+        }
+        impl $trait_asg<u32> for Num<$kind> {
+            #[inline(always)]
+            fn $func_asg(&mut self, other: u32) {
+                self.data = self.data.$wrapping(other);
+            }
+        }
+    };
+}
+
+/// forwards formatting to the primative
+macro_rules! implement_fmt {
+    ($kind: ident, $($trait: ident),*) => {
+        $(
+            impl fmt::$trait for Num<$kind> {
+                #[inline]
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    fmt::$trait::fmt(&self.data, f)
+                }
+            }
+        )*
+    };
 }
 
 macro_rules! implement_primative_number {
-    ($kind: ident) => {
+    ($kind: ident, $unsigned: ident, $signed: expr) => {
         impl PrimativeNumber for Num<$kind> {
             type Primative = $kind;
 
@@ -622,22 +910,12 @@ macro_rules! implement_primative_number {
 
             #[inline(always)]
             fn max() -> $kind {
-                #[cfg(not(feature = "std"))]
-                use core::$kind::MAX;
-                #[cfg(feature = "std")]
-                use std::$kind::MAX;
-
-                MAX
+                $kind::MAX
             }
 
             #[inline(always)]
             fn min() -> $kind {
-                #[cfg(not(feature = "std"))]
-                use core::$kind::MIN;
-                #[cfg(feature = "std")]
-                use std::$kind::MIN;
-
-                MIN
+                $kind::MIN
             }
 
             #[inline(always)]
@@ -655,6 +933,26 @@ macro_rules! implement_primative_number {
                 Num::from(self.data.rotate_left(arg))
             }
 
+            #[inline(always)]
+            fn leading_zeros(self) -> u32 {
+                self.data.leading_zeros()
+            }
+
+            #[inline(always)]
+            fn trailing_zeros(self) -> u32 {
+                self.data.trailing_zeros()
+            }
+
+            #[inline(always)]
+            fn count_ones(self) -> u32 {
+                self.data.count_ones()
+            }
+
+            #[inline(always)]
+            fn count_zeros(self) -> u32 {
+                self.data.count_zeros()
+            }
+
             #[inline(always)]
             fn wrapping_add<G>(self, arg: G) -> Num<$kind>
             where
@@ -684,21 +982,458 @@ macro_rules! implement_primative_number {
                 let y: $kind = self.data.wrapping_mul(x.data);
                 <Num<$kind> as From<$kind>>::from(y)
             }
+
+            #[inline(always)]
+            fn mul_wide<G>(self, arg: G) -> (Num<$kind>, Num<$kind>)
+            where
+                Self: From<G>,
+            {
+                let x: Num<$kind> = <Num<$kind> as From<G>>::from(arg);
+                let (low, high) =
+                    unsigned_mul_wide!(self.data as $unsigned, x.data as $unsigned, $unsigned);
+                let mut high = high as $kind;
+                if $signed {
+                    // a negative operand was read as `2^bits` too large,
+                    // which overstated the high half by the other operand
+                    let sign = (size_of::<$kind>() * 8 - 1) as u32;
+                    if (self.data as $unsigned) >> sign != 0 {
+                        high = high.wrapping_sub(x.data);
+                    }
+                    if (x.data as $unsigned) >> sign != 0 {
+                        high = high.wrapping_sub(self.data);
+                    }
+                }
+                (
+                    <Num<$kind> as From<$kind>>::from(low as $kind),
+                    <Num<$kind> as From<$kind>>::from(high),
+                )
+            }
+
+            #[inline(always)]
+            fn checked_add<G>(self, arg: G) -> Option<Num<$kind>>
+            where
+                Self: From<G>,
+            {
+                let x: Num<$kind> = <Num<$kind> as From<G>>::from(arg);
+                self.data.checked_add(x.data).map(<Num<$kind> as From<$kind>>::from)
+            }
+
+            #[inline(always)]
+            fn checked_sub<G>(self, arg: G) -> Option<Num<$kind>>
+            where
+                Self: From<G>,
+            {
+                let x: Num<$kind> = <Num<$kind> as From<G>>::from(arg);
+                self.data.checked_sub(x.data).map(<Num<$kind> as From<$kind>>::from)
+            }
+
+            #[inline(always)]
+            fn checked_mul<G>(self, arg: G) -> Option<Num<$kind>>
+            where
+                Self: From<G>,
+            {
+                let x: Num<$kind> = <Num<$kind> as From<G>>::from(arg);
+                self.data.checked_mul(x.data).map(<Num<$kind> as From<$kind>>::from)
+            }
+
+            #[inline(always)]
+            fn saturating_add<G>(self, arg: G) -> Num<$kind>
+            where
+                Self: From<G>,
+            {
+                let x: Num<$kind> = <Num<$kind> as From<G>>::from(arg);
+                <Num<$kind> as From<$kind>>::from(self.data.saturating_add(x.data))
+            }
+
+            #[inline(always)]
+            fn saturating_sub<G>(self, arg: G) -> Num<$kind>
+            where
+                Self: From<G>,
+            {
+                let x: Num<$kind> = <Num<$kind> as From<G>>::from(arg);
+                <Num<$kind> as From<$kind>>::from(self.data.saturating_sub(x.data))
+            }
+
+            #[inline(always)]
+            fn saturating_mul<G>(self, arg: G) -> Num<$kind>
+            where
+                Self: From<G>,
+            {
+                let x: Num<$kind> = <Num<$kind> as From<G>>::from(arg);
+                <Num<$kind> as From<$kind>>::from(self.data.saturating_mul(x.data))
+            }
+        }
+
+        implement_wrapping_op!($kind, Add => add, AddAssign => add_assign, wrapping_add);
+        implement_wrapping_op!($kind, Sub => sub, SubAssign => sub_assign, wrapping_sub);
+        implement_wrapping_op!($kind, Mul => mul, MulAssign => mul_assign, wrapping_mul);
+        implement_wrapping_op!($kind, Div => div, DivAssign => div_assign, wrapping_div);
+        implement_wrapping_op!($kind, Rem => rem, RemAssign => rem_assign, wrapping_rem);
+        implement_wrapping_shift!($kind, Shr => shr, ShrAssign => shr_assign, wrapping_shr);
+        implement_wrapping_shift!($kind, Shl => shl, ShlAssign => shl_assign, wrapping_shl);
+
+        impl Neg for Num<$kind> {
+            type Output = Self;
+
+            #[inline(always)]
+            fn neg(self) -> Self {
+                Num::from(self.data.wrapping_neg())
+            }
         }
+        impl Not for Num<$kind> {
+            type Output = Self;
+
+            #[inline(always)]
+            fn not(self) -> Self {
+                Num::from(!self.data)
+            }
+        }
+
+        implement_fmt!($kind, Debug, Display, LowerHex, UpperHex, Binary, Octal);
     };
 }
 
-implement_primative_number!(u8);
-implement_primative_number!(i8);
-implement_primative_number!(u16);
-implement_primative_number!(i16);
-implement_primative_number!(u32);
-implement_primative_number!(i32);
-implement_primative_number!(u64);
-implement_primative_number!(i64);
-implement_primative_number!(usize);
-implement_primative_number!(isize);
-#[cfg(feature = "RUSTC_VERSION_GE_1_26")]
-implement_primative_number!(u128);
-#[cfg(feature = "RUSTC_VERSION_GE_1_26")]
-implement_primative_number!(i128);
+implement_primative_number!(u8, u8, false);
+implement_primative_number!(i8, u8, true);
+implement_primative_number!(u16, u16, false);
+implement_primative_number!(i16, u16, true);
+implement_primative_number!(u32, u32, false);
+implement_primative_number!(i32, u32, true);
+implement_primative_number!(u64, u64, false);
+implement_primative_number!(i64, u64, true);
+implement_primative_number!(usize, usize, false);
+implement_primative_number!(isize, usize, true);
+#[cfg(HAS_U128)]
+implement_primative_number!(u128, u128, false);
+#[cfg(HAS_U128)]
+implement_primative_number!(i128, u128, true);
+
+#[cfg(test)]
+mod test {
+
+    use super::super::error::Error;
+    use super::super::mem::size_of;
+    use super::{Num, PrimativeNumber};
+
+    #[test]
+    fn try_read_reports_truncation() {
+        let data = [0x01u8, 0x02, 0x03, 0x04, 0x05];
+        assert_eq!(
+            Num::<u32>::try_read_value_le(&data).map(|x| x.inner()),
+            Ok(0x04030201u32)
+        );
+        assert_eq!(
+            Num::<u32>::try_read_value_be(&data[1..]).map(|x| x.inner()),
+            Ok(0x02030405u32)
+        );
+        assert_eq!(
+            Num::<u64>::try_read_value_le(&data).map(|x| x.inner()),
+            Err(Error::Truncated { offset: 5 })
+        );
+        assert_eq!(
+            Num::<u16>::try_read_value(&data[4..]).map(|x| x.inner()),
+            Err(Error::Truncated { offset: 1 })
+        );
+    }
+
+    /// checks every load against `from_*_bytes` at every alignment
+    macro_rules! check_loads {
+        ($name: ident, $kind: ident) => {
+            #[test]
+            fn $name() {
+                const WIDTH: usize = size_of::<$kind>();
+                let mut data = [0u8; 64];
+                for (i, byte) in data.iter_mut().enumerate() {
+                    *byte = (i as u8).wrapping_mul(37).wrapping_add(11);
+                }
+                for offset in 0..=(data.len() - WIDTH) {
+                    let slice = &data[offset..];
+                    let mut bytes = [0u8; WIDTH];
+                    bytes.copy_from_slice(&slice[0..WIDTH]);
+
+                    assert_eq!(
+                        Num::<$kind>::read_value(slice).inner(),
+                        $kind::from_ne_bytes(bytes)
+                    );
+                    assert_eq!(
+                        Num::<$kind>::read_value_le(slice).inner(),
+                        $kind::from_le_bytes(bytes)
+                    );
+                    assert_eq!(
+                        Num::<$kind>::read_value_be(slice).inner(),
+                        $kind::from_be_bytes(bytes)
+                    );
+                    // exactly sized input is in bounds
+                    assert_eq!(
+                        Num::<$kind>::read_value_le(&bytes).inner(),
+                        $kind::from_le_bytes(bytes)
+                    );
+                }
+            }
+        };
+    }
+
+    check_loads!(loads_u8, u8);
+    check_loads!(loads_i8, i8);
+    check_loads!(loads_u16, u16);
+    check_loads!(loads_i16, i16);
+    check_loads!(loads_u32, u32);
+    check_loads!(loads_i32, i32);
+    check_loads!(loads_u64, u64);
+    check_loads!(loads_i64, i64);
+    check_loads!(loads_usize, usize);
+    check_loads!(loads_isize, isize);
+    #[cfg(HAS_U128)]
+    check_loads!(loads_u128, u128);
+    #[cfg(HAS_U128)]
+    check_loads!(loads_i128, i128);
+
+    /// checks every store against `to_*_bytes` at every alignment
+    macro_rules! check_stores {
+        ($name: ident, $kind: ident) => {
+            #[test]
+            fn $name() {
+                const WIDTH: usize = size_of::<$kind>();
+                let value = (0x0123_4567_89AB_CDEF_u64 as $kind).rotate_left(3);
+                for offset in 0..=(64 - WIDTH) {
+                    let mut data = [0xEEu8; 64];
+                    Num::<$kind>::from(value).write_value(&mut data[offset..]);
+                    assert_eq!(&data[offset..offset + WIDTH], &value.to_ne_bytes()[..]);
+
+                    Num::<$kind>::from(value).write_value_le(&mut data[offset..]);
+                    assert_eq!(&data[offset..offset + WIDTH], &value.to_le_bytes()[..]);
+
+                    Num::<$kind>::from(value).write_value_be_at(&mut data, offset);
+                    assert_eq!(&data[offset..offset + WIDTH], &value.to_be_bytes()[..]);
+                    assert_eq!(Num::<$kind>::read_value_be_at(&data, offset).inner(), value);
+
+                    // neighbouring bytes are untouched
+                    assert!(data[..offset].iter().all(|x| *x == 0xEE));
+                    assert!(data[offset + WIDTH..].iter().all(|x| *x == 0xEE));
+                }
+            }
+        };
+    }
+
+    check_stores!(stores_u8, u8);
+    check_stores!(stores_i8, i8);
+    check_stores!(stores_u16, u16);
+    check_stores!(stores_i16, i16);
+    check_stores!(stores_u32, u32);
+    check_stores!(stores_i32, i32);
+    check_stores!(stores_u64, u64);
+    check_stores!(stores_i64, i64);
+    check_stores!(stores_usize, usize);
+    check_stores!(stores_isize, isize);
+    #[cfg(HAS_U128)]
+    check_stores!(stores_u128, u128);
+    #[cfg(HAS_U128)]
+    check_stores!(stores_i128, i128);
+
+    #[test]
+    fn try_write_reports_full_output() {
+        let mut data = [0u8; 5];
+        assert_eq!(
+            Num::from(0x01020304u32).try_write_value_be(&mut data[1..]),
+            Ok(())
+        );
+        assert_eq!(data, [0, 1, 2, 3, 4]);
+        assert_eq!(
+            Num::from(0u32).try_write_value_le(&mut data[2..]),
+            Err(Error::OutputFull { offset: 3 })
+        );
+        assert_eq!(data, [0, 1, 2, 3, 4]);
+    }
+
+    #[cfg(any(not(feature = "unbounded"), debug_assertions))]
+    #[test]
+    #[should_panic(expected = "out of bounds memory access")]
+    fn short_store_panics() {
+        let mut data = [0u8; 7];
+        Num::from(0u64).write_value_le(&mut data[..]);
+    }
+
+    #[cfg(any(not(feature = "unbounded"), debug_assertions))]
+    #[test]
+    #[should_panic]
+    fn short_store_at_panics() {
+        let mut data = [0u8; 8];
+        Num::from(0u32).write_value_le_at(&mut data[..], 6);
+    }
+
+    #[test]
+    fn loads_at_offset() {
+        let data = [0x01u8, 0x02, 0x03, 0x04, 0x05, 0x06];
+        for offset in 0..3 {
+            assert_eq!(
+                Num::<u32>::read_value_le_at(&data, offset).inner(),
+                Num::<u32>::read_value_le(&data[offset..]).inner()
+            );
+            assert_eq!(
+                Num::<u32>::read_value_be_at(&data, offset).inner(),
+                Num::<u32>::read_value_be(&data[offset..]).inner()
+            );
+        }
+    }
+
+    /// with `unbounded` the check only exists as a `debug_assert!`
+    #[cfg(any(not(feature = "unbounded"), debug_assertions))]
+    #[test]
+    #[should_panic(expected = "out of bounds memory access")]
+    fn short_load_panics() {
+        let data = [0u8; 7];
+        let _ = Num::<u64>::read_value_le(&data[..]);
+    }
+
+    #[cfg(any(not(feature = "unbounded"), debug_assertions))]
+    #[test]
+    #[should_panic]
+    fn short_load_at_panics() {
+        let data = [0u8; 8];
+        let _ = Num::<u32>::read_value_le_at(&data[..], 6);
+    }
+
+    #[test]
+    fn operators_wrap() {
+        let a = Num::from(250u8);
+        assert_eq!(a + 10u8, Num::from(4u8));
+        assert_eq!(Num::from(3u8) - a, Num::from(9u8));
+        assert_eq!(a * Num::from(2u8), Num::from(244u8));
+        assert_eq!(a / 3u8, Num::from(83u8));
+        assert_eq!(a % 7u8, Num::from(5u8));
+        assert_eq!(-Num::from(1u32), Num::from(u32::MAX));
+        assert_eq!(!Num::from(0x0Fu8), Num::from(0xF0u8));
+
+        // signed division only overflows for `MIN / -1`
+        assert_eq!(Num::from(i32::MIN) / -1i32, Num::from(i32::MIN));
+        assert_eq!(-Num::from(i64::MIN), Num::from(i64::MIN));
+
+        let mut b = Num::from(u64::MAX);
+        b += 2u64;
+        assert_eq!(b, Num::from(1u64));
+        b -= Num::from(2u64);
+        assert_eq!(b, Num::from(u64::MAX));
+        b *= 3u64;
+        assert_eq!(b, Num::from(u64::MAX - 2));
+        b /= 2u64;
+        b %= 1000u64;
+        assert_eq!(b, Num::from((u64::MAX - 2) / 2 % 1000));
+    }
+
+    #[test]
+    fn shifts_and_rotates_take_u32() {
+        let a = Num::from(0x8000_0001u32);
+        let n: u32 = 1;
+        assert_eq!(a << n, Num::from(2u32));
+        assert_eq!(a >> n, Num::from(0x4000_0000u32));
+        assert_eq!(a.rotate_left(n), Num::from(3u32));
+        assert_eq!(a.rotate_right(n), Num::from(0xC000_0000u32));
+    }
+
+    #[test]
+    fn shifts_mask_the_count() {
+        let a = Num::from(0x81u8);
+        assert_eq!(a << 8, a);
+        assert_eq!(a >> 9, Num::from(0x40u8));
+        assert_eq!(Num::from(-8i64) >> 66, Num::from(-2i64));
+        let mut b = Num::from(1u32);
+        b <<= 33;
+        assert_eq!(b, Num::from(2u32));
+        b >>= 32;
+        assert_eq!(b, Num::from(2u32));
+    }
+
+    #[test]
+    fn counts_bits() {
+        let a = Num::from(0x00F0u16);
+        assert_eq!(a.leading_zeros(), 8);
+        assert_eq!(a.trailing_zeros(), 4);
+        assert_eq!(a.count_ones(), 4);
+        assert_eq!(a.count_zeros(), 12);
+        assert_eq!(Num::from(-1i64).count_ones(), 64);
+        assert_eq!(Num::from(0usize).trailing_zeros(), usize::MAX.count_ones());
+    }
+
+    #[test]
+    fn checked_and_saturating() {
+        assert_eq!(Num::from(200u8).checked_add(55u8), Some(Num::from(255u8)));
+        assert_eq!(Num::from(200u8).checked_add(56u8), None);
+        assert_eq!(Num::from(0u32).checked_sub(1u32), None);
+        assert_eq!(Num::from(i16::MIN).checked_mul(-1i16), None);
+        assert_eq!(Num::from(200u8).saturating_add(100u8), Num::from(255u8));
+        assert_eq!(Num::from(5u64).saturating_sub(9u64), Num::from(0u64));
+        assert_eq!(Num::from(-100i8).saturating_mul(2i8), Num::from(-128i8));
+    }
+
+    #[test]
+    fn mul_wide_matches_wider_multiply() {
+        // every pair of `u8`/`i8`, sampled under Miri
+        let step = if cfg!(miri) { 37 } else { 1 };
+        for a in (0..=255u8).step_by(step) {
+            for b in 0..=255u8 {
+                let (low, high) = Num::from(a).mul_wide(b);
+                let expected = (a as u16) * (b as u16);
+                assert_eq!(
+                    (low.inner(), high.inner()),
+                    (expected as u8, (expected >> 8) as u8)
+                );
+
+                let (a, b) = (a as i8, b as i8);
+                let (low, high) = Num::from(a).mul_wide(b);
+                let expected = (a as i16) * (b as i16);
+                assert_eq!(
+                    (low.inner(), high.inner()),
+                    (expected as i8, (expected >> 8) as i8)
+                );
+            }
+        }
+
+        let edges = [
+            0u64,
+            1,
+            2,
+            0xFFFF_FFFF,
+            0x1_0000_0000,
+            0x9E37_79B9_7F4A_7C15,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        for a in edges.iter() {
+            for b in edges.iter() {
+                let (low, high) = Num::from(*a).mul_wide(*b);
+                let expected = (*a as u128) * (*b as u128);
+                assert_eq!(low.inner(), expected as u64);
+                assert_eq!(high.inner(), (expected >> 64) as u64);
+
+                let (a, b) = (*a as i64, *b as i64);
+                let (low, high) = Num::from(a).mul_wide(b);
+                let expected = (a as i128) * (b as i128);
+                assert_eq!(low.inner(), expected as i64);
+                assert_eq!(high.inner(), (expected >> 64) as i64);
+            }
+        }
+    }
+
+    #[cfg(HAS_U128)]
+    #[test]
+    fn mul_wide_u128() {
+        let (low, high) = Num::from(u128::MAX).mul_wide(u128::MAX);
+        assert_eq!((low.inner(), high.inner()), (1, u128::MAX - 1));
+        let (low, high) = Num::from(1u128 << 64).mul_wide(1u128 << 64);
+        assert_eq!((low.inner(), high.inner()), (0, 1));
+        let (low, high) = Num::from(-1i128).mul_wide(i128::MIN);
+        assert_eq!((low.inner(), high.inner()), (i128::MIN, 0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn formats_as_primative() {
+        let a = Num::from(0xABu8);
+        assert_eq!(format!("{:?}", a), "171");
+        assert_eq!(format!("{}", Num::from(-5i32)), "-5");
+        assert_eq!(format!("{:x}", a), "ab");
+        assert_eq!(format!("{:#06X}", a), "0x00AB");
+        assert_eq!(format!("{:b}", a), "10101011");
+        assert_eq!(format!("{:o}", a), "253");
+    }
+}
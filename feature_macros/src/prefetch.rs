@@ -1,3 +1,245 @@
+#[cfg(all(
+    feature = "prefetch_hints",
+    not(feature = "std"),
+    HAS_CORE_ARCH,
+    target_arch = "x86"
+))]
+use core::arch::x86 as arch;
+#[cfg(all(
+    feature = "prefetch_hints",
+    not(feature = "std"),
+    HAS_CORE_ARCH,
+    target_arch = "x86_64"
+))]
+use core::arch::x86_64 as arch;
+#[cfg(all(
+    feature = "prefetch_hints",
+    feature = "std",
+    HAS_CORE_ARCH,
+    target_arch = "x86"
+))]
+use std::arch::x86 as arch;
+#[cfg(all(
+    feature = "prefetch_hints",
+    feature = "std",
+    HAS_CORE_ARCH,
+    target_arch = "x86_64"
+))]
+use std::arch::x86_64 as arch;
+
+#[cfg(all(
+    feature = "prefetch_hints",
+    not(feature = "std"),
+    HAS_ASM,
+    target_arch = "aarch64"
+))]
+use core::arch::asm;
+#[cfg(all(
+    feature = "prefetch_hints",
+    feature = "std",
+    HAS_ASM,
+    target_arch = "aarch64"
+))]
+use std::arch::asm;
+
+#[cfg(all(
+    feature = "prefetch_hints",
+    not(feature = "std"),
+    HAS_PREFETCH_INTRINSICS,
+    not(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64"))),
+    not(all(HAS_ASM, not(miri), target_arch = "aarch64"))
+))]
+use core::intrinsics::{prefetch_read_data, prefetch_write_data};
+#[cfg(all(
+    feature = "prefetch_hints",
+    feature = "std",
+    HAS_PREFETCH_INTRINSICS,
+    not(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64"))),
+    not(all(HAS_ASM, not(miri), target_arch = "aarch64"))
+))]
+use std::intrinsics::{prefetch_read_data, prefetch_write_data};
+
+use super::cache::line_size;
+use super::mem::size_of;
+#[cfg(feature = "prefetch_hints")]
+use super::mem::size_of_val;
+
+/// Locality is how soon, and how often, prefetched data is expected to
+/// be used. It selects which levels of cache the line is brought into.
+///
+/// The methods are an implementation detail, use `prefetch_read` and
+/// `prefetch_write`.
+pub trait Locality {
+    #[doc(hidden)]
+    fn read(ptr: *const u8);
+    #[doc(hidden)]
+    fn write(ptr: *const u8);
+}
+
+/// declares a `Locality` and how it is hinted on each platform.
+///
+/// x86 has no write hints below L2, so those fall back to `_MM_HINT_ET1`.
+/// Platforms without an `arch` path use the nightly intrinsics, where
+/// a higher number means the data is kept closer.
+macro_rules! locality {
+    (
+        $(#[$meta:meta])*
+        $name:ident,
+        x86: $read:ident / $write:ident,
+        aarch64: $pld:literal / $pst:literal,
+        intrinsic: $level:literal
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+        pub enum $name {}
+        impl Locality for $name {
+            #[inline(always)]
+            #[allow(unused_variables, unused_unsafe)]
+            fn read(ptr: *const u8) {
+                #[cfg(all(
+                    feature = "prefetch_hints",
+                    HAS_CORE_ARCH,
+                    any(target_arch = "x86", target_arch = "x86_64")
+                ))]
+                unsafe {
+                    arch::_mm_prefetch(ptr as *const i8, arch::$read)
+                };
+
+                #[cfg(all(
+                    feature = "prefetch_hints",
+                    HAS_ASM,
+                    not(miri),
+                    target_arch = "aarch64"
+                ))]
+                unsafe {
+                    asm!(
+                        concat!("prfm ", $pld, ", [{0}]"),
+                        in(reg) ptr,
+                        options(nostack, preserves_flags, readonly)
+                    )
+                };
+
+                #[cfg(all(
+                    feature = "prefetch_hints",
+                    HAS_PREFETCH_INTRINSICS,
+                    not(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64"))),
+                    not(all(HAS_ASM, not(miri), target_arch = "aarch64"))
+                ))]
+                unsafe {
+                    prefetch_read_data::<u8, $level>(ptr)
+                };
+            }
+
+            #[inline(always)]
+            #[allow(unused_variables, unused_unsafe)]
+            fn write(ptr: *const u8) {
+                #[cfg(all(
+                    feature = "prefetch_hints",
+                    HAS_CORE_ARCH,
+                    any(target_arch = "x86", target_arch = "x86_64")
+                ))]
+                unsafe {
+                    arch::_mm_prefetch(ptr as *const i8, arch::$write)
+                };
+
+                #[cfg(all(
+                    feature = "prefetch_hints",
+                    HAS_ASM,
+                    not(miri),
+                    target_arch = "aarch64"
+                ))]
+                unsafe {
+                    asm!(
+                        concat!("prfm ", $pst, ", [{0}]"),
+                        in(reg) ptr,
+                        options(nostack, preserves_flags, readonly)
+                    )
+                };
+
+                #[cfg(all(
+                    feature = "prefetch_hints",
+                    HAS_PREFETCH_INTRINSICS,
+                    not(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64"))),
+                    not(all(HAS_ASM, not(miri), target_arch = "aarch64"))
+                ))]
+                unsafe {
+                    prefetch_write_data::<u8, $level>(ptr)
+                };
+            }
+        }
+    };
+}
+
+locality! {
+    /// data which will be used soon, fetched into every level of cache
+    T0,
+    x86: _MM_HINT_T0 / _MM_HINT_ET0,
+    aarch64: "pldl1keep" / "pstl1keep",
+    intrinsic: 3
+}
+
+locality! {
+    /// fetched into L2 and outward
+    T1,
+    x86: _MM_HINT_T1 / _MM_HINT_ET1,
+    aarch64: "pldl2keep" / "pstl2keep",
+    intrinsic: 2
+}
+
+locality! {
+    /// fetched into L3 and outward
+    T2,
+    x86: _MM_HINT_T2 / _MM_HINT_ET1,
+    aarch64: "pldl3keep" / "pstl3keep",
+    intrinsic: 1
+}
+
+locality! {
+    /// data which will be used once, fetched while minimizing how much
+    /// of the cache it displaces
+    Nta,
+    x86: _MM_HINT_NTA / _MM_HINT_ET1,
+    aarch64: "pldl1strm" / "pstl1strm",
+    intrinsic: 0
+}
+
+/// hints to the processor the line holding `ptr` will be read.
+///
+/// This never faults, so `ptr` may be dangling or past the end of a
+/// buffer. When `prefetch_hints` is not enabled this is a `nop`.
+#[inline(always)]
+pub fn prefetch_read<L: Locality>(ptr: *const u8) {
+    L::read(ptr)
+}
+
+/// hints to the processor the line holding `ptr` will be written,
+/// so it may be fetched in an exclusive state.
+///
+/// This never faults, so `ptr` may be dangling or past the end of a
+/// buffer. When `prefetch_hints` is not enabled this is a `nop`.
+#[inline(always)]
+pub fn prefetch_write<L: Locality>(ptr: *const u8) {
+    L::write(ptr)
+}
+
+/// prefetch_ahead hints the line `distance` bytes past the start of
+/// `buffer` will be read soon, for loops which consume a buffer front
+/// to back. Nothing is hinted when that is beyond the end of `buffer`.
+///
+/// `distance` should cover the latency of a memory load, a few hundred
+/// bytes for most loops.
+#[allow(unused_variables)]
+#[inline(always)]
+pub fn prefetch_ahead<T: Sized>(buffer: &[T], distance: usize) {
+    #[cfg(feature = "prefetch_hints")]
+    {
+        if distance < size_of_val(buffer) {
+            let ptr = buffer.as_ptr() as *const u8;
+            prefetch_read::<T0>(ptr.wrapping_add(distance));
+        }
+    }
+}
+
 /// walks a buffer and hints to processor that we may want the whole thing
 /// in cache.
 ///
@@ -17,67 +259,66 @@ pub fn prefetch_buffer<T: Sized>(buffer: &[T]) {
     }
 }
 
-/// prefetch will provide some hinting to the CPU we want data within the cache
-/// it does not specify which cache. This may only work on
-#[allow(dead_code, unused_variables)]
+/// prefetch will provide some hinting to the CPU we want data within the cache,
+/// it is `prefetch_read::<T2>`.
 #[inline(always)]
 pub fn prefetch<T>(ptr: *const T) {
-    #[cfg(all(
-        not(feature = "std"),
-        target_arch = "x86_64",
-        feature = "prefetch_hints",
-        not(feature = "RUSTC_NIGHTLY")
-    ))]
-    {
-        #[cfg(not(feature = "std"))]
-        use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T2};
-
-        #[cfg(feature = "std")]
-        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T2};
-
-        // no need to check if sse is active/un-active. SSE (and SSE2) are part of x86_64's ABI
-        unsafe { _mm_prefetch(ptr as *const i8, _MM_HINT_T2) };
-    }
-
-    #[cfg(all(
-        not(feature = "std"),
-        feature = "RUSTC_NIGHTLY",
-        feature = "prefetch_hints"
-    ))]
-    {
-        unsafe { ::core::intrinsics::prefetch_read_data(ptr, 0) };
-    }
-    #[cfg(all(feature = "std", feature = "RUSTC_NIGHTLY", feature = "prefetch_hints"))]
-    {
-        unsafe { ::std::intrinsics::prefetch_read_data(ptr, 0) };
-    }
+    prefetch_read::<T2>(ptr as *const u8)
 }
 
 /// Assuming you're working with an array, this calculates the (rounded down) number
 /// of items within a cache line. YMMV with alignment.
+///
+/// This uses the detected line size, which is cached after the first call.
 #[inline(always)]
 fn item_per_cache_line<T: Sized>() -> usize {
-    use super::mem::*;
-
-    // cache lines are 64bytes.
-    // The author is aware they are not sometimes for example:
-    // - https://www.mono-project.com/news/2016/09/12/arm64-icache/
-    // - https://reviews.llvm.org/rG457ddd311a164b31c7ef431abd4fd5dba84683f4
-    //
-    // but honestly, it doesn't matter. prefetching hints can be
-    // discarded anyways
-    let cache_line_size = 64usize;
     let item_size = size_of::<T>();
-    if item_size == 0 || item_size >= cache_line_size {
+    let line = line_size();
+    if item_size == 0 || item_size >= line {
         return 1;
     }
-    let size = (0..cache_line_size)
-        .map(|i| (i, i * item_size))
-        .filter(|(_, s)| *s >= cache_line_size)
-        .map(|(i, _)| i)
-        .next();
-    match size {
-        Option::None => inconceivable!(),
-        Option::Some(x) => x,
+    line / item_size
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::cache::line_size;
+    use super::{
+        item_per_cache_line, prefetch_ahead, prefetch_buffer, prefetch_read, prefetch_write, Nta,
+        T0, T1, T2,
+    };
+
+    #[test]
+    fn items_per_cache_line() {
+        let line = line_size();
+        assert_eq!(item_per_cache_line::<u8>(), line);
+        assert_eq!(item_per_cache_line::<u32>(), line / 4);
+        assert_eq!(item_per_cache_line::<[u8; 24]>(), line / 24);
+        assert_eq!(item_per_cache_line::<[u8; 256]>(), 1);
+        assert_eq!(item_per_cache_line::<()>(), 1);
+    }
+
+    #[test]
+    fn hints_do_not_fault() {
+        let buffer = [0u8; 300];
+        let ptr = buffer.as_ptr();
+        prefetch_read::<T0>(ptr);
+        prefetch_read::<T1>(ptr);
+        prefetch_read::<T2>(ptr);
+        prefetch_read::<Nta>(ptr);
+        prefetch_write::<T0>(ptr);
+        prefetch_write::<T1>(ptr);
+        prefetch_write::<T2>(ptr);
+        prefetch_write::<Nta>(ptr);
+
+        // past the end is permitted
+        prefetch_read::<T0>(ptr.wrapping_add(4096));
+        prefetch_ahead(&buffer[..], 256);
+        prefetch_ahead(&buffer[..], 4096);
+        prefetch_ahead(&buffer[299..], 256);
+
+        prefetch_buffer(&buffer[..]);
+        prefetch_buffer(&[0u64; 17][..]);
     }
 }
@@ -0,0 +1,1119 @@
+#[cfg(not(feature = "std"))]
+use core::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+#[cfg(feature = "std")]
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
+#[cfg(all(not(feature = "std"), HAS_CORE_ARCH, target_arch = "x86"))]
+use core::arch::x86 as arch;
+#[cfg(all(not(feature = "std"), HAS_CORE_ARCH, target_arch = "x86_64"))]
+use core::arch::x86_64 as arch;
+#[cfg(all(feature = "std", HAS_CORE_ARCH, target_arch = "x86"))]
+use std::arch::x86 as arch;
+#[cfg(all(feature = "std", HAS_CORE_ARCH, target_arch = "x86_64"))]
+use std::arch::x86_64 as arch;
+
+#[cfg(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64")))]
+use super::cpu::{has, Feature};
+use super::error::Error;
+use super::limits::Limits;
+use super::mem::size_of;
+use super::numbers::{Num, PrimativeNumber};
+
+/// Varint is implemented for every `Num<T>`, it provides the
+/// conversions the encoders below are written in terms of.
+pub trait Varint:
+    PrimativeNumber
+    + Not<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + BitAnd<Self, Output = Self>
+    + BitOr<Self, Output = Self>
+    + BitXor<Self, Output = Self>
+{
+    /// the unsigned type of the same width
+    type Unsigned: Varint<Unsigned = Self::Unsigned>;
+
+    /// width in bits
+    fn bits() -> u32;
+
+    /// reinterprets the bits as unsigned
+    fn to_unsigned(self) -> Self::Unsigned;
+
+    /// reinterprets unsigned bits as `Self`
+    fn from_unsigned(value: Self::Unsigned) -> Self;
+
+    /// the low 64 bits, sign extended when `Self` is narrower
+    fn low_u64(self) -> u64;
+
+    /// `value` truncated to the width of `Self`
+    fn from_low_u64(value: u64) -> Self;
+}
+
+/// SignedVarint marks the signed `Num<T>`, whose `>>` is arithmetic.
+pub trait SignedVarint: Varint {}
+
+macro_rules! implement_varint {
+    ($kind: ident, $unsigned: ident) => {
+        #[allow(clippy::unnecessary_cast)]
+        impl Varint for Num<$kind> {
+            type Unsigned = Num<$unsigned>;
+
+            #[inline(always)]
+            fn bits() -> u32 {
+                (size_of::<$kind>() * 8) as u32
+            }
+
+            #[inline(always)]
+            fn to_unsigned(self) -> Num<$unsigned> {
+                <Num<$unsigned> as From<$unsigned>>::from(self.inner() as $unsigned)
+            }
+
+            #[inline(always)]
+            fn from_unsigned(value: Num<$unsigned>) -> Num<$kind> {
+                <Num<$kind> as From<$kind>>::from(value.inner() as $kind)
+            }
+
+            #[inline(always)]
+            fn low_u64(self) -> u64 {
+                self.inner() as u64
+            }
+
+            #[inline(always)]
+            fn from_low_u64(value: u64) -> Num<$kind> {
+                <Num<$kind> as From<$kind>>::from(value as $kind)
+            }
+        }
+    };
+    ($kind: ident, $unsigned: ident, signed) => {
+        implement_varint!($kind, $unsigned);
+        impl SignedVarint for Num<$kind> {}
+    };
+}
+
+implement_varint!(u8, u8);
+implement_varint!(i8, u8, signed);
+implement_varint!(u16, u16);
+implement_varint!(i16, u16, signed);
+implement_varint!(u32, u32);
+implement_varint!(i32, u32, signed);
+implement_varint!(u64, u64);
+implement_varint!(i64, u64, signed);
+implement_varint!(usize, usize);
+implement_varint!(isize, usize, signed);
+#[cfg(HAS_U128)]
+implement_varint!(u128, u128);
+#[cfg(HAS_U128)]
+implement_varint!(i128, u128, signed);
+
+/// number of 7 bit groups needed to hold `used` bits, at least one
+#[inline(always)]
+fn groups_of_seven(used: u32) -> usize {
+    1 + (used.saturating_sub(1) / 7) as usize
+}
+
+/// number of bytes `encode_leb128` (and `encode_prefix` below 8) writes
+#[inline]
+pub fn leb128_len<N: Varint>(value: N) -> usize {
+    groups_of_seven(N::bits() - value.to_unsigned().leading_zeros())
+}
+
+/// number of bytes `encode_sleb128` writes
+#[inline]
+pub fn sleb128_len<S: SignedVarint>(value: S) -> usize {
+    // the sign needs one bit past the magnitude
+    let redundant = if value < S::default() {
+        (!value).leading_zeros()
+    } else {
+        value.leading_zeros()
+    };
+    groups_of_seven(S::bits() - redundant + 1)
+}
+
+/// encode_leb128 writes `value` as unsigned LEB128, seven bits per byte
+/// least significant first, with the high bit set on all but the last.
+///
+/// Signed values are encoded by their bits, so negative values take the
+/// most bytes, see `zigzag_encode` and `encode_sleb128`. Returns the
+/// number of bytes written, or `Error::OutputFull` without writing.
+#[inline]
+pub fn encode_leb128<N: Varint>(value: N, output: &mut [u8]) -> Result<usize, Error> {
+    let length = leb128_len(value);
+    if output.len() < length {
+        return Err(Error::OutputFull {
+            offset: output.len(),
+        });
+    }
+    let mut value = value.to_unsigned();
+    for byte in output[..length - 1].iter_mut() {
+        *byte = (value.low_u64() as u8) | 0x80;
+        value = value >> 7;
+    }
+    output[length - 1] = value.low_u64() as u8;
+    Ok(length)
+}
+
+/// decode_leb128 reads an unsigned LEB128 value, returning it and the
+/// number of bytes it took.
+///
+/// Encodings which are longer than `N` permits, or which set bits past
+/// its width, are reported as `Error::CorruptBlock` at the offending
+/// byte. Non-minimal encodings (trailing `0x80`s) are accepted.
+#[inline]
+pub fn decode_leb128<N: Varint>(input: &[u8]) -> Result<(N, usize), Error> {
+    let bits = N::bits();
+    let mut value = N::Unsigned::default();
+    let mut shift = 0u32;
+    for (index, byte) in input.iter().enumerate() {
+        let payload = (*byte & 0x7F) as u64;
+        let last = *byte & 0x80 == 0;
+        // no width is a multiple of 7, so the widest encoding always
+        // ends with a partially used byte
+        if bits - shift < 7 && (!last || payload >> (bits - shift) != 0) {
+            return Err(Error::CorruptBlock { offset: index });
+        }
+        value = value | (N::Unsigned::from_low_u64(payload) << shift);
+        if last {
+            return Ok((N::from_unsigned(value), index + 1));
+        }
+        shift += 7;
+    }
+    Err(Error::Truncated {
+        offset: input.len(),
+    })
+}
+
+/// encode_sleb128 writes `value` as signed LEB128, where bit 6 of the
+/// last byte is the sign. Small negative values stay short.
+///
+/// Returns the number of bytes written, or `Error::OutputFull` without
+/// writing.
+#[inline]
+pub fn encode_sleb128<S: SignedVarint>(value: S, output: &mut [u8]) -> Result<usize, Error> {
+    let length = sleb128_len(value);
+    if output.len() < length {
+        return Err(Error::OutputFull {
+            offset: output.len(),
+        });
+    }
+    let mut value = value;
+    for byte in output[..length - 1].iter_mut() {
+        *byte = (value.low_u64() as u8) | 0x80;
+        value = value >> 7;
+    }
+    output[length - 1] = (value.low_u64() as u8) & 0x7F;
+    Ok(length)
+}
+
+/// decode_sleb128 reads a signed LEB128 value, returning it and the
+/// number of bytes it took.
+///
+/// Bits past the width of `S` must repeat its sign, otherwise (or when
+/// the encoding is too long) `Error::CorruptBlock` is returned.
+#[inline]
+pub fn decode_sleb128<S: SignedVarint>(input: &[u8]) -> Result<(S, usize), Error> {
+    let bits = S::bits();
+    let mut value = S::default();
+    let mut shift = 0u32;
+    for (index, byte) in input.iter().enumerate() {
+        let payload = (*byte & 0x7F) as u64;
+        let last = *byte & 0x80 == 0;
+        if bits - shift < 7 {
+            let extra = payload >> (bits - shift - 1);
+            if !last || (extra != 0 && extra != 0x7F >> (bits - shift - 1)) {
+                return Err(Error::CorruptBlock { offset: index });
+            }
+        }
+        value = value | (S::from_low_u64(payload) << shift);
+        shift += 7;
+        if last {
+            if shift < bits && payload & 0x40 != 0 {
+                value = value | (!S::default() << shift);
+            }
+            return Ok((value, index + 1));
+        }
+    }
+    Err(Error::Truncated {
+        offset: input.len(),
+    })
+}
+
+/// zigzag_encode maps signed values onto unsigned ones so that small
+/// magnitudes stay small, `0, -1, 1, -2` become `0, 1, 2, 3`.
+#[inline(always)]
+pub fn zigzag_encode<S: SignedVarint>(value: S) -> S::Unsigned {
+    ((value << 1) ^ (value >> (S::bits() - 1))).to_unsigned()
+}
+
+/// inverse of `zigzag_encode`
+#[inline(always)]
+pub fn zigzag_decode<S: SignedVarint>(value: S::Unsigned) -> S {
+    // moving the low bit to the top and shifting back smears it
+    let sign = S::from_unsigned(value << (S::bits() - 1)) >> (S::bits() - 1);
+    S::from_unsigned(value >> 1) ^ sign
+}
+
+/// number of bytes `encode_prefix` writes
+#[inline]
+pub fn prefix_len<N: Varint>(value: N) -> usize {
+    match leb128_len(value) {
+        length @ 1..=8 => length,
+        _ => 1 + size_of::<<N::Unsigned as PrimativeNumber>::Primative>(),
+    }
+}
+
+/// encode_prefix writes `value` as a prefix varint. The number of
+/// trailing zeros in the first byte, plus one, is the length, and the
+/// value follows those marker bits as little endian, 7 bits per byte.
+///
+/// Values needing more than 56 bits are written as a zero byte followed
+/// by the whole value as little endian. As the length is known from the
+/// first byte decoding needs no loop, unlike LEB128. Returns the number
+/// of bytes written, or `Error::OutputFull` without writing.
+#[inline]
+pub fn encode_prefix<N: Varint>(value: N, output: &mut [u8]) -> Result<usize, Error> {
+    let length = prefix_len(value);
+    if output.len() < length {
+        return Err(Error::OutputFull {
+            offset: output.len(),
+        });
+    }
+    let value = value.to_unsigned();
+    if length > 8 {
+        output[0] = 0;
+        value.write_value_le(&mut output[1..length]);
+        return Ok(length);
+    }
+    let word = (value.low_u64() << length) | (1 << (length - 1));
+    for (index, byte) in output[..length].iter_mut().enumerate() {
+        *byte = (word >> (index * 8)) as u8;
+    }
+    Ok(length)
+}
+
+/// decode_prefix reads a prefix varint, returning it and the number of
+/// bytes it took.
+///
+/// When 8 bytes are available the value is read with a single load and
+/// masked, so there is no branch per byte. A value wider than `N` is
+/// reported as `Error::CorruptBlock`.
+#[inline]
+pub fn decode_prefix<N: Varint>(input: &[u8]) -> Result<(N, usize), Error> {
+    let first = match input.first() {
+        Option::Some(first) => *first,
+        Option::None => return Err(Error::Truncated { offset: 0 }),
+    };
+    if first == 0 {
+        let width = size_of::<<N::Unsigned as PrimativeNumber>::Primative>();
+        if input.len() < 1 + width {
+            return Err(Error::Truncated {
+                offset: input.len(),
+            });
+        }
+        let value = N::Unsigned::read_value_le(&input[1..1 + width]);
+        return Ok((N::from_unsigned(value), 1 + width));
+    }
+
+    let length = first.trailing_zeros() as usize + 1;
+    let word = if input.len() >= 8 {
+        Num::<u64>::read_value_le(input).inner()
+    } else if input.len() >= length {
+        input[..length]
+            .iter()
+            .rev()
+            .fold(0u64, |acc, byte| (acc << 8) | (*byte as u64))
+    } else {
+        return Err(Error::Truncated {
+            offset: input.len(),
+        });
+    };
+    let word = if length < 8 {
+        word & ((1u64 << (length * 8)) - 1)
+    } else {
+        word
+    };
+    let value = word >> length;
+    if N::bits() < 64 && value >> N::bits() != 0 {
+        return Err(Error::CorruptBlock { offset: 0 });
+    }
+    Ok((N::from_unsigned(N::Unsigned::from_low_u64(value)), length))
+}
+
+/// decode_leb128_length reads a LEB128 count of `size` byte elements,
+/// such as the length prefix of an array, returning it and the number of
+/// bytes it took.
+///
+/// The `count * size` bytes the elements take are checked against
+/// `limits.max_output`, so a caller may allocate them without trusting
+/// the input. A larger (or unrepresentable) count is reported as
+/// `Error::LimitExceeded` at offset 0.
+#[inline]
+pub fn decode_leb128_length(
+    input: &[u8],
+    size: usize,
+    limits: &Limits,
+) -> Result<(usize, usize), Error> {
+    let (count, length) = decode_leb128::<Num<u64>>(input)?;
+    Ok((check_length(count.inner(), size, limits)?, length))
+}
+
+/// decode_prefix_length is `decode_leb128_length` for a prefix varint
+#[inline]
+pub fn decode_prefix_length(
+    input: &[u8],
+    size: usize,
+    limits: &Limits,
+) -> Result<(usize, usize), Error> {
+    let (count, length) = decode_prefix::<Num<u64>>(input)?;
+    Ok((check_length(count.inner(), size, limits)?, length))
+}
+
+/// checks `count` elements of `size` bytes fit in `limits` and `usize`
+#[inline]
+fn check_length(count: u64, size: usize, limits: &Limits) -> Result<usize, Error> {
+    match count.checked_mul(size as u64) {
+        Option::Some(bytes) if count <= usize::MAX as u64 => {
+            limits.check_output(bytes, 0)?;
+            Ok(count as usize)
+        }
+        _ => Err(Error::LimitExceeded { offset: 0 }),
+    }
+}
+
+/// number of Stream VByte control bytes for `count` values, 2 bits each
+#[inline(always)]
+fn control_len(count: usize) -> usize {
+    count / 4 + usize::from(count & 3 != 0)
+}
+
+/// number of bytes a `u32` is stored in by Stream VByte, from 1 to 4
+#[inline(always)]
+fn value_len(value: u32) -> usize {
+    4 - (value.leading_zeros().min(24) / 8) as usize
+}
+
+/// number of data bytes described by a Stream VByte control byte
+#[cfg(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline(always)]
+fn group_len(control: u8) -> usize {
+    let control = control as usize;
+    4 + (control & 3) + ((control >> 2) & 3) + ((control >> 4) & 3) + (control >> 6)
+}
+
+/// number of bytes `stream_vbyte_encode` writes for `values`
+#[inline]
+pub fn stream_vbyte_len(values: &[u32]) -> usize {
+    control_len(values.len()) + values.iter().map(|value| value_len(*value)).sum::<usize>()
+}
+
+/// stream_vbyte_encode writes `values` as Stream VByte. Each value takes
+/// 1 to 4 little endian bytes, and the lengths are stored separately as
+/// 2 bit codes, 4 to a control byte, ahead of all the data.
+///
+/// Keeping the lengths apart lets a decoder find where 4 values start
+/// from a single control byte, without a dependency between them. The
+/// count is not stored. Returns the number of bytes written, or
+/// `Error::OutputFull` without writing.
+pub fn stream_vbyte_encode(values: &[u32], output: &mut [u8]) -> Result<usize, Error> {
+    let length = stream_vbyte_len(values);
+    if output.len() < length {
+        return Err(Error::OutputFull {
+            offset: output.len(),
+        });
+    }
+    let (control, data) = output[..length].split_at_mut(control_len(values.len()));
+    let mut position = 0;
+    for (group, chunk) in values.chunks(4).enumerate() {
+        let mut codes = 0u8;
+        for (lane, value) in chunk.iter().enumerate() {
+            let width = value_len(*value);
+            for (index, byte) in data[position..position + width].iter_mut().enumerate() {
+                *byte = (*value >> (index * 8)) as u8;
+            }
+            position += width;
+            codes |= ((width - 1) as u8) << (lane * 2);
+        }
+        control[group] = codes;
+    }
+    Ok(length)
+}
+
+/// stream_vbyte_decode reads `output.len()` values written by
+/// `stream_vbyte_encode`, returning the number of bytes consumed.
+///
+/// Whole groups are decoded with one 16 byte load and a `pshufb` when
+/// the CPU has SSSE3, otherwise with 4 unaligned loads. The final few
+/// values (where 16 bytes may not remain) are decoded one at a time.
+pub fn stream_vbyte_decode(input: &[u8], output: &mut [u32]) -> Result<usize, Error> {
+    let control_length = control_len(output.len());
+    if input.len() < control_length {
+        return Err(Error::Truncated {
+            offset: input.len(),
+        });
+    }
+    let (control, data) = input.split_at(control_length);
+    let (groups, mut position) = decode_groups(&control[..output.len() / 4], data, output);
+    for index in groups * 4..output.len() {
+        let width = ((control[index / 4] >> ((index % 4) * 2)) & 3) as usize + 1;
+        let bytes = match data.get(position..position + width) {
+            Option::Some(bytes) => bytes,
+            Option::None => {
+                return Err(Error::Truncated {
+                    offset: input.len(),
+                })
+            }
+        };
+        output[index] = bytes
+            .iter()
+            .rev()
+            .fold(0u32, |acc, byte| (acc << 8) | (*byte as u32));
+        position += width;
+    }
+    Ok(control_length + position)
+}
+
+/// decodes whole groups while 16 bytes of `data` remain, returning the
+/// number of groups decoded and the data consumed.
+#[inline]
+fn decode_groups(control: &[u8], data: &[u8], output: &mut [u32]) -> (usize, usize) {
+    #[cfg(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        if has(Feature::Ssse3) {
+            // the CPU was just checked for SSSE3
+            return unsafe { decode_groups_ssse3(control, data, output) };
+        }
+    }
+    decode_groups_portable(control, data, output)
+}
+
+/// portable form of `decode_groups`, every value is a 4 byte load which
+/// is masked to its width.
+#[inline]
+fn decode_groups_portable(control: &[u8], data: &[u8], output: &mut [u32]) -> (usize, usize) {
+    const MASK: [u32; 4] = [0xFF, 0xFFFF, 0xFF_FFFF, 0xFFFF_FFFF];
+    let mut position = 0;
+    for (group, codes) in control.iter().enumerate() {
+        if data.len() - position < 16 {
+            return (group, position);
+        }
+        let mut offset = position;
+        for (lane, value) in output[group * 4..group * 4 + 4].iter_mut().enumerate() {
+            let code = ((*codes >> (lane * 2)) & 3) as usize;
+            *value = Num::<u32>::read_value_le_at(data, offset).inner() & MASK[code];
+            offset += code + 1;
+        }
+        position = offset;
+    }
+    (control.len(), position)
+}
+
+/// SSSE3 form of `decode_groups`, `SHUFFLE` moves each value's bytes into
+/// its lane and zeroes the rest.
+#[cfg(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64")))]
+#[target_feature(enable = "ssse3")]
+unsafe fn decode_groups_ssse3(control: &[u8], data: &[u8], output: &mut [u32]) -> (usize, usize) {
+    debug_assert!(output.len() >= control.len() * 4);
+    let mut position = 0;
+    for (group, codes) in control.iter().enumerate() {
+        if data.len() - position < 16 {
+            return (group, position);
+        }
+        let bytes = arch::_mm_loadu_si128(data.as_ptr().add(position) as *const arch::__m128i);
+        let shuffle =
+            arch::_mm_loadu_si128(SHUFFLE[*codes as usize].as_ptr() as *const arch::__m128i);
+        arch::_mm_storeu_si128(
+            output.as_mut_ptr().add(group * 4) as *mut arch::__m128i,
+            arch::_mm_shuffle_epi8(bytes, shuffle),
+        );
+        position += group_len(*codes);
+    }
+    (control.len(), position)
+}
+
+/// `pshufb` zeroes a byte when the high bit of its index is set
+#[cfg(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64")))]
+const Z: u8 = 0x80;
+
+/// for every control byte, the data byte each output byte is taken from
+#[cfg(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64")))]
+static SHUFFLE: [[u8; 16]; 256] = [
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, Z, Z, Z, 3, Z, Z, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, Z, Z, Z, 4, Z, Z, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, Z, Z, Z, 5, Z, Z, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, Z, Z, Z, 6, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, Z, Z, Z, 4, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, Z, Z, Z, 5, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, Z, Z, Z, 6, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, Z, Z, Z, 7, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, Z, Z, Z, 5, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, Z, Z, Z, 6, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, Z, Z, Z, 7, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, Z, Z, Z, 8, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, Z, Z, Z, 6, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, Z, Z, Z, 7, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, Z, Z, Z, 8, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, Z, Z, Z, 9, Z, Z, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, Z, Z, 4, Z, Z, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, Z, Z, 5, Z, Z, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, Z, Z, 6, Z, Z, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, Z, Z, 7, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, Z, Z, 5, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, Z, Z, 6, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, Z, Z, 7, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, Z, Z, 8, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, Z, Z, 6, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, Z, Z, 7, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, Z, Z, 8, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, Z, Z, 9, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, Z, Z, 7, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, Z, Z, 8, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, Z, Z, 9, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, Z, Z, 10, Z, Z, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, 4, Z, 5, Z, Z, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, 5, Z, 6, Z, Z, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, 6, Z, 7, Z, Z, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, 7, Z, 8, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, 5, Z, 6, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, 6, Z, 7, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, 7, Z, 8, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, 8, Z, 9, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, 6, Z, 7, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, 7, Z, 8, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, 8, Z, 9, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, 9, Z, 10, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, 7, Z, 8, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, 8, Z, 9, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, 9, Z, 10, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, Z, 11, Z, Z, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, 4, 5, 6, Z, Z, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, 5, 6, 7, Z, Z, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, 6, 7, 8, Z, Z, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, 7, 8, 9, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, 5, 6, 7, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, 6, 7, 8, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, 7, 8, 9, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, 8, 9, 10, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, 6, 7, 8, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, 7, 8, 9, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, 8, 9, 10, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, 9, 10, 11, Z, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, 7, 8, 9, Z, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, 8, 9, 10, Z, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, 9, 10, 11, Z, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, Z, Z, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, Z, Z, Z, 3, 4, Z, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, Z, Z, Z, 4, 5, Z, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, Z, Z, Z, 5, 6, Z, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, Z, Z, Z, 6, 7, Z, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, Z, Z, Z, 4, 5, Z, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, Z, Z, Z, 5, 6, Z, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, Z, Z, Z, 6, 7, Z, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, Z, Z, Z, 7, 8, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, Z, Z, Z, 5, 6, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, Z, Z, Z, 6, 7, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, Z, Z, Z, 7, 8, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, Z, Z, Z, 8, 9, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, Z, Z, Z, 6, 7, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, Z, Z, Z, 7, 8, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, Z, Z, Z, 8, 9, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, Z, Z, Z, 9, 10, Z, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, Z, Z, 4, 5, Z, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, Z, Z, 5, 6, Z, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, Z, Z, 6, 7, Z, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, Z, Z, 7, 8, Z, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, Z, Z, 5, 6, Z, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, Z, Z, 6, 7, Z, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, Z, Z, 7, 8, Z, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, Z, Z, 8, 9, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, Z, Z, 6, 7, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, Z, Z, 7, 8, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, Z, Z, 8, 9, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, Z, Z, 9, 10, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, Z, Z, 7, 8, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, Z, Z, 8, 9, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, Z, Z, 9, 10, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, Z, Z, 10, 11, Z, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, 4, Z, 5, 6, Z, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, 5, Z, 6, 7, Z, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, 6, Z, 7, 8, Z, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, 7, Z, 8, 9, Z, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, 5, Z, 6, 7, Z, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, 6, Z, 7, 8, Z, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, 7, Z, 8, 9, Z, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, 8, Z, 9, 10, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, 6, Z, 7, 8, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, 7, Z, 8, 9, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, 8, Z, 9, 10, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, 9, Z, 10, 11, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, 7, Z, 8, 9, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, 8, Z, 9, 10, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, 9, Z, 10, 11, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, Z, 11, 12, Z, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, 4, 5, 6, 7, Z, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, 5, 6, 7, 8, Z, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, 6, 7, 8, 9, Z, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, 7, 8, 9, 10, Z, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, 5, 6, 7, 8, Z, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, 6, 7, 8, 9, Z, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, 7, 8, 9, 10, Z, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, 8, 9, 10, 11, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, 6, 7, 8, 9, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, 7, 8, 9, 10, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, 8, 9, 10, 11, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, 9, 10, 11, 12, Z, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, Z, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, Z, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, Z, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, Z, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, Z, Z, Z, 3, 4, 5, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, Z, Z, Z, 4, 5, 6, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, Z, Z, Z, 5, 6, 7, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, Z, Z, Z, 6, 7, 8, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, Z, Z, Z, 4, 5, 6, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, Z, Z, Z, 5, 6, 7, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, Z, Z, Z, 6, 7, 8, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, Z, Z, Z, 7, 8, 9, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, Z, Z, Z, 5, 6, 7, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, Z, Z, Z, 6, 7, 8, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, Z, Z, Z, 7, 8, 9, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, Z, Z, Z, 8, 9, 10, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, Z, Z, Z, 6, 7, 8, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, Z, Z, Z, 7, 8, 9, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, Z, Z, Z, 8, 9, 10, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, Z, Z, Z, 9, 10, 11, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, Z, Z, 4, 5, 6, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, Z, Z, 5, 6, 7, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, Z, Z, 6, 7, 8, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, Z, Z, 7, 8, 9, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, Z, Z, 5, 6, 7, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, Z, Z, 6, 7, 8, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, Z, Z, 7, 8, 9, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, Z, Z, 8, 9, 10, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, Z, Z, 6, 7, 8, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, Z, Z, 7, 8, 9, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, Z, Z, 8, 9, 10, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, Z, Z, 9, 10, 11, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, Z, Z, 7, 8, 9, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, Z, Z, 8, 9, 10, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, Z, Z, 9, 10, 11, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, Z, Z, 10, 11, 12, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, 4, Z, 5, 6, 7, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, 5, Z, 6, 7, 8, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, 6, Z, 7, 8, 9, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, 7, Z, 8, 9, 10, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, 5, Z, 6, 7, 8, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, 6, Z, 7, 8, 9, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, 7, Z, 8, 9, 10, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, 8, Z, 9, 10, 11, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, 6, Z, 7, 8, 9, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, 7, Z, 8, 9, 10, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, 8, Z, 9, 10, 11, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, 9, Z, 10, 11, 12, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, 7, Z, 8, 9, 10, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, 8, Z, 9, 10, 11, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, 9, Z, 10, 11, 12, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, Z, 11, 12, 13, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, 4, 5, 6, 7, 8, Z],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, 5, 6, 7, 8, 9, Z],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, 6, 7, 8, 9, 10, Z],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, 7, 8, 9, 10, 11, Z],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, 5, 6, 7, 8, 9, Z],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, 6, 7, 8, 9, 10, Z],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, 7, 8, 9, 10, 11, Z],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, 8, 9, 10, 11, 12, Z],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, 6, 7, 8, 9, 10, Z],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, 7, 8, 9, 10, 11, Z],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, 8, 9, 10, 11, 12, Z],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, 9, 10, 11, 12, 13, Z],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, Z],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, Z],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, Z],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, Z],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, Z, Z, Z, 3, 4, 5, 6],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, Z, Z, Z, 4, 5, 6, 7],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, Z, Z, Z, 5, 6, 7, 8],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, Z, Z, Z, 6, 7, 8, 9],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, Z, Z, Z, 4, 5, 6, 7],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, Z, Z, Z, 5, 6, 7, 8],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, Z, Z, Z, 6, 7, 8, 9],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, Z, Z, Z, 7, 8, 9, 10],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, Z, Z, Z, 5, 6, 7, 8],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, Z, Z, Z, 6, 7, 8, 9],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, Z, Z, Z, 7, 8, 9, 10],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, Z, Z, Z, 8, 9, 10, 11],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, Z, Z, Z, 6, 7, 8, 9],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, Z, Z, Z, 7, 8, 9, 10],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, Z, Z, Z, 8, 9, 10, 11],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, Z, Z, Z, 9, 10, 11, 12],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, Z, Z, 4, 5, 6, 7],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, Z, Z, 5, 6, 7, 8],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, Z, Z, 6, 7, 8, 9],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, Z, Z, 7, 8, 9, 10],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, Z, Z, 5, 6, 7, 8],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, Z, Z, 6, 7, 8, 9],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, Z, Z, 7, 8, 9, 10],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, Z, Z, 8, 9, 10, 11],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, Z, Z, 6, 7, 8, 9],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, Z, Z, 7, 8, 9, 10],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, Z, Z, 8, 9, 10, 11],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, Z, Z, 9, 10, 11, 12],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, Z, Z, 7, 8, 9, 10],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, Z, Z, 8, 9, 10, 11],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, Z, Z, 9, 10, 11, 12],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, Z, Z, 10, 11, 12, 13],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, 4, Z, 5, 6, 7, 8],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, 5, Z, 6, 7, 8, 9],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, 6, Z, 7, 8, 9, 10],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, 7, Z, 8, 9, 10, 11],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, 5, Z, 6, 7, 8, 9],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, 6, Z, 7, 8, 9, 10],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, 7, Z, 8, 9, 10, 11],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, 8, Z, 9, 10, 11, 12],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, 6, Z, 7, 8, 9, 10],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, 7, Z, 8, 9, 10, 11],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, 8, Z, 9, 10, 11, 12],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, 9, Z, 10, 11, 12, 13],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, 7, Z, 8, 9, 10, 11],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, 8, Z, 9, 10, 11, 12],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, 9, Z, 10, 11, 12, 13],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, Z, 11, 12, 13, 14],
+    [0, Z, Z, Z, 1, Z, Z, Z, 2, 3, 4, 5, 6, 7, 8, 9],
+    [0, 1, Z, Z, 2, Z, Z, Z, 3, 4, 5, 6, 7, 8, 9, 10],
+    [0, 1, 2, Z, 3, Z, Z, Z, 4, 5, 6, 7, 8, 9, 10, 11],
+    [0, 1, 2, 3, 4, Z, Z, Z, 5, 6, 7, 8, 9, 10, 11, 12],
+    [0, Z, Z, Z, 1, 2, Z, Z, 3, 4, 5, 6, 7, 8, 9, 10],
+    [0, 1, Z, Z, 2, 3, Z, Z, 4, 5, 6, 7, 8, 9, 10, 11],
+    [0, 1, 2, Z, 3, 4, Z, Z, 5, 6, 7, 8, 9, 10, 11, 12],
+    [0, 1, 2, 3, 4, 5, Z, Z, 6, 7, 8, 9, 10, 11, 12, 13],
+    [0, Z, Z, Z, 1, 2, 3, Z, 4, 5, 6, 7, 8, 9, 10, 11],
+    [0, 1, Z, Z, 2, 3, 4, Z, 5, 6, 7, 8, 9, 10, 11, 12],
+    [0, 1, 2, Z, 3, 4, 5, Z, 6, 7, 8, 9, 10, 11, 12, 13],
+    [0, 1, 2, 3, 4, 5, 6, Z, 7, 8, 9, 10, 11, 12, 13, 14],
+    [0, Z, Z, Z, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+    [0, 1, Z, Z, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13],
+    [0, 1, 2, Z, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+];
+
+#[cfg(test)]
+mod test {
+
+    use super::super::error::Error;
+    use super::super::limits::Limits;
+    use super::super::numbers::{Num, PrimativeNumber};
+    use super::{
+        decode_groups_portable, decode_leb128, decode_leb128_length, decode_prefix,
+        decode_prefix_length, decode_sleb128, encode_leb128, encode_prefix, encode_sleb128,
+        leb128_len, prefix_len, sleb128_len, stream_vbyte_decode, stream_vbyte_encode,
+        stream_vbyte_len, zigzag_decode, zigzag_encode, Varint,
+    };
+
+    /// calls `check` with zero, the extremes, and the values either side
+    /// of every power of two (so every 7 bit boundary)
+    fn boundaries<N: Varint, F: FnMut(N)>(mut check: F) {
+        let zero = N::default();
+        check(zero);
+        check(<N as From<N::Primative>>::from(
+            <N as PrimativeNumber>::max(),
+        ));
+        check(<N as From<N::Primative>>::from(
+            <N as PrimativeNumber>::min(),
+        ));
+        for shift in 0..N::bits() {
+            let power = N::from_low_u64(1) << shift;
+            check(power);
+            check(!power);
+            check(!(!zero << shift));
+            check(!zero << shift);
+        }
+    }
+
+    macro_rules! round_trips {
+        ($name: ident, $kind: ident) => {
+            #[test]
+            fn $name() {
+                let mut buffer = [0u8; 32];
+                boundaries::<Num<$kind>, _>(|value| {
+                    let length = encode_leb128(value, &mut buffer).unwrap();
+                    assert_eq!(length, leb128_len(value));
+                    assert_eq!(decode_leb128(&buffer[..length]), Ok((value, length)));
+                    assert_eq!(
+                        decode_leb128::<Num<$kind>>(&buffer[..length - 1]),
+                        Err(Error::Truncated { offset: length - 1 })
+                    );
+
+                    let length = encode_prefix(value, &mut buffer).unwrap();
+                    assert_eq!(length, prefix_len(value));
+                    assert_eq!(decode_prefix(&buffer[..length]), Ok((value, length)));
+                    // trailing data takes the single load path
+                    assert_eq!(decode_prefix(&buffer), Ok((value, length)));
+                    assert_eq!(
+                        decode_prefix::<Num<$kind>>(&buffer[..length - 1]),
+                        Err(Error::Truncated { offset: length - 1 })
+                    );
+                    assert_eq!(
+                        encode_prefix(value, &mut buffer[..length - 1]),
+                        Err(Error::OutputFull { offset: length - 1 })
+                    );
+                });
+            }
+        };
+        ($name: ident, $kind: ident, signed) => {
+            #[test]
+            fn $name() {
+                let mut buffer = [0u8; 32];
+                boundaries::<Num<$kind>, _>(|value| {
+                    let length = encode_sleb128(value, &mut buffer).unwrap();
+                    assert_eq!(length, sleb128_len(value));
+                    assert_eq!(decode_sleb128(&buffer[..length]), Ok((value, length)));
+                    assert_eq!(
+                        decode_sleb128::<Num<$kind>>(&buffer[..length - 1]),
+                        Err(Error::Truncated { offset: length - 1 })
+                    );
+
+                    let zigzag = zigzag_encode(value);
+                    assert_eq!(zigzag_decode::<Num<$kind>>(zigzag), value);
+                    assert_eq!(leb128_len(zigzag), length);
+                });
+            }
+        };
+    }
+
+    round_trips!(round_trips_u8, u8);
+    round_trips!(round_trips_i8, i8);
+    round_trips!(round_trips_signed_i8, i8, signed);
+    round_trips!(round_trips_u16, u16);
+    round_trips!(round_trips_i16, i16);
+    round_trips!(round_trips_signed_i16, i16, signed);
+    round_trips!(round_trips_u32, u32);
+    round_trips!(round_trips_i32, i32);
+    round_trips!(round_trips_signed_i32, i32, signed);
+    round_trips!(round_trips_u64, u64);
+    round_trips!(round_trips_i64, i64);
+    round_trips!(round_trips_signed_i64, i64, signed);
+    round_trips!(round_trips_usize, usize);
+    round_trips!(round_trips_isize, isize);
+    round_trips!(round_trips_signed_isize, isize, signed);
+    #[cfg(HAS_U128)]
+    round_trips!(round_trips_u128, u128);
+    #[cfg(HAS_U128)]
+    round_trips!(round_trips_i128, i128);
+    #[cfg(HAS_U128)]
+    round_trips!(round_trips_signed_i128, i128, signed);
+
+    #[test]
+    fn matches_known_encodings() {
+        let mut buffer = [0u8; 32];
+        let length = encode_leb128(Num::from(624485u32), &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], &[0xE5, 0x8E, 0x26]);
+        let length = encode_sleb128(Num::from(-123456i32), &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], &[0xC0, 0xBB, 0x78]);
+
+        for (signed, unsigned) in [
+            (0i32, 0u32),
+            (-1, 1),
+            (1, 2),
+            (-2, 3),
+            (i32::MAX, u32::MAX - 1),
+            (i32::MIN, u32::MAX),
+        ]
+        .iter()
+        {
+            assert_eq!(zigzag_encode(Num::from(*signed)), Num::from(*unsigned));
+        }
+
+        let length = encode_prefix(Num::from(127u32), &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], &[0xFF]);
+        let length = encode_prefix(Num::from(128u32), &mut buffer).unwrap();
+        assert_eq!(&buffer[..length], &[0x02, 0x02]);
+        let length = encode_prefix(Num::from(u64::MAX), &mut buffer).unwrap();
+        assert_eq!(
+            &buffer[..length],
+            &[0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        assert_eq!(
+            decode_leb128::<Num<u8>>(&[0xFF, 0x01]),
+            Ok((Num::from(255u8), 2))
+        );
+        assert_eq!(
+            decode_leb128::<Num<u8>>(&[0x80, 0x02]),
+            Err(Error::CorruptBlock { offset: 1 })
+        );
+        assert_eq!(
+            decode_leb128::<Num<u8>>(&[0x80, 0x80, 0x00]),
+            Err(Error::CorruptBlock { offset: 1 })
+        );
+        assert_eq!(
+            decode_leb128::<Num<u32>>(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x00]),
+            Err(Error::CorruptBlock { offset: 4 })
+        );
+        // non-minimal is accepted
+        assert_eq!(
+            decode_leb128::<Num<u32>>(&[0x81, 0x80, 0x00]),
+            Ok((Num::from(1u32), 3))
+        );
+
+        assert_eq!(
+            decode_sleb128::<Num<i8>>(&[0x80, 0x7F]),
+            Ok((Num::from(-128i8), 2))
+        );
+        assert_eq!(
+            decode_sleb128::<Num<i8>>(&[0x80, 0x3F]),
+            Err(Error::CorruptBlock { offset: 1 })
+        );
+        assert_eq!(
+            decode_sleb128::<Num<i8>>(&[0xFF, 0x00]),
+            Ok((Num::from(127i8), 2))
+        );
+
+        // 14 bits does not fit in a `u8`
+        assert_eq!(
+            decode_prefix::<Num<u8>>(&[0xFE, 0xFF]),
+            Err(Error::CorruptBlock { offset: 0 })
+        );
+        assert_eq!(
+            decode_prefix::<Num<u8>>(&[]),
+            Err(Error::Truncated { offset: 0 })
+        );
+        assert_eq!(
+            encode_leb128(Num::from(300u16), &mut [0u8; 1]),
+            Err(Error::OutputFull { offset: 1 })
+        );
+    }
+
+    #[test]
+    fn limits_lengths() {
+        let limits = Limits {
+            max_output: 4096,
+            ..Limits::default()
+        };
+        let mut buffer = [0u8; 10];
+        let length = encode_leb128(Num::from(1024u64), &mut buffer).unwrap();
+        assert_eq!(
+            decode_leb128_length(&buffer[..length], 4, &limits),
+            Ok((1024, length))
+        );
+        assert_eq!(
+            decode_leb128_length(&buffer[..length], 8, &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        let length = encode_prefix(Num::from(1024u64), &mut buffer).unwrap();
+        assert_eq!(
+            decode_prefix_length(&buffer[..length], 4, &limits),
+            Ok((1024, length))
+        );
+        assert_eq!(
+            decode_prefix_length(&buffer[..length], 5, &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+
+        // a count claiming most of the address space, which overflows
+        // when multiplied by the element size
+        let length = encode_prefix(Num::from(u64::MAX / 2), &mut buffer).unwrap();
+        assert_eq!(
+            decode_prefix_length(&buffer[..length], 4, &Limits::unlimited()),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        let length = encode_leb128(Num::from(1u64 << 40), &mut buffer).unwrap();
+        assert_eq!(
+            decode_leb128_length(&buffer[..length], 1, &Limits::default()),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        // malformed counts are still corrupt
+        assert_eq!(
+            decode_leb128_length(&[0x80], 1, &limits),
+            Err(Error::Truncated { offset: 1 })
+        );
+    }
+
+    /// values of every byte width, from a fixed xorshift sequence
+    fn mixed_values(count: usize) -> [u32; 256] {
+        let mut values = [0u32; 256];
+        let mut state = 0x9E37_79B9u32;
+        for value in values[..count].iter_mut() {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            *value = state >> ((state & 3) * 8);
+        }
+        values
+    }
+
+    #[test]
+    fn stream_vbyte_matches_known_encoding() {
+        let values = [1u32, 0x100, 0x1_0000, 0x100_0000, 5];
+        let mut buffer = [0u8; 32];
+        let length = stream_vbyte_encode(&values, &mut buffer).unwrap();
+        assert_eq!(
+            &buffer[..length],
+            &[0xE4, 0x00, 1, 0, 1, 0, 0, 1, 0, 0, 0, 1, 5]
+        );
+        let mut output = [0u32; 5];
+        assert_eq!(stream_vbyte_decode(&buffer[..length], &mut output), Ok(13));
+        assert_eq!(output, values);
+    }
+
+    #[test]
+    fn stream_vbyte_round_trips() {
+        let mut buffer = [0u8; 5 * 256];
+        for count in 0..=256 {
+            let values = mixed_values(count);
+            let values = &values[..count];
+            let length = stream_vbyte_encode(values, &mut buffer).unwrap();
+            assert_eq!(length, stream_vbyte_len(values));
+
+            // trailing data must not be consumed
+            let mut output = [0u32; 256];
+            assert_eq!(
+                stream_vbyte_decode(&buffer[..length + 7], &mut output[..count]),
+                Ok(length)
+            );
+            assert_eq!(&output[..count], values);
+        }
+    }
+
+    #[test]
+    fn stream_vbyte_kernels_agree() {
+        let values = mixed_values(256);
+        let mut buffer = [0u8; 5 * 256];
+        let length = stream_vbyte_encode(&values, &mut buffer).unwrap();
+        let (control, data) = buffer[..length].split_at(64);
+
+        let mut expected = [0u32; 256];
+        let decoded = decode_groups_portable(control, data, &mut expected);
+        assert!(decoded.0 > 60);
+        assert_eq!(&expected[..decoded.0 * 4], &values[..decoded.0 * 4]);
+
+        #[cfg(all(HAS_CORE_ARCH, any(target_arch = "x86", target_arch = "x86_64")))]
+        {
+            use super::super::cpu::{has, Feature};
+            if has(Feature::Ssse3) {
+                let mut output = [0u32; 256];
+                let simd = unsafe { super::decode_groups_ssse3(control, data, &mut output) };
+                assert_eq!(simd, decoded);
+                assert_eq!(&output[..], &expected[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn stream_vbyte_reports_truncation() {
+        let values = mixed_values(40);
+        let mut buffer = [0u8; 5 * 40];
+        let length = stream_vbyte_encode(&values[..40], &mut buffer).unwrap();
+        let mut output = [0u32; 40];
+        for short in 0..length {
+            assert_eq!(
+                stream_vbyte_decode(&buffer[..short], &mut output),
+                Err(Error::Truncated { offset: short })
+            );
+        }
+        assert_eq!(
+            stream_vbyte_encode(&values[..40], &mut buffer[..length - 1]),
+            Err(Error::OutputFull { offset: length - 1 })
+        );
+    }
+}
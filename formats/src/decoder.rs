@@ -0,0 +1,1269 @@
+use std::hash::Hasher;
+use std::io::{self, Read};
+
+use super::checksum::{adler32, crc32, crc32c};
+use super::deflate::Inflater;
+use super::detect::{detect_format, Format};
+use super::feature_macros::error::Error;
+use super::feature_macros::limits::Limits;
+use super::feature_macros::numbers::{Num, PrimativeNumber};
+use super::lz4::{self, FrameDescriptor};
+use super::snappy;
+use super::xxhash::bits32::{xxhash32_reference, XXHash32};
+use super::zstd;
+
+/// how much output is kept for matches to reference, LZ4's 64KiB window
+/// covers deflate's 32KiB
+const HISTORY: usize = 1 << 16;
+
+/// the most read from the underlying reader at once
+const READ_SIZE: usize = 1 << 14;
+
+/// how much a deflate stream is decoded by at a time
+const DEFLATE_STEP: usize = 1 << 16;
+
+/// how much is peeked to detect the format, the first check only needs
+/// a magic number, raw snappy's heuristic may need a whole first literal
+const PEEK_SIZE: usize = 16;
+const SNAPPY_PEEK_SIZE: usize = snappy::MAX_CHUNK_LENGTH + 8;
+
+/// the longest an LZ4 frame's header can be
+const LZ4_HEADER_SIZE: usize = 19;
+
+/// the longest a zstd frame's header can be
+const ZSTD_HEADER_SIZE: usize = 18;
+
+/// legacy LZ4 blocks all decompress to 8MiB, except the last
+const LZ4_LEGACY_BLOCK_SIZE: usize = 8 << 20;
+
+/// the largest legacy block `lz4` will write, `LZ4_compressBound(8MiB)`
+const LZ4_LEGACY_BOUND: usize = LZ4_LEGACY_BLOCK_SIZE + LZ4_LEGACY_BLOCK_SIZE / 255 + 16;
+
+/// the flags of a gzip member's header
+const GZIP_FHCRC: u8 = 0b10;
+const GZIP_FEXTRA: u8 = 0b100;
+const GZIP_FNAME: u8 = 0b1000;
+const GZIP_FCOMMENT: u8 = 0b1_0000;
+
+/// what the decoder expects next
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Stage {
+    /// nothing has been read yet
+    Detect,
+    /// an LZ4 or zstd frame, a skippable frame, or the end
+    Frame,
+    /// within a skippable frame (or snappy chunk) with this much left
+    Skip {
+        remaining: u64,
+        chunk: bool,
+    },
+    /// an LZ4 frame's block, or its end mark
+    Lz4Block,
+    /// a legacy LZ4 block, or the end
+    Lz4Legacy,
+    /// a snappy framing format chunk, or the end
+    SnappyChunk,
+    /// a whole raw snappy block
+    SnappyRaw,
+    /// a gzip member, or the end
+    GzipMember,
+    /// within a deflate stream, wrapped by gzip or zlib
+    Deflate {
+        gzip: bool,
+    },
+    Done,
+    Failed(Error),
+}
+
+/// AnyDecoder decompresses whatever format `detect_format` recognizes in
+/// the start of `reader`.
+///
+/// LZ4 frames (with skippable frames between them), legacy LZ4, gzip
+/// (including concatenated members), zlib, and snappy's framing format
+/// and raw blocks are decoded, and every checksum they store is verified.
+/// zstd is detected but not yet decoded, so it returns `Unsupported`.
+///
+/// Decoding errors are returned as `io::ErrorKind::InvalidData`, or
+/// `UnexpectedEof` for truncated input, wrapping an `Error` whose offset
+/// is into the compressed stream. They end decoding, later reads return
+/// the same error. An empty reader decodes to nothing.
+///
+/// The stream is held to `Limits`, `Limits::default()` unless given to
+/// `with_limits`. Frame headers are checked as they are parsed, a raw
+/// snappy block's length before it is allocated, what is buffered from
+/// the reader before it is read, and the output (in total and per byte
+/// consumed) as it is produced. Exactly `max_output` bytes are returned
+/// before a stream which exceeds it fails.
+pub struct AnyDecoder<R> {
+    reader: R,
+    input: Vec<u8>,
+    consumed: usize,
+    base: usize,
+    eof: bool,
+    output: Vec<u8>,
+    returned: usize,
+    history: usize,
+    format: Option<Format>,
+    stage: Stage,
+    descriptor: Option<FrameDescriptor>,
+    hasher: XXHash32,
+    produced: u64,
+    inflater: Inflater,
+    check: u32,
+    limits: Limits,
+    frames: u64,
+    total: u64,
+}
+impl<R: Read> AnyDecoder<R> {
+    #[inline]
+    pub fn new(reader: R) -> AnyDecoder<R> {
+        AnyDecoder::with_limits(reader, &Limits::default())
+    }
+
+    pub fn with_limits(reader: R, limits: &Limits) -> AnyDecoder<R> {
+        AnyDecoder {
+            reader,
+            input: Vec::new(),
+            consumed: 0,
+            base: 0,
+            eof: false,
+            output: Vec::new(),
+            returned: 0,
+            history: 0,
+            format: Option::None,
+            stage: Stage::Detect,
+            descriptor: Option::None,
+            hasher: XXHash32::new(),
+            produced: 0,
+            inflater: Inflater::new(),
+            check: 0,
+            limits: *limits,
+            frames: 0,
+            total: 0,
+        }
+    }
+
+    /// the format detected from the start of the stream, once the first
+    /// read has been made
+    #[inline]
+    pub fn format(&self) -> Option<Format> {
+        self.format
+    }
+
+    /// returns the underlying reader, anything buffered from it is lost
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// the offset in the compressed stream of the next unconsumed byte
+    #[inline(always)]
+    fn position(&self) -> usize {
+        self.base + self.consumed
+    }
+
+    /// the input read but not yet consumed
+    #[inline(always)]
+    fn available(&self) -> &[u8] {
+        &self.input[self.consumed..]
+    }
+
+    #[inline(always)]
+    fn consume(&mut self, count: usize) {
+        debug_assert!(count <= self.input.len() - self.consumed);
+        self.consumed += count;
+    }
+
+    /// reads until `count` bytes are available, returning false when the
+    /// reader ends first
+    fn fill(&mut self, count: usize) -> io::Result<bool> {
+        if let Err(error) = self.limits.check_memory(count as u64, 0) {
+            return Err(self.fail_here(error));
+        }
+        while self.input.len() - self.consumed < count && !self.eof {
+            if self.consumed > 0 {
+                self.input.drain(..self.consumed);
+                self.base += self.consumed;
+                self.consumed = 0;
+            }
+            let mut buffer = [0u8; READ_SIZE];
+            match self.reader.read(&mut buffer) {
+                Ok(0) => self.eof = true,
+                Ok(read) => self.input.extend_from_slice(&buffer[..read]),
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(self.input.len() - self.consumed >= count)
+    }
+
+    /// reads until `count` bytes are available, the reader ending first
+    /// is an error
+    fn require(&mut self, count: usize) -> io::Result<()> {
+        if self.fill(count)? {
+            Ok(())
+        } else {
+            let end = self.base + self.input.len();
+            Err(self.fail(Error::Truncated { offset: end }))
+        }
+    }
+
+    /// ends decoding with `error`, whose offset is into the stream
+    fn fail(&mut self, error: Error) -> io::Error {
+        self.stage = Stage::Failed(error);
+        into_io_error(error)
+    }
+
+    /// ends decoding with `error`, whose offset is from the next
+    /// unconsumed byte
+    fn fail_here(&mut self, error: Error) -> io::Error {
+        let error = error.rebase(self.position());
+        self.fail(error)
+    }
+
+    /// counts the frame (or member) which starts at the next byte
+    fn start_frame(&mut self) -> io::Result<()> {
+        self.frames += 1;
+        match self.limits.check_frames(self.frames, 0) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(self.fail_here(error)),
+        }
+    }
+
+    /// counts the output of the last step. Output past `max_output` is
+    /// cut, so exactly that much is returned before the error, and a step
+    /// over `max_ratio` to the input consumed is discarded.
+    fn account(&mut self) -> io::Result<()> {
+        let position = self.position();
+        let produced = (self.output.len() - self.returned) as u64;
+        if let Err(error) = self.limits.check_output(self.total + produced, position) {
+            let keep = self.limits.max_output - self.total;
+            self.output.truncate(self.returned + keep as usize);
+            self.total = self.limits.max_output;
+            self.stage = Stage::Failed(error);
+            return Ok(());
+        }
+        self.total += produced;
+        if let Err(error) = self
+            .limits
+            .check_ratio(self.total, position as u64, position)
+        {
+            let returned = self.returned;
+            self.output.truncate(returned);
+            return Err(self.fail(error));
+        }
+        Ok(())
+    }
+
+    /// reads a little endian `u32` from the available input
+    #[inline]
+    fn read_u32(&self, at: usize) -> u32 {
+        Num::<u32>::read_value_le(&self.available()[at..at + 4]).inner()
+    }
+
+    /// takes one step through the stream, which may or may not output
+    fn step(&mut self) -> io::Result<()> {
+        match self.stage {
+            Stage::Detect => self.detect(),
+            Stage::Frame => self.frame(),
+            Stage::Skip { remaining, chunk } => self.skip(remaining, chunk),
+            Stage::Lz4Block => self.lz4_block(),
+            Stage::Lz4Legacy => self.lz4_legacy(),
+            Stage::SnappyChunk => self.snappy_chunk(),
+            Stage::SnappyRaw => self.snappy_raw(),
+            Stage::GzipMember => self.gzip_member(),
+            Stage::Deflate { gzip } => self.deflate(gzip),
+            Stage::Done | Stage::Failed(_) => Ok(()),
+        }
+    }
+
+    fn detect(&mut self) -> io::Result<()> {
+        if !self.fill(PEEK_SIZE)? && self.available().is_empty() {
+            self.stage = Stage::Done;
+            return Ok(());
+        }
+        let mut format = detect_format(self.available());
+        if format.is_none() || format == Option::Some(Format::SnappyRaw) {
+            // the peek is a heuristic, so is not worth exceeding a limit
+            let peek = (self.limits.max_memory.min(SNAPPY_PEEK_SIZE as u64)) as usize;
+            self.fill(peek)?;
+            format = detect_format(self.available());
+        }
+        self.format = format;
+        self.stage = match format {
+            Option::Some(Format::Lz4Frame)
+            | Option::Some(Format::Lz4Legacy)
+            | Option::Some(Format::Zstd)
+            | Option::Some(Format::Skippable) => Stage::Frame,
+            Option::Some(Format::Gzip) => Stage::GzipMember,
+            Option::Some(Format::Zlib) => {
+                // `detect_format` checked the header, bar a preset dictionary
+                if self.available()[1] & 0x20 != 0 {
+                    return Err(self.fail_here(Error::Unsupported { offset: 1 }));
+                }
+                self.consume(2);
+                self.start_deflate(adler32(1, &[]));
+                Stage::Deflate { gzip: false }
+            }
+            Option::Some(Format::SnappyFramed) => Stage::SnappyChunk,
+            Option::Some(Format::SnappyRaw) => Stage::SnappyRaw,
+            Option::None => return Err(self.fail_here(Error::BadMagic { offset: 0 })),
+        };
+        Ok(())
+    }
+
+    fn frame(&mut self) -> io::Result<()> {
+        if !self.fill(4)? {
+            if self.available().is_empty() {
+                self.stage = Stage::Done;
+                return Ok(());
+            }
+            let end = self.base + self.input.len();
+            return Err(self.fail(Error::Truncated { offset: end }));
+        }
+        self.start_frame()?;
+        match detect_format(&self.available()[..4]) {
+            Option::Some(Format::Lz4Frame) => {
+                self.fill(LZ4_HEADER_SIZE)?;
+                let descriptor =
+                    match lz4::parse_frame_descriptor_with_limits(self.available(), &self.limits) {
+                        Ok(descriptor) => descriptor,
+                        Err(error) => return Err(self.fail_here(error)),
+                    };
+                self.consume(descriptor.header_length);
+                self.descriptor = Option::Some(descriptor);
+                self.hasher = XXHash32::new();
+                self.produced = 0;
+                self.history = 0;
+                self.stage = Stage::Lz4Block;
+            }
+            Option::Some(Format::Lz4Legacy) => {
+                let memory = (HISTORY + LZ4_LEGACY_BLOCK_SIZE + LZ4_LEGACY_BOUND) as u64;
+                if let Err(error) = self.limits.check_memory(memory, 0) {
+                    return Err(self.fail_here(error));
+                }
+                self.consume(4);
+                self.stage = Stage::Lz4Legacy;
+            }
+            Option::Some(Format::Skippable) => {
+                self.require(8)?;
+                let remaining = self.read_u32(4) as u64;
+                self.consume(8);
+                self.stage = Stage::Skip {
+                    remaining,
+                    chunk: false,
+                };
+            }
+            Option::Some(Format::Zstd) => {
+                // a header over the limits is reported as such
+                self.fill(ZSTD_HEADER_SIZE)?;
+                let error =
+                    match zstd::parse_frame_header_with_limits(self.available(), &self.limits) {
+                        Err(error @ Error::LimitExceeded { .. }) => error,
+                        _ => Error::Unsupported { offset: 0 },
+                    };
+                return Err(self.fail_here(error));
+            }
+            _ => return Err(self.fail_here(Error::BadMagic { offset: 0 })),
+        };
+        Ok(())
+    }
+
+    fn skip(&mut self, remaining: u64, chunk: bool) -> io::Result<()> {
+        if remaining > 0 {
+            self.require(1)?;
+            let count = (self.available().len() as u64).min(remaining);
+            self.consume(count as usize);
+            self.stage = Stage::Skip {
+                remaining: remaining - count,
+                chunk,
+            };
+        } else if chunk {
+            self.stage = Stage::SnappyChunk;
+        } else {
+            self.stage = Stage::Frame;
+        }
+        Ok(())
+    }
+
+    fn lz4_block(&mut self) -> io::Result<()> {
+        let descriptor = self.descriptor.expect("an LZ4 block follows a descriptor");
+        self.require(4)?;
+        let size = self.read_u32(0);
+        if size == 0 {
+            return self.lz4_end(descriptor);
+        }
+
+        let stored = size & 0x8000_0000 != 0;
+        let length = (size & 0x7FFF_FFFF) as usize;
+        if length > descriptor.block_max_size {
+            return Err(self.fail_here(Error::CorruptBlock { offset: 0 }));
+        }
+        let checksum = if descriptor.block_checksum { 4 } else { 0 };
+        self.require(4 + length + checksum)?;
+        if descriptor.block_checksum {
+            let expected = self.read_u32(4 + length);
+            let actual = xxhash32_reference(0, &self.available()[4..4 + length]);
+            if expected != actual {
+                return Err(self.fail_here(Error::ChecksumMismatch {
+                    offset: 4 + length,
+                    expected: expected as u64,
+                    actual: actual as u64,
+                }));
+            }
+        }
+
+        let start = self.output.len();
+        let data = &self.input[self.consumed + 4..self.consumed + 4 + length];
+        if stored {
+            self.output.extend_from_slice(data);
+        } else {
+            let keep = if descriptor.block_independence {
+                0
+            } else {
+                self.history.min(start)
+            };
+            let begin = start - keep;
+            let bound = lz4_output_bound(length, descriptor.block_max_size);
+            self.output.resize(start + bound, 0);
+            match lz4::decompress_block(data, &mut self.output[begin..], keep) {
+                Ok(end) => self.output.truncate(begin + end),
+                Err(error) => {
+                    self.output.truncate(start);
+                    let error = match error {
+                        // more than the block's maximum size
+                        Error::OutputFull { .. } => Error::CorruptBlock { offset: 0 },
+                        error => error,
+                    };
+                    return Err(self.fail_here(error.rebase(4)));
+                }
+            }
+        }
+        self.consume(4 + length + checksum);
+        self.hasher.write(&self.output[start..]);
+        self.produced += (self.output.len() - start) as u64;
+        self.history = (self.history + self.output.len() - start).min(HISTORY);
+        Ok(())
+    }
+
+    /// checks the end of an LZ4 frame against its descriptor
+    fn lz4_end(&mut self, descriptor: FrameDescriptor) -> io::Result<()> {
+        if let Option::Some(size) = descriptor.content_size {
+            if size != self.produced {
+                return Err(self.fail_here(Error::CorruptBlock { offset: 0 }));
+            }
+        }
+        if descriptor.content_checksum {
+            self.require(8)?;
+            let expected = self.read_u32(4);
+            let actual = self.hasher.finish() as u32;
+            if expected != actual {
+                return Err(self.fail_here(Error::ChecksumMismatch {
+                    offset: 4,
+                    expected: expected as u64,
+                    actual: actual as u64,
+                }));
+            }
+            self.consume(8);
+        } else {
+            self.consume(4);
+        }
+        self.stage = Stage::Frame;
+        Ok(())
+    }
+
+    fn lz4_legacy(&mut self) -> io::Result<()> {
+        if !self.fill(4)? {
+            if self.available().is_empty() {
+                self.stage = Stage::Done;
+                return Ok(());
+            }
+            let end = self.base + self.input.len();
+            return Err(self.fail(Error::Truncated { offset: end }));
+        }
+        // legacy frames have no end mark, another frame may follow
+        match detect_format(&self.available()[..4]) {
+            Option::Some(Format::Lz4Frame)
+            | Option::Some(Format::Lz4Legacy)
+            | Option::Some(Format::Zstd)
+            | Option::Some(Format::Skippable) => {
+                self.stage = Stage::Frame;
+                return Ok(());
+            }
+            _ => {}
+        };
+
+        let length = self.read_u32(0) as usize;
+        if length > LZ4_LEGACY_BOUND {
+            return Err(self.fail_here(Error::CorruptBlock { offset: 0 }));
+        }
+        self.require(4 + length)?;
+        let start = self.output.len();
+        let bound = lz4_output_bound(length, LZ4_LEGACY_BLOCK_SIZE);
+        self.output.resize(start + bound, 0);
+        let data = &self.input[self.consumed + 4..self.consumed + 4 + length];
+        match lz4::decompress_block(data, &mut self.output[start..], 0) {
+            Ok(end) => self.output.truncate(start + end),
+            Err(error) => {
+                self.output.truncate(start);
+                let error = match error {
+                    Error::OutputFull { .. } => Error::CorruptBlock { offset: 0 },
+                    error => error,
+                };
+                return Err(self.fail_here(error.rebase(4)));
+            }
+        }
+        self.consume(4 + length);
+        Ok(())
+    }
+
+    fn snappy_chunk(&mut self) -> io::Result<()> {
+        if !self.fill(4)? {
+            if self.available().is_empty() {
+                self.stage = Stage::Done;
+                return Ok(());
+            }
+            let end = self.base + self.input.len();
+            return Err(self.fail(Error::Truncated { offset: end }));
+        }
+        let kind = self.available()[0];
+        let length = (self.read_u32(0) >> 8) as usize;
+        match kind {
+            // the stream identifier, which may be repeated
+            0xFF => {
+                if length != 6 {
+                    return Err(self.fail_here(Error::BadMagic { offset: 0 }));
+                }
+                self.require(10)?;
+                if &self.available()[..10] != b"\xff\x06\x00\x00sNaPpY" {
+                    return Err(self.fail_here(Error::BadMagic { offset: 0 }));
+                }
+                self.consume(10);
+            }
+            // compressed or uncompressed data, after a masked CRC-32C
+            0x00 | 0x01 => {
+                let limit = if kind == 0x00 {
+                    snappy::max_compressed_length(snappy::MAX_CHUNK_LENGTH)
+                } else {
+                    snappy::MAX_CHUNK_LENGTH
+                };
+                if length < 4 || length - 4 > limit {
+                    return Err(self.fail_here(Error::CorruptBlock { offset: 1 }));
+                }
+                self.require(4 + length)?;
+                let start = self.output.len();
+                let data = &self.input[self.consumed + 8..self.consumed + 4 + length];
+                if kind == 0x01 {
+                    self.output.extend_from_slice(data);
+                } else {
+                    let result = match snappy::uncompressed_length(data) {
+                        Ok((size, _)) if size > snappy::MAX_CHUNK_LENGTH => {
+                            Err(Error::CorruptBlock { offset: 0 })
+                        }
+                        Ok((size, _)) => {
+                            self.output.resize(start + size, 0);
+                            snappy::decompress(data, &mut self.output[start..])
+                        }
+                        Err(error) => Err(error),
+                    };
+                    if let Err(error) = result {
+                        self.output.truncate(start);
+                        return Err(self.fail_here(error.rebase(8)));
+                    }
+                }
+                let expected = self.read_u32(4);
+                let actual = snappy::mask_checksum(crc32c(0, &self.output[start..]));
+                if expected != actual {
+                    self.output.truncate(start);
+                    return Err(self.fail_here(Error::ChecksumMismatch {
+                        offset: 4,
+                        expected: expected as u64,
+                        actual: actual as u64,
+                    }));
+                }
+                self.consume(4 + length);
+            }
+            // reserved chunks which must be understood
+            0x02..=0x7F => return Err(self.fail_here(Error::Unsupported { offset: 0 })),
+            // reserved skippable chunks and padding
+            _ => {
+                self.consume(4);
+                self.stage = Stage::Skip {
+                    remaining: length as u64,
+                    chunk: true,
+                };
+            }
+        };
+        Ok(())
+    }
+
+    fn snappy_raw(&mut self) -> io::Result<()> {
+        // the block is not framed, so it runs to the end of the reader
+        while !self.eof {
+            let available = self.available().len();
+            self.fill(available + READ_SIZE)?;
+        }
+        let start = self.output.len();
+        let data = &self.input[self.consumed..];
+        let limits = self.limits;
+        let total = self.total;
+        let result = match snappy::uncompressed_length(data) {
+            // a 3 byte copy of 64 is the most any element expands by, so
+            // a larger length cannot be valid and is not allocated
+            Ok((size, _)) if size / 22 > data.len() => Err(Error::CorruptBlock { offset: 0 }),
+            Ok((size, _)) => limits
+                .check_output(total + size as u64, 0)
+                .and_then(|_| limits.check_ratio(size as u64, data.len() as u64, 0))
+                .and_then(|_| limits.check_memory((data.len() + size) as u64, 0))
+                .map(|_| size),
+            Err(error) => Err(error),
+        };
+        let result = match result {
+            Ok(size) => {
+                self.output.resize(start + size, 0);
+                snappy::decompress(data, &mut self.output[start..])
+            }
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            self.output.truncate(start);
+            return Err(self.fail_here(error));
+        }
+        let length = self.available().len();
+        self.consume(length);
+        self.stage = Stage::Done;
+        Ok(())
+    }
+
+    fn gzip_member(&mut self) -> io::Result<()> {
+        if !self.fill(1)? {
+            self.stage = Stage::Done;
+            return Ok(());
+        }
+        self.start_frame()?;
+        let header = loop {
+            match gzip_header_length(self.available()) {
+                Ok(length) => break length,
+                Err(Error::Truncated { .. }) if !self.eof => {
+                    let available = self.available().len();
+                    self.fill(available + 1)?;
+                }
+                Err(error) => return Err(self.fail_here(error)),
+            }
+        };
+        self.consume(header);
+        self.start_deflate(crc32(0, &[]));
+        self.stage = Stage::Deflate { gzip: true };
+        Ok(())
+    }
+
+    /// resets the deflate state for a new stream
+    fn start_deflate(&mut self, check: u32) {
+        self.inflater = Inflater::new();
+        self.check = check;
+        self.produced = 0;
+        self.history = 0;
+    }
+
+    fn deflate(&mut self, gzip: bool) -> io::Result<()> {
+        if self.inflater.is_done() {
+            return self.deflate_trailer(gzip);
+        }
+        if self.available().is_empty() {
+            self.require(1)?;
+        }
+
+        let start = self.output.len();
+        let keep = self.history.min(start);
+        let begin = start - keep;
+        self.output.resize(start + DEFLATE_STEP, 0);
+        let result = self.inflater.inflate(
+            &self.input[self.consumed..],
+            &mut self.output[begin..],
+            keep,
+        );
+        let (used, end) = match result {
+            Ok(progress) => progress,
+            Err(error) => {
+                self.output.truncate(start);
+                return Err(self.fail_here(error));
+            }
+        };
+        self.output.truncate(begin + end);
+        self.consume(used);
+
+        let produced = &self.output[start..];
+        self.check = if gzip {
+            crc32(self.check, produced)
+        } else {
+            adler32(self.check, produced)
+        };
+        self.produced += produced.len() as u64;
+        self.history = (self.history + produced.len()).min(HISTORY);
+        if used == 0 && produced.is_empty() && !self.inflater.is_done() {
+            // the next symbol runs past what has been read
+            let available = self.available().len();
+            self.require(available + 1)?;
+        }
+        Ok(())
+    }
+
+    /// checks the trailer which follows a deflate stream
+    fn deflate_trailer(&mut self, gzip: bool) -> io::Result<()> {
+        if gzip {
+            self.require(8)?;
+            let expected = self.read_u32(0);
+            if expected != self.check {
+                let actual = self.check;
+                return Err(self.fail_here(Error::ChecksumMismatch {
+                    offset: 0,
+                    expected: expected as u64,
+                    actual: actual as u64,
+                }));
+            }
+            // the length is only stored modulo 4GiB
+            if self.read_u32(4) != self.produced as u32 {
+                return Err(self.fail_here(Error::CorruptBlock { offset: 4 }));
+            }
+            self.consume(8);
+            self.stage = Stage::GzipMember;
+        } else {
+            self.require(4)?;
+            let expected = Num::<u32>::read_value_be(&self.available()[..4]).inner();
+            if expected != self.check {
+                let actual = self.check;
+                return Err(self.fail_here(Error::ChecksumMismatch {
+                    offset: 0,
+                    expected: expected as u64,
+                    actual: actual as u64,
+                }));
+            }
+            // zlib streams are not concatenated, nothing after is read
+            self.consume(4);
+            self.stage = Stage::Done;
+        }
+        Ok(())
+    }
+}
+impl<R: Read> Read for AnyDecoder<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        while self.returned == self.output.len() {
+            match self.stage {
+                Stage::Done => return Ok(0),
+                Stage::Failed(error) => return Err(into_io_error(error)),
+                _ => {}
+            };
+            // only what later matches may reference is kept
+            if self.output.len() > HISTORY {
+                let excess = self.output.len() - HISTORY;
+                self.output.drain(..excess);
+            }
+            self.returned = self.output.len();
+            self.step()?;
+            self.account()?;
+        }
+        let count = buffer.len().min(self.output.len() - self.returned);
+        buffer[..count].copy_from_slice(&self.output[self.returned..self.returned + count]);
+        self.returned += count;
+        Ok(count)
+    }
+}
+
+/// the most an LZ4 block of `length` bytes can decompress to, every
+/// byte of a sequence adds at most 255 to its match length
+#[inline]
+fn lz4_output_bound(length: usize, maximum: usize) -> usize {
+    maximum.min(length.saturating_mul(255))
+}
+
+/// truncation is reported as `UnexpectedEof`, anything else as `InvalidData`
+fn into_io_error(error: Error) -> io::Error {
+    let kind = match error {
+        Error::Truncated { .. } => io::ErrorKind::UnexpectedEof,
+        _ => io::ErrorKind::InvalidData,
+    };
+    io::Error::new(kind, error)
+}
+
+/// returns the length of the gzip member header at the start of `buffer`,
+/// checking its optional CRC-16
+fn gzip_header_length(buffer: &[u8]) -> Result<usize, Error> {
+    if !buffer.starts_with(&[0x1F, 0x8B][..buffer.len().min(2)]) {
+        return Err(Error::BadMagic { offset: 0 });
+    }
+    if buffer.len() < 10 {
+        return Err(Error::Truncated {
+            offset: buffer.len(),
+        });
+    }
+    if buffer[2] != 0x08 {
+        return Err(Error::Unsupported { offset: 2 });
+    }
+    let flags = buffer[3];
+    if flags & 0xE0 != 0 {
+        return Err(Error::CorruptBlock { offset: 3 });
+    }
+
+    let mut position = 10usize;
+    if flags & GZIP_FEXTRA != 0 {
+        if buffer.len() < position + 2 {
+            return Err(Error::Truncated {
+                offset: buffer.len(),
+            });
+        }
+        position += 2 + Num::<u16>::read_value_le(&buffer[position..]).inner() as usize;
+    }
+    for flag in [GZIP_FNAME, GZIP_FCOMMENT].iter() {
+        if flags & *flag != 0 {
+            // zero terminated
+            match buffer
+                .get(position..)
+                .and_then(|rest| rest.iter().position(|x| *x == 0))
+            {
+                Option::Some(length) => position += length + 1,
+                Option::None => {
+                    return Err(Error::Truncated {
+                        offset: buffer.len(),
+                    })
+                }
+            }
+        }
+    }
+    if flags & GZIP_FHCRC != 0 {
+        if buffer.len() < position + 2 {
+            return Err(Error::Truncated {
+                offset: buffer.len(),
+            });
+        }
+        let expected = Num::<u16>::read_value_le(&buffer[position..]).inner();
+        let actual = crc32(0, &buffer[..position]) as u16;
+        if expected != actual {
+            return Err(Error::ChecksumMismatch {
+                offset: position,
+                expected: expected as u64,
+                actual: actual as u64,
+            });
+        }
+        position += 2;
+    }
+    if buffer.len() < position {
+        return Err(Error::Truncated {
+            offset: buffer.len(),
+        });
+    }
+    Ok(position)
+}
+
+#[cfg(test)]
+mod test {
+
+    use std::io::{ErrorKind, Read};
+
+    use super::super::checksum::crc32c;
+    use super::super::detect::Format;
+    use super::super::feature_macros::error::Error;
+    use super::super::feature_macros::limits::Limits;
+    use super::super::snappy::mask_checksum;
+    use super::super::xxhash::bits32::xxhash32_reference;
+    use super::AnyDecoder;
+
+    const TEXT: &[u8] = b"hello hello hello hello hello, this is any decoder data, hello hello!\n";
+
+    /// `lz4` on `TEXT`
+    const LZ4_FRAME: [u8; 66] = [
+        0x04, 0x22, 0x4D, 0x18, 0x64, 0x40, 0xA7, 0x2F, 0x00, 0x00, 0x00, 0x6F, 0x68, 0x65, 0x6C,
+        0x6C, 0x6F, 0x20, 0x06, 0x00, 0x04, 0xF5, 0x0C, 0x2C, 0x20, 0x74, 0x68, 0x69, 0x73, 0x20,
+        0x69, 0x73, 0x20, 0x61, 0x6E, 0x79, 0x20, 0x64, 0x65, 0x63, 0x6F, 0x64, 0x65, 0x72, 0x20,
+        0x64, 0x61, 0x74, 0x61, 0x2C, 0x33, 0x00, 0x50, 0x6C, 0x6C, 0x6F, 0x21, 0x0A, 0x00, 0x00,
+        0x00, 0x00, 0xD6, 0x94, 0x8E, 0xB6,
+    ];
+
+    /// `lz4 -l` on `TEXT`
+    const LZ4_LEGACY: [u8; 55] = [
+        0x02, 0x21, 0x4C, 0x18, 0x2F, 0x00, 0x00, 0x00, 0x6F, 0x68, 0x65, 0x6C, 0x6C, 0x6F, 0x20,
+        0x06, 0x00, 0x04, 0xF5, 0x0C, 0x2C, 0x20, 0x74, 0x68, 0x69, 0x73, 0x20, 0x69, 0x73, 0x20,
+        0x61, 0x6E, 0x79, 0x20, 0x64, 0x65, 0x63, 0x6F, 0x64, 0x65, 0x72, 0x20, 0x64, 0x61, 0x74,
+        0x61, 0x2C, 0x33, 0x00, 0x50, 0x6C, 0x6C, 0x6F, 0x21, 0x0A,
+    ];
+
+    /// `gzip -9` on `TEXT`, which stores the file name
+    const GZIP: [u8; 67] = [
+        0x1F, 0x8B, 0x08, 0x08, 0x1D, 0xB5, 0xD5, 0x6A, 0x02, 0x03, 0x74, 0x65, 0x78, 0x74, 0x2E,
+        0x74, 0x78, 0x74, 0x00, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0xC0, 0x4E, 0xEA, 0x28,
+        0x94, 0x64, 0x64, 0x16, 0x2B, 0x00, 0x51, 0x62, 0x5E, 0xA5, 0x42, 0x4A, 0x6A, 0x72, 0x7E,
+        0x4A, 0x6A, 0x91, 0x42, 0x4A, 0x62, 0x49, 0xA2, 0x0E, 0xB2, 0x32, 0x45, 0x2E, 0x00, 0x8D,
+        0xC3, 0xF5, 0x03, 0x46, 0x00, 0x00, 0x00,
+    ];
+
+    /// python's `zlib.compress` on `TEXT` at level 9
+    const ZLIB: [u8; 46] = [
+        0x78, 0xDA, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0xC0, 0x4E, 0xEA, 0x28, 0x94, 0x64,
+        0x64, 0x16, 0x2B, 0x00, 0x51, 0x62, 0x5E, 0xA5, 0x42, 0x4A, 0x6A, 0x72, 0x7E, 0x4A, 0x6A,
+        0x91, 0x42, 0x4A, 0x62, 0x49, 0xA2, 0x0E, 0xB2, 0x32, 0x45, 0x2E, 0x00, 0x86, 0x85, 0x18,
+        0xBC,
+    ];
+
+    /// a reader which returns at most 3 bytes at a time, or a larger
+    /// piece under Miri where every step is slow
+    struct Trickle<'a>(&'a [u8]);
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            let most = if cfg!(miri) { 61 } else { 3 };
+            let count = buffer.len().min(self.0.len()).min(most);
+            buffer[..count].copy_from_slice(&self.0[..count]);
+            self.0 = &self.0[count..];
+            Ok(count)
+        }
+    }
+
+    /// decodes `input` whole, and through a trickling reader into a
+    /// small buffer, checking both agree
+    fn decode(input: &[u8]) -> Result<(Option<Format>, Vec<u8>), Error> {
+        decode_with_limits(input, &Limits::default())
+    }
+
+    fn decode_with_limits(
+        input: &[u8],
+        limits: &Limits,
+    ) -> Result<(Option<Format>, Vec<u8>), Error> {
+        let mut dut = AnyDecoder::with_limits(input, limits);
+        let mut whole = Vec::new();
+        let result = dut.read_to_end(&mut whole);
+
+        let mut trickled = Vec::new();
+        let mut pieces = AnyDecoder::with_limits(Trickle(input), limits);
+        let mut buffer = [0u8; 5];
+        let trickled_result = loop {
+            match pieces.read(&mut buffer) {
+                Ok(0) => break Ok(()),
+                Ok(count) => trickled.extend_from_slice(&buffer[..count]),
+                Err(error) => break Err(error),
+            }
+        };
+        assert_eq!(trickled, whole);
+
+        match (result, trickled_result) {
+            (Ok(_), Ok(())) => Ok((dut.format(), whole)),
+            (Err(error), Err(trickled_error)) => {
+                assert_eq!(error.kind(), trickled_error.kind());
+                let error = *error.get_ref().unwrap().downcast_ref::<Error>().unwrap();
+                let trickled_error = *trickled_error
+                    .get_ref()
+                    .unwrap()
+                    .downcast_ref::<Error>()
+                    .unwrap();
+                assert_eq!(error, trickled_error);
+                // the error is sticky
+                assert_eq!(dut.read(&mut buffer).unwrap_err().kind(), error_kind(error));
+                Err(error)
+            }
+            (result, trickled_result) => panic!("{:?} and {:?}", result, trickled_result),
+        }
+    }
+
+    fn error_kind(error: Error) -> ErrorKind {
+        match error {
+            Error::Truncated { .. } => ErrorKind::UnexpectedEof,
+            _ => ErrorKind::InvalidData,
+        }
+    }
+
+    /// an LZ4 frame of `blocks`, each `(stored, data)`, with every flag
+    /// other than block independence and a dictionary set
+    fn lz4_frame(blocks: &[(bool, &[u8])], content: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0x04, 0x22, 0x4D, 0x18, 0x5C, 0x40];
+        frame.extend_from_slice(&(content.len() as u64).to_le_bytes());
+        let checksum = (xxhash32_reference(0, &frame[4..]) >> 8) as u8;
+        frame.push(checksum);
+        for &(stored, data) in blocks {
+            let size = data.len() as u32 | if stored { 0x8000_0000 } else { 0 };
+            frame.extend_from_slice(&size.to_le_bytes());
+            frame.extend_from_slice(data);
+            frame.extend_from_slice(&xxhash32_reference(0, data).to_le_bytes());
+        }
+        frame.extend_from_slice(&[0, 0, 0, 0]);
+        frame.extend_from_slice(&xxhash32_reference(0, content).to_le_bytes());
+        frame
+    }
+
+    /// a snappy framing format chunk
+    fn snappy_chunk(kind: u8, uncompressed: &[u8], data: &[u8]) -> Vec<u8> {
+        let length = data.len() as u32 + 4;
+        let mut chunk = vec![
+            kind,
+            length as u8,
+            (length >> 8) as u8,
+            (length >> 16) as u8,
+        ];
+        chunk.extend_from_slice(&mask_checksum(crc32c(0, uncompressed)).to_le_bytes());
+        chunk.extend_from_slice(data);
+        chunk
+    }
+
+    #[test]
+    fn decodes_each_format() {
+        for (input, format) in [
+            (&LZ4_FRAME[..], Format::Lz4Frame),
+            (&LZ4_LEGACY[..], Format::Lz4Legacy),
+            (&GZIP[..], Format::Gzip),
+            (&ZLIB[..], Format::Zlib),
+        ]
+        .iter()
+        {
+            assert_eq!(
+                decode(input),
+                Ok((Some(*format), TEXT.to_vec())),
+                "{:?}",
+                format
+            );
+        }
+
+        let mut framed = b"\xff\x06\x00\x00sNaPpY".to_vec();
+        framed.extend(snappy_chunk(0x00, b"abababababab", b"\x0c\x04ab\x19\x02"));
+        framed.extend(snappy_chunk(0x01, b"xyz", b"xyz"));
+        framed.extend_from_slice(b"\xfe\x02\x00\x00\x00\x00");
+        framed.extend_from_slice(b"\xff\x06\x00\x00sNaPpY");
+        assert_eq!(
+            decode(&framed),
+            Ok((Some(Format::SnappyFramed), b"ababababababxyz".to_vec()))
+        );
+
+        let raw = b"\x0c\x04ab\x19\x02";
+        assert_eq!(
+            decode(raw),
+            Ok((Some(Format::SnappyRaw), b"abababababab".to_vec()))
+        );
+        // a length no block of this size could reach
+        assert_eq!(
+            decode(b"\x80\x80\x04\x04ab\x19\x02"),
+            Err(Error::CorruptBlock { offset: 0 })
+        );
+
+        assert_eq!(decode(&[]), Ok((None, Vec::new())));
+    }
+
+    #[test]
+    fn decodes_linked_lz4_blocks() {
+        // the second block copies from the first
+        let frame = lz4_frame(
+            &[(true, b"abcd"), (false, &[0x04, 0x04, 0x00, 0x00])],
+            b"abcdabcdabcd",
+        );
+        assert_eq!(
+            decode(&frame),
+            Ok((Some(Format::Lz4Frame), b"abcdabcdabcd".to_vec()))
+        );
+    }
+
+    #[test]
+    fn decodes_concatenated_streams() {
+        // a skippable frame, then LZ4 frames of each kind
+        let mut input = vec![0x5A, 0x2A, 0x4D, 0x18, 0x03, 0x00, 0x00, 0x00, 1, 2, 3];
+        input.extend_from_slice(&LZ4_FRAME);
+        input.extend_from_slice(&LZ4_LEGACY);
+        input.extend_from_slice(&LZ4_FRAME);
+        input.extend_from_slice(&[0x50, 0x2A, 0x4D, 0x18, 0x00, 0x00, 0x00, 0x00]);
+        let mut expected = TEXT.to_vec();
+        expected.extend_from_slice(TEXT);
+        expected.extend_from_slice(TEXT);
+        assert_eq!(
+            decode(&input),
+            Ok((Some(Format::Lz4Frame), expected.clone()))
+        );
+
+        let mut input = GZIP.to_vec();
+        input.extend_from_slice(&GZIP);
+        input.extend_from_slice(&GZIP);
+        assert_eq!(decode(&input), Ok((Some(Format::Gzip), expected)));
+    }
+
+    #[test]
+    fn verifies_checksums() {
+        let mut input = LZ4_FRAME;
+        let end = input.len() - 1;
+        input[end] ^= 1;
+        match decode(&input) {
+            Err(Error::ChecksumMismatch { offset, .. }) => assert_eq!(offset, end - 3),
+            other => panic!("{:?}", other),
+        }
+
+        let mut frame = lz4_frame(&[(true, b"abcd")], b"abcd");
+        frame[19] ^= 1;
+        match decode(&frame) {
+            Err(Error::ChecksumMismatch { offset: 23, .. }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        let mut input = GZIP;
+        let end = input.len() - 8;
+        input[end] ^= 1;
+        match decode(&input) {
+            Err(Error::ChecksumMismatch { offset, .. }) => assert_eq!(offset, end),
+            other => panic!("{:?}", other),
+        }
+
+        let mut input = ZLIB;
+        let end = input.len() - 1;
+        input[end] ^= 1;
+        match decode(&input) {
+            Err(Error::ChecksumMismatch { offset, .. }) => assert_eq!(offset, end - 3),
+            other => panic!("{:?}", other),
+        }
+
+        let mut framed = b"\xff\x06\x00\x00sNaPpY".to_vec();
+        framed.extend(snappy_chunk(0x01, b"xyw", b"xyz"));
+        match decode(&framed) {
+            Err(Error::ChecksumMismatch { offset: 14, .. }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_truncation() {
+        // every length, sampled under Miri
+        let step = if cfg!(miri) { 11 } else { 1 };
+        for input in [&LZ4_FRAME[..], &LZ4_LEGACY[..], &GZIP[..], &ZLIB[..]].iter() {
+            for length in (1..input.len()).step_by(step) {
+                match decode(&input[..length]) {
+                    Err(_) => {}
+                    // a legacy frame may end after any block
+                    Ok((Some(Format::Lz4Legacy), ref output)) if length == 4 => {
+                        assert!(output.is_empty())
+                    }
+                    other => panic!("{} of {:?} decoded as {:?}", length, input, other),
+                }
+            }
+        }
+        assert_eq!(
+            decode(&LZ4_FRAME[..20]),
+            Err(Error::Truncated { offset: 20 })
+        );
+        assert_eq!(decode(&GZIP[..30]), Err(Error::Truncated { offset: 30 }));
+    }
+
+    #[test]
+    fn rejects_unknown_and_unsupported() {
+        assert_eq!(decode(b"Hello, world!"), Err(Error::BadMagic { offset: 0 }));
+        assert_eq!(
+            decode(&[0x28, 0xB5, 0x2F, 0xFD, 0x24, 0x18, 0x6D, 0x00]),
+            Err(Error::Unsupported { offset: 0 })
+        );
+        // zstd after a skippable frame
+        assert_eq!(
+            decode(&[0x50, 0x2A, 0x4D, 0x18, 0x00, 0x00, 0x00, 0x00, 0x28, 0xB5, 0x2F, 0xFD]),
+            Err(Error::Unsupported { offset: 8 })
+        );
+        // garbage after a complete frame
+        let mut input = LZ4_FRAME.to_vec();
+        input.extend_from_slice(b"junk");
+        assert_eq!(
+            decode(&input),
+            Err(Error::BadMagic {
+                offset: LZ4_FRAME.len()
+            })
+        );
+        let mut input = GZIP.to_vec();
+        input.extend_from_slice(b"junk");
+        assert_eq!(decode(&input), Err(Error::BadMagic { offset: GZIP.len() }));
+    }
+
+    #[test]
+    fn enforces_limits() {
+        // an LZ4 frame claiming 2^60 bytes of content
+        let mut input = vec![0x04, 0x22, 0x4D, 0x18, 0x6C, 0x40];
+        input.extend_from_slice(&(1u64 << 60).to_le_bytes());
+        let checksum = (xxhash32_reference(0, &input[4..]) >> 8) as u8;
+        input.push(checksum);
+        assert_eq!(decode(&input), Err(Error::LimitExceeded { offset: 6 }));
+
+        // a zstd frame claiming a 3.75TB window, after a skippable frame
+        assert_eq!(
+            decode(&[
+                0x50, 0x2A, 0x4D, 0x18, 0x00, 0x00, 0x00, 0x00, 0x28, 0xB5, 0x2F, 0xFD, 0x00, 0xFF
+            ]),
+            Err(Error::LimitExceeded { offset: 13 })
+        );
+
+        // a 64KiB block and its window do not fit in 100KiB
+        let limits = Limits {
+            max_memory: 100 << 10,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(&LZ4_FRAME, &limits),
+            Err(Error::LimitExceeded { offset: 5 })
+        );
+        // nor does a legacy block
+        assert_eq!(
+            decode_with_limits(&LZ4_LEGACY, &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+
+        // a raw snappy block is checked before it is allocated
+        let limits = Limits {
+            max_output: 11,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(b"\x0c\x04ab\x19\x02", &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+
+        // the output is cut at the limit, where in the input that is
+        // reached depends on how much each read returns
+        let limits = Limits {
+            max_output: TEXT.len() as u64 - 1,
+            ..Limits::default()
+        };
+        for input in [&LZ4_FRAME[..], &LZ4_LEGACY[..], &GZIP[..], &ZLIB[..]].iter() {
+            let mut whole = Vec::new();
+            let error = AnyDecoder::with_limits(*input, &limits)
+                .read_to_end(&mut whole)
+                .unwrap_err();
+            let mut trickled = Vec::new();
+            let trickled_error = AnyDecoder::with_limits(Trickle(input), &limits)
+                .read_to_end(&mut trickled)
+                .unwrap_err();
+            for error in [error, trickled_error].iter() {
+                assert_eq!(error.kind(), ErrorKind::InvalidData);
+                match error.get_ref().unwrap().downcast_ref::<Error>() {
+                    Some(Error::LimitExceeded { .. }) => {}
+                    other => panic!("{:?} decoded as {:?}", input, other),
+                }
+            }
+            assert_eq!(&whole[..], &TEXT[..TEXT.len() - 1]);
+            assert_eq!(whole, trickled);
+        }
+
+        // 70 bytes from the 55 of a legacy frame is over a ratio of 1
+        let limits = Limits {
+            max_ratio: 1,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(&LZ4_LEGACY, &limits),
+            Err(Error::LimitExceeded { offset: 55 })
+        );
+
+        // the third of three gzip members
+        let mut input = GZIP.to_vec();
+        input.extend_from_slice(&GZIP);
+        input.extend_from_slice(&GZIP);
+        let limits = Limits {
+            max_frames: 2,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(&input, &limits),
+            Err(Error::LimitExceeded {
+                offset: 2 * GZIP.len()
+            })
+        );
+        assert_eq!(
+            decode_with_limits(&input[..2 * GZIP.len()], &limits).map(|(_, output)| output.len()),
+            Ok(2 * TEXT.len())
+        );
+    }
+}
@@ -0,0 +1,565 @@
+use super::feature_macros::error::Error;
+
+/// the furthest back a match may reach
+pub const WINDOW_SIZE: usize = 1 << 15;
+
+/// the longest code a deflate Huffman table may use
+const MAX_BITS: usize = 15;
+
+/// the number of literal/length symbols, including the two which are
+/// reserved but still take part in the fixed code
+const LITERAL_SYMBOLS: usize = 288;
+
+/// the number of distance symbols, 30 and 31 are reserved
+const DISTANCE_SYMBOLS: usize = 30;
+
+/// the first length of symbols 257 to 285, and how many extra bits follow
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// the first distance of each distance symbol, and how many extra bits follow
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// the order a dynamic block stores its code length code's lengths in
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// reads a deflate stream's bits, least significant first
+struct Bits<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+impl<'a> Bits<'a> {
+    /// the byte the next bit is in
+    #[inline(always)]
+    fn offset(&self) -> usize {
+        self.position >> 3
+    }
+
+    #[inline]
+    fn bits(&mut self, count: usize) -> Result<u32, Error> {
+        let mut value = 0u32;
+        for index in 0..count {
+            let byte = match self.input.get(self.position >> 3) {
+                Option::Some(byte) => *byte,
+                Option::None => {
+                    return Err(Error::Truncated {
+                        offset: self.input.len(),
+                    })
+                }
+            };
+            value |= (((byte >> (self.position & 7)) & 1) as u32) << index;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// a canonical Huffman code, stored as the number of codes of each
+/// length and the symbols ordered by their code
+#[derive(Clone)]
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: [u16; LITERAL_SYMBOLS],
+}
+impl Huffman {
+    fn new() -> Huffman {
+        Huffman {
+            counts: [0; MAX_BITS + 1],
+            symbols: [0; LITERAL_SYMBOLS],
+        }
+    }
+
+    /// builds the code from each symbol's length, returning how many
+    /// codes of the longest length are left unused, which is negative
+    /// when the lengths are over subscribed
+    fn build(&mut self, lengths: &[u8]) -> isize {
+        self.counts = [0; MAX_BITS + 1];
+        for length in lengths {
+            self.counts[*length as usize] += 1;
+        }
+        if self.counts[0] as usize == lengths.len() {
+            return 0;
+        }
+
+        let mut left = 1isize;
+        for length in 1..=MAX_BITS {
+            left <<= 1;
+            left -= self.counts[length] as isize;
+            if left < 0 {
+                return left;
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for length in 1..MAX_BITS {
+            offsets[length + 1] = offsets[length] + self.counts[length];
+        }
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                self.symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+        left
+    }
+
+    /// checks a built code is complete, or is a single code of one bit,
+    /// which is the only incomplete code deflate permits
+    fn is_valid(&self, left: isize, symbols: usize) -> bool {
+        left == 0 || (left > 0 && symbols == (self.counts[0] + self.counts[1]) as usize)
+    }
+
+    /// reads one symbol, a bit at a time
+    fn decode(&self, bits: &mut Bits) -> Result<usize, Error> {
+        let mut code = 0i32;
+        let mut first = 0i32;
+        let mut index = 0i32;
+        for length in 1..=MAX_BITS {
+            code |= bits.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + (code - first)) as usize] as usize);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(Error::CorruptBlock {
+            offset: bits.offset(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum State {
+    /// the next bits are a block header
+    Header,
+    /// within a stored block, with this many bytes left
+    Stored(usize),
+    /// within a Huffman coded block, with a match of `length` left to copy
+    Codes { length: usize, distance: usize },
+    /// the final block has ended
+    Done,
+}
+
+/// Inflater decodes a deflate (RFC 1951) stream incrementally.
+///
+/// Input and output may be supplied in pieces of any size. Each call
+/// decodes whole symbols until it runs out of input or space, and the
+/// caller passes the unconsumed input back with more appended.
+#[derive(Clone)]
+pub struct Inflater {
+    state: State,
+    last: bool,
+    bit: usize,
+    literals: Huffman,
+    distances: Huffman,
+}
+impl Default for Inflater {
+    fn default() -> Inflater {
+        Inflater::new()
+    }
+}
+impl Inflater {
+    pub fn new() -> Inflater {
+        Inflater {
+            state: State::Header,
+            last: false,
+            bit: 0,
+            literals: Huffman::new(),
+            distances: Huffman::new(),
+        }
+    }
+
+    /// the final block has been decoded
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.state == State::Done
+    }
+
+    /// inflate decodes from `input` into `output[start..]`, returning how
+    /// many bytes of `input` were consumed and the end of what it wrote.
+    ///
+    /// `output[..start]` is the history matches may reference, which
+    /// should be the previous 32KiB of output (`WINDOW_SIZE`). A byte of
+    /// which only some bits were used is not counted as consumed, so it
+    /// must lead the next call's `input`. Once the stream ends the rest
+    /// of its last byte is consumed, and nothing after it is read.
+    ///
+    /// Making no progress means more input is needed, or `output` is full.
+    /// Offsets in errors are into `input`.
+    pub fn inflate(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        start: usize,
+    ) -> Result<(usize, usize), Error> {
+        let mut bits = Bits {
+            input,
+            position: self.bit,
+        };
+        let mut written = start;
+        loop {
+            let saved = bits.position;
+            let step = match self.state {
+                State::Done => break,
+                State::Header => self.header(&mut bits),
+                State::Stored(remaining) => {
+                    // stored blocks start on a byte boundary
+                    let offset = bits.offset();
+                    let count = remaining
+                        .min(input.len() - offset)
+                        .min(output.len() - written);
+                    if count == 0 {
+                        break;
+                    }
+                    output[written..written + count]
+                        .copy_from_slice(&input[offset..offset + count]);
+                    written += count;
+                    bits.position += count * 8;
+                    self.state = if remaining > count {
+                        State::Stored(remaining - count)
+                    } else {
+                        self.end_of_block()
+                    };
+                    Ok(())
+                }
+                State::Codes { length, distance } if length > 0 => {
+                    let count = length.min(output.len() - written);
+                    if count == 0 {
+                        break;
+                    }
+                    for index in written..written + count {
+                        output[index] = output[index - distance];
+                    }
+                    written += count;
+                    self.state = State::Codes {
+                        length: length - count,
+                        distance,
+                    };
+                    Ok(())
+                }
+                State::Codes { .. } => {
+                    if written == output.len() {
+                        break;
+                    }
+                    self.symbol(&mut bits, output, &mut written)
+                }
+            };
+            match step {
+                Ok(()) => {}
+                Err(Error::Truncated { .. }) => {
+                    bits.position = saved;
+                    break;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        if self.state == State::Done {
+            // the stream ends with its last byte
+            bits.position = (bits.position + 7) & !7;
+        }
+        self.bit = bits.position & 7;
+        Ok((bits.offset(), written))
+    }
+
+    /// the state following the end of a block
+    #[inline]
+    fn end_of_block(&self) -> State {
+        if self.last {
+            State::Done
+        } else {
+            State::Header
+        }
+    }
+
+    /// reads a block header, and a dynamic block's code lengths
+    fn header(&mut self, bits: &mut Bits) -> Result<(), Error> {
+        let offset = bits.offset();
+        self.last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => {
+                bits.position = (bits.position + 7) & !7;
+                let length = bits.bits(16)? as usize;
+                let complement = bits.bits(16)? as usize;
+                if length != !complement & 0xFFFF {
+                    return Err(Error::CorruptBlock {
+                        offset: bits.offset() - 2,
+                    });
+                }
+                self.state = if length == 0 {
+                    self.end_of_block()
+                } else {
+                    State::Stored(length)
+                };
+            }
+            1 => {
+                let mut lengths = [0u8; LITERAL_SYMBOLS];
+                for (symbol, length) in lengths.iter_mut().enumerate() {
+                    *length = match symbol {
+                        0..=143 => 8,
+                        144..=255 => 9,
+                        256..=279 => 7,
+                        _ => 8,
+                    };
+                }
+                self.literals.build(&lengths);
+                self.distances.build(&[5; DISTANCE_SYMBOLS]);
+                self.state = State::Codes {
+                    length: 0,
+                    distance: 0,
+                };
+            }
+            2 => {
+                self.dynamic(bits, offset)?;
+                self.state = State::Codes {
+                    length: 0,
+                    distance: 0,
+                };
+            }
+            _ => return Err(Error::CorruptBlock { offset }),
+        };
+        Ok(())
+    }
+
+    /// reads the code lengths of a dynamic block, which are themselves
+    /// Huffman coded
+    fn dynamic(&mut self, bits: &mut Bits, offset: usize) -> Result<(), Error> {
+        let literals = bits.bits(5)? as usize + 257;
+        let distances = bits.bits(5)? as usize + 1;
+        let code_lengths = bits.bits(4)? as usize + 4;
+        if literals > 286 || distances > DISTANCE_SYMBOLS {
+            return Err(Error::CorruptBlock { offset });
+        }
+
+        let mut lengths = [0u8; LITERAL_SYMBOLS + DISTANCE_SYMBOLS];
+        for index in 0..code_lengths {
+            lengths[CODE_LENGTH_ORDER[index]] = bits.bits(3)? as u8;
+        }
+        let mut code = Huffman::new();
+        if code.build(&lengths[..19]) != 0 {
+            return Err(Error::CorruptBlock { offset });
+        }
+
+        lengths = [0u8; LITERAL_SYMBOLS + DISTANCE_SYMBOLS];
+        let mut index = 0usize;
+        while index < literals + distances {
+            let symbol = code.decode(bits)?;
+            if symbol < 16 {
+                lengths[index] = symbol as u8;
+                index += 1;
+                continue;
+            }
+            let (length, repeat) = match symbol {
+                16 => {
+                    if index == 0 {
+                        return Err(Error::CorruptBlock {
+                            offset: bits.offset(),
+                        });
+                    }
+                    (lengths[index - 1], 3 + bits.bits(2)? as usize)
+                }
+                17 => (0, 3 + bits.bits(3)? as usize),
+                _ => (0, 11 + bits.bits(7)? as usize),
+            };
+            if index + repeat > literals + distances {
+                return Err(Error::CorruptBlock {
+                    offset: bits.offset(),
+                });
+            }
+            for slot in lengths[index..index + repeat].iter_mut() {
+                *slot = length;
+            }
+            index += repeat;
+        }
+
+        // a block without an end of block code could never end
+        if lengths[256] == 0 {
+            return Err(Error::CorruptBlock { offset });
+        }
+        let left = self.literals.build(&lengths[..literals]);
+        if !self.literals.is_valid(left, literals) {
+            return Err(Error::CorruptBlock { offset });
+        }
+        let left = self
+            .distances
+            .build(&lengths[literals..literals + distances]);
+        if !self.distances.is_valid(left, distances) {
+            return Err(Error::CorruptBlock { offset });
+        }
+        Ok(())
+    }
+
+    /// decodes a literal, the end of the block, or the start of a match
+    fn symbol(
+        &mut self,
+        bits: &mut Bits,
+        output: &mut [u8],
+        written: &mut usize,
+    ) -> Result<(), Error> {
+        let offset = bits.offset();
+        let symbol = self.literals.decode(bits)?;
+        if symbol < 256 {
+            output[*written] = symbol as u8;
+            *written += 1;
+            return Ok(());
+        }
+        if symbol == 256 {
+            self.state = self.end_of_block();
+            return Ok(());
+        }
+
+        let symbol = symbol - 257;
+        if symbol >= LENGTH_BASE.len() {
+            return Err(Error::CorruptBlock { offset });
+        }
+        let length =
+            LENGTH_BASE[symbol] as usize + bits.bits(LENGTH_EXTRA[symbol] as usize)? as usize;
+        let symbol = self.distances.decode(bits)?;
+        if symbol >= DISTANCE_BASE.len() {
+            return Err(Error::CorruptBlock { offset });
+        }
+        let distance =
+            DISTANCE_BASE[symbol] as usize + bits.bits(DISTANCE_EXTRA[symbol] as usize)? as usize;
+        if distance > *written {
+            return Err(Error::CorruptBlock { offset });
+        }
+        self.state = State::Codes { length, distance };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::checksum::crc32;
+    use super::super::feature_macros::error::Error;
+    use super::Inflater;
+
+    /// `hello...` compressed with a fixed Huffman code
+    const FIXED: [u8; 36] = [
+        0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0xC0, 0x4E, 0xEA, 0x28, 0x94, 0x64, 0x64, 0x16,
+        0x2B, 0x00, 0x51, 0x4A, 0x6A, 0x5A, 0x4E, 0x62, 0x49, 0xAA, 0x42, 0x4A, 0x62, 0x49, 0xA2,
+        0x0E, 0xB2, 0x12, 0x45, 0x2E, 0x00,
+    ];
+    const FIXED_TEXT: &[u8] =
+        b"hello hello hello hello hello, this is deflate data, hello hello!\n";
+
+    /// `99 bottles of beer...` down to 90, compressed with a dynamic code
+    const DYNAMIC: [u8; 90] = [
+        0x85, 0xCB, 0xCB, 0x09, 0x80, 0x30, 0x10, 0x05, 0xC0, 0xBB, 0x55, 0x6C, 0x01, 0x22, 0x89,
+        0xF9, 0x97, 0x63, 0x60, 0xC5, 0x43, 0x30, 0xA0, 0x01, 0xDB, 0xB7, 0x80, 0x3C, 0x78, 0xE7,
+        0x61, 0x4A, 0x91, 0xDA, 0xC7, 0x68, 0xFA, 0x4A, 0x3F, 0xA5, 0xAA, 0x3E, 0xD2, 0x6F, 0x19,
+        0x97, 0xCA, 0x77, 0xB4, 0xB6, 0x4A, 0x99, 0x7C, 0x5B, 0x4A, 0x26, 0x27, 0x83, 0x93, 0xC8,
+        0x49, 0xE0, 0x44, 0x72, 0x22, 0x38, 0x81, 0x9C, 0x00, 0x8E, 0x27, 0xC7, 0x83, 0xE3, 0xC8,
+        0x71, 0xE0, 0xEC, 0xE4, 0xEC, 0xE0, 0x58, 0x72, 0x2C, 0x38, 0x86, 0x1C, 0x33, 0x9F, 0x1F,
+    ];
+    const DYNAMIC_LENGTH: usize = 520;
+    const DYNAMIC_CRC: u32 = 0x3BCDEAF4;
+
+    #[test]
+    fn inflates_whole() {
+        let mut output = [0u8; 1024];
+        let mut dut = Inflater::new();
+        assert_eq!(
+            dut.inflate(&FIXED, &mut output, 0),
+            Ok((FIXED.len(), FIXED_TEXT.len()))
+        );
+        assert!(dut.is_done());
+        assert_eq!(&output[..FIXED_TEXT.len()], FIXED_TEXT);
+
+        let mut dut = Inflater::new();
+        assert_eq!(
+            dut.inflate(&DYNAMIC, &mut output, 0),
+            Ok((DYNAMIC.len(), DYNAMIC_LENGTH))
+        );
+        assert!(dut.is_done());
+        assert_eq!(crc32(0, &output[..DYNAMIC_LENGTH]), DYNAMIC_CRC);
+        assert!(output.starts_with(b"99 bottles of beer on the wall, 99 bottles of beer.\n98"));
+
+        // stored, then trailing data which is not read
+        let dut_input = [
+            0x01, 0x06, 0x00, 0xF9, 0xFF, b's', b't', b'o', b'r', b'e', b'd', 0xAA,
+        ];
+        let mut dut = Inflater::new();
+        assert_eq!(dut.inflate(&dut_input, &mut output, 0), Ok((11, 6)));
+        assert_eq!(&output[..6], b"stored");
+    }
+
+    #[test]
+    fn inflates_in_pieces() {
+        // a byte of input and at most 7 bytes of output at a time, with
+        // the output kept as history in one buffer
+        let mut output = [0u8; 1024];
+        let mut dut = Inflater::new();
+        let mut consumed = 0usize;
+        let mut available = 0usize;
+        let mut written = 0usize;
+        while !dut.is_done() {
+            if available < DYNAMIC.len() {
+                available += 1;
+            }
+            let end = (written + 7).min(output.len());
+            let (used, end) = dut
+                .inflate(&DYNAMIC[consumed..available], &mut output[..end], written)
+                .unwrap();
+            consumed += used;
+            written = end;
+        }
+        assert_eq!(consumed, DYNAMIC.len());
+        assert_eq!(written, DYNAMIC_LENGTH);
+        assert_eq!(crc32(0, &output[..written]), DYNAMIC_CRC);
+    }
+
+    #[test]
+    fn references_history() {
+        // a fixed block of one match, 4 bytes from 3 back, then the end
+        let dut_input = [0x03, 0x21, 0x00, 0xAA];
+        let mut output = *b"abc\0\0\0\0\0";
+        let mut dut = Inflater::new();
+        assert_eq!(dut.inflate(&dut_input, &mut output, 3), Ok((3, 7)));
+        assert!(dut.is_done());
+        assert_eq!(&output[..7], b"abcabca");
+
+        let mut dut = Inflater::new();
+        assert_eq!(
+            dut.inflate(&dut_input, &mut output, 2),
+            Err(Error::CorruptBlock { offset: 0 })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        let mut output = [0u8; 64];
+        // the reserved block type
+        assert_eq!(
+            Inflater::new().inflate(&[0x07], &mut output, 0),
+            Err(Error::CorruptBlock { offset: 0 })
+        );
+        // a stored length which does not match its complement
+        assert_eq!(
+            Inflater::new().inflate(&[0x01, 0x06, 0x00, 0xF9, 0xFE], &mut output, 0),
+            Err(Error::CorruptBlock { offset: 3 })
+        );
+        // truncated input only stops progress, the caller knows it ended
+        let mut dut = Inflater::new();
+        let (used, end) = dut.inflate(&DYNAMIC[..40], &mut output, 0).unwrap();
+        assert!(used < 40 && end < DYNAMIC_LENGTH && !dut.is_done());
+    }
+}
@@ -0,0 +1,373 @@
+use super::feature_macros::numbers::{Num, PrimativeNumber};
+
+/// LZ4 frame format magic number
+const LZ4_FRAME_MAGIC: u32 = 0x184D2204u32;
+
+/// LZ4 legacy frame format magic number, as emitted by `lz4 -l`
+const LZ4_LEGACY_MAGIC: u32 = 0x184C2102u32;
+
+/// zstd frame magic number
+const ZSTD_MAGIC: u32 = 0xFD2FB528u32;
+
+/// skippable frames are shared between LZ4 and zstd, the low
+/// nibble of the magic number is user defined.
+const SKIPPABLE_MAGIC: u32 = 0x184D2A50u32;
+const SKIPPABLE_MASK: u32 = 0xFFFFFFF0u32;
+
+/// the snappy framing format always opens with a stream identifier chunk
+const SNAPPY_STREAM_IDENTIFIER: &[u8] = b"\xff\x06\x00\x00sNaPpY";
+
+/// Format is an encoding which `detect_format` can recognize
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Format {
+    /// LZ4 frame format
+    Lz4Frame,
+    /// LZ4 legacy frame format
+    Lz4Legacy,
+    /// zstd frame
+    Zstd,
+    /// the buffer only contains skippable frames, or ends within one,
+    /// which both LZ4 and zstd may emit, so the codec cannot be determined
+    Skippable,
+    /// gzip (RFC 1952) with deflate compression
+    Gzip,
+    /// zlib (RFC 1950) with deflate compression
+    Zlib,
+    /// snappy framing format
+    SnappyFramed,
+    /// possibly a raw snappy block with no framing, this has no magic
+    /// number so it is only a guess
+    SnappyRaw,
+}
+
+/// detect_format inspects the start of a buffer and reports what encoding
+/// it appears to be in.
+///
+/// Skippable frames are stepped over so that the frame which follows them
+/// decides the format, when the buffer ends within one (as a peeked header
+/// may) the result is `Format::Skippable`. Raw snappy has no magic number,
+/// so it is identified heuristically and only after every other format has
+/// been ruled out, `Format::SnappyRaw` means the buffer is possibly snappy.
+pub fn detect_format(buffer: &[u8]) -> Option<Format> {
+    let mut buffer = buffer;
+    let mut skipped = false;
+    while is_skippable(buffer) {
+        match skip_skippable_frame(buffer) {
+            Some(remaining) => buffer = remaining,
+            // the frame continues past what was peeked
+            None => return Some(Format::Skippable),
+        };
+        skipped = true;
+    }
+    if buffer.is_empty() {
+        return if skipped {
+            Some(Format::Skippable)
+        } else {
+            None
+        };
+    }
+
+    if buffer.len() >= 4 {
+        match Num::<u32>::read_value_le(buffer).inner() {
+            LZ4_FRAME_MAGIC => return Some(Format::Lz4Frame),
+            ZSTD_MAGIC => return Some(Format::Zstd),
+            LZ4_LEGACY_MAGIC if !skipped => return Some(Format::Lz4Legacy),
+            _ => {}
+        };
+    }
+    if skipped {
+        // only LZ4 and zstd frames may follow a skippable frame
+        return None;
+    }
+
+    if buffer.starts_with(SNAPPY_STREAM_IDENTIFIER) {
+        Some(Format::SnappyFramed)
+    } else if is_gzip(buffer) {
+        Some(Format::Gzip)
+    } else if is_zlib(buffer) {
+        Some(Format::Zlib)
+    } else if is_snappy_raw(buffer) {
+        Some(Format::SnappyRaw)
+    } else {
+        None
+    }
+}
+
+/// checks if the buffer starts with a skippable frame's magic number
+#[inline]
+fn is_skippable(buffer: &[u8]) -> bool {
+    buffer.len() >= 4
+        && Num::<u32>::read_value_le(&buffer[0..4]).inner() & SKIPPABLE_MASK == SKIPPABLE_MAGIC
+}
+
+/// returns the data following the skippable frame at the start of the
+/// buffer, if the whole frame is present
+#[inline]
+fn skip_skippable_frame(buffer: &[u8]) -> Option<&[u8]> {
+    if buffer.len() < 8 {
+        return None;
+    }
+    let length = Num::<u32>::read_value_le(&buffer[4..8]).inner() as usize;
+    let remaining = &buffer[8..];
+    if remaining.len() < length {
+        return None;
+    }
+    Some(&remaining[length..])
+}
+
+/// gzip has a two byte magic number, followed by the compression method
+/// where `8` is the only one defined (deflate).
+#[inline]
+fn is_gzip(buffer: &[u8]) -> bool {
+    buffer.len() >= 3 && buffer[0] == 0x1F && buffer[1] == 0x8B && buffer[2] == 0x08
+}
+
+/// zlib's two byte header is self checking. The compression method
+/// must be deflate with at most a 32KiB window, and the header read
+/// as a big endian `u16` must be a multiple of 31.
+#[inline]
+fn is_zlib(buffer: &[u8]) -> bool {
+    if buffer.len() < 2 {
+        return false;
+    }
+    let cmf = buffer[0];
+    let header = Num::<u16>::read_value_be(buffer).inner();
+    (cmf & 0x0F) == 8 && (cmf >> 4) <= 7 && (header % 31) == 0
+}
+
+/// the number of elements `is_snappy_raw` checks before it accepts a
+/// buffer, if the stream does not end first
+const SNAPPY_RAW_ELEMENTS: usize = 4;
+
+/// raw snappy starts with a varint of the uncompressed length, followed by
+/// elements which are literals or copies of earlier output.
+///
+/// Short text can look like a length and a literal, so the elements are
+/// walked: the first must be a literal, literals must fit within both the
+/// buffer and the length, and copies may only reference what was already
+/// produced. Encoders only emit two literals in a row where they split
+/// the input into 64KiB fragments, so a literal must otherwise be followed
+/// by a copy. The buffer is accepted when it holds exactly a whole stream,
+/// or when at least two elements check out before it ends (as a peeked
+/// header may, within an element).
+#[inline]
+fn is_snappy_raw(buffer: &[u8]) -> bool {
+    // the length is a `u32` so the varint is at most 5 bytes
+    let mut uncompressed = 0u64;
+    let mut position = 0usize;
+    loop {
+        if position >= 5 || position >= buffer.len() {
+            return false;
+        }
+        let byte = buffer[position];
+        uncompressed |= ((byte & 0x7F) as u64) << (7 * position);
+        position += 1;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    if uncompressed == 0 || uncompressed > (<Num<u32> as PrimativeNumber>::max() as u64) {
+        return false;
+    }
+
+    let mut produced = 0u64;
+    let mut elements = 0usize;
+    let mut literal = false;
+    while elements < SNAPPY_RAW_ELEMENTS {
+        if produced == uncompressed {
+            // the stream is complete, nothing may follow it
+            return position == buffer.len();
+        }
+        let tag = match buffer.get(position) {
+            Option::Some(tag) => *tag,
+            Option::None => break,
+        };
+        let (width, length) = match tag & 0x03 {
+            0 => match tag >> 2 {
+                x @ 0..=59 => (0, (x as u64) + 1),
+                // 60..=63 store the length in the following 1 to 4 bytes
+                x => {
+                    let width = (x - 59) as usize;
+                    match read_le(buffer, position + 1, width) {
+                        Option::Some(length) => (width, length + 1),
+                        Option::None => break,
+                    }
+                }
+            },
+            1 => (1, 4 + ((tag >> 2) & 0x07) as u64),
+            2 => (2, ((tag >> 2) as u64) + 1),
+            _ => (4, ((tag >> 2) as u64) + 1),
+        };
+        if length > uncompressed - produced {
+            return false;
+        }
+        if tag & 0x03 == 0 {
+            if literal && produced & 0xFFFF != 0 {
+                return false;
+            }
+            let end = position as u64 + 1 + width as u64 + length;
+            if end > buffer.len() as u64 {
+                // a literal running past the buffer is only acceptable
+                // once the start of the stream has been checked
+                if elements == 0 {
+                    return false;
+                }
+                break;
+            }
+            position = end as usize;
+            literal = true;
+        } else {
+            if elements == 0 {
+                // nothing has been produced for the copy to reference
+                return false;
+            }
+            let offset = match read_le(buffer, position + 1, width) {
+                Option::Some(offset) if tag & 0x03 == 1 => (((tag >> 5) as u64) << 8) | offset,
+                Option::Some(offset) => offset,
+                Option::None => break,
+            };
+            if offset == 0 || offset > produced {
+                return false;
+            }
+            position += 1 + width;
+            literal = false;
+        }
+        produced += length;
+        elements += 1;
+    }
+    if produced == uncompressed {
+        return position == buffer.len();
+    }
+    elements >= 2
+}
+
+/// reads a little endian value of `width` bytes at `position`
+#[inline]
+fn read_le(buffer: &[u8], position: usize, width: usize) -> Option<u64> {
+    buffer.get(position..position + width).map(|bytes| {
+        bytes
+            .iter()
+            .rev()
+            .fold(0u64, |acc, byte| (acc << 8) | (*byte as u64))
+    })
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{detect_format, Format};
+
+    #[test]
+    fn detects_lz4_frame() {
+        let dut = [0x04, 0x22, 0x4D, 0x18, 0x64, 0x40, 0xA7];
+        assert_eq!(detect_format(&dut), Some(Format::Lz4Frame));
+    }
+
+    #[test]
+    fn detects_lz4_legacy() {
+        let dut = [0x02, 0x21, 0x4C, 0x18, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(detect_format(&dut), Some(Format::Lz4Legacy));
+    }
+
+    #[test]
+    fn detects_zstd() {
+        let dut = [0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x00];
+        assert_eq!(detect_format(&dut), Some(Format::Zstd));
+    }
+
+    #[test]
+    fn steps_over_skippable_frames() {
+        let dut = [
+            0x5E, 0x2A, 0x4D, 0x18, 0x02, 0x00, 0x00, 0x00, 0xAA, 0xBB, 0x28, 0xB5, 0x2F, 0xFD,
+        ];
+        assert_eq!(detect_format(&dut), Some(Format::Zstd));
+
+        let dut = [0x50, 0x2A, 0x4D, 0x18, 0x01, 0x00, 0x00, 0x00, 0xAA];
+        assert_eq!(detect_format(&dut), Some(Format::Skippable));
+
+        // a peeked header may end within a large skippable frame
+        let dut = [0x50, 0x2A, 0x4D, 0x18, 0x00, 0x10, 0x00, 0x00, 0xAA, 0xBB];
+        assert_eq!(detect_format(&dut), Some(Format::Skippable));
+        assert_eq!(detect_format(&dut[..6]), Some(Format::Skippable));
+        assert_eq!(detect_format(&dut[..4]), Some(Format::Skippable));
+        let dut = [
+            0x5E, 0x2A, 0x4D, 0x18, 0x01, 0x00, 0x00, 0x00, 0xAA, 0x51, 0x2A, 0x4D, 0x18, 0xFF,
+        ];
+        assert_eq!(detect_format(&dut), Some(Format::Skippable));
+
+        // a skippable frame may not be followed by arbitrary data
+        let dut = [
+            0x50, 0x2A, 0x4D, 0x18, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x8B, 0x08,
+        ];
+        assert_eq!(detect_format(&dut), None);
+    }
+
+    #[test]
+    fn detects_gzip() {
+        let dut = [0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03];
+        assert_eq!(detect_format(&dut), Some(Format::Gzip));
+    }
+
+    #[test]
+    fn detects_zlib() {
+        for header in [[0x78u8, 0x01], [0x78, 0x5E], [0x78, 0x9C], [0x78, 0xDA]].iter() {
+            assert_eq!(detect_format(header), Some(Format::Zlib));
+        }
+        assert_eq!(detect_format(&[0x78, 0x9D]), None);
+    }
+
+    #[test]
+    fn detects_snappy_framed() {
+        let dut = b"\xff\x06\x00\x00sNaPpY\x01\x09\x00\x00";
+        assert_eq!(detect_format(dut), Some(Format::SnappyFramed));
+    }
+
+    #[test]
+    fn detects_snappy_raw() {
+        // "hello" as a single literal
+        let dut = b"\x05\x10hello";
+        assert_eq!(detect_format(dut), Some(Format::SnappyRaw));
+
+        // "abababababab" as a literal and an overlapping copy
+        let dut = b"\x0c\x04ab\x19\x02";
+        assert_eq!(detect_format(dut), Some(Format::SnappyRaw));
+
+        // a peeked header ending within the third element
+        let dut = b"\x40\x04ab\x19\x02\x1cabc";
+        assert_eq!(detect_format(dut), Some(Format::SnappyRaw));
+
+        // literal longer than the declared length
+        let dut = b"\x02\x10hello";
+        assert_eq!(detect_format(dut), None);
+
+        // copy from before the start of the output
+        let dut = b"\x0c\x04ab\x19\x03";
+        assert_eq!(detect_format(dut), None);
+
+        // data following a complete stream
+        let dut = b"\x05\x10hello\x00";
+        assert_eq!(detect_format(dut), None);
+    }
+
+    #[test]
+    fn text_is_not_snappy_raw() {
+        // each starts with a plausible length and literal tag
+        let texts: [&[u8]; 6] = [
+            b"a ",
+            b"ad",
+            b"adding some text to the buffer",
+            b"dd if=/dev/zero of=/tmp/out bs=1M",
+            b"a dog, a cat and a horse walked into a bar",
+            b"\x05\x10hel",
+        ];
+        for text in texts.iter() {
+            assert_eq!(detect_format(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown() {
+        assert_eq!(detect_format(&[]), None);
+        assert_eq!(detect_format(b"Hello, world!"), None);
+    }
+}
@@ -0,0 +1,31 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate feature_macros;
+extern crate xxhash;
+
+/// detect handles identifying the encoding of a buffer by its header
+pub mod detect;
+pub use detect::{detect_format, Format};
+
+/// lz4 parses the LZ4 frame format's headers
+pub mod lz4;
+
+/// zstd parses the zstd frame format's headers
+pub mod zstd;
+
+/// checksum holds the CRCs and Adler-32 the formats store
+pub mod checksum;
+
+/// snappy decodes raw snappy blocks
+pub mod snappy;
+
+/// deflate decodes the deflate stream within gzip and zlib
+pub mod deflate;
+
+/// decoder decompresses a stream of any detected format
+#[cfg(feature = "std")]
+pub mod decoder;
+#[cfg(feature = "std")]
+pub use decoder::AnyDecoder;
@@ -0,0 +1,438 @@
+use super::feature_macros::endian::{view, Le};
+use super::feature_macros::error::Error;
+use super::feature_macros::limits::Limits;
+use super::feature_macros::numbers::PrimativeNumber;
+use super::xxhash::bits32::xxhash32_reference;
+
+/// LZ4 frame format magic number
+const MAGIC: u32 = 0x184D2204u32;
+
+/// the only frame format version which has been defined
+const VERSION: u8 = 0b01;
+
+/// how far back LZ4 matches reach
+pub const WINDOW_SIZE: usize = 1 << 16;
+
+view! {
+    /// the fixed width start of an LZ4 frame
+    struct Prefix {
+        magic: Le<u32>,
+        flags: u8,
+        block_descriptor: u8,
+    }
+}
+
+/// FrameDescriptor is the header of an LZ4 frame, it describes how the
+/// blocks which follow it are laid out.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct FrameDescriptor {
+    /// blocks do not reference data within earlier blocks
+    pub block_independence: bool,
+    /// every block is followed by an XXH32 of its contents
+    pub block_checksum: bool,
+    /// the frame ends with an XXH32 of the decompressed contents
+    pub content_checksum: bool,
+    /// the decompressed size of the frame, when it was recorded
+    pub content_size: Option<u64>,
+    /// the dictionary the frame was compressed with, if any
+    pub dictionary_id: Option<u32>,
+    /// the largest decompressed size of a block
+    pub block_max_size: usize,
+    /// the length of the header (including the magic number) in bytes
+    pub header_length: usize,
+}
+
+/// parse_frame_descriptor reads the header at the start of an LZ4 frame,
+/// and verifies its checksum.
+///
+/// Reserved bits must be zero, as the specification requires decoders to
+/// reject them, so a set bit is reported as `Error::CorruptBlock`. The
+/// descriptor is checked against `Limits::default()`.
+#[inline]
+pub fn parse_frame_descriptor(buffer: &[u8]) -> Result<FrameDescriptor, Error> {
+    parse_frame_descriptor_with_limits(buffer, &Limits::default())
+}
+
+/// parse_frame_descriptor_with_limits is `parse_frame_descriptor`, which
+/// also checks the frame against `limits`.
+///
+/// A block and the window behind it must fit in `max_memory`, and a
+/// recorded content size in `max_output`, otherwise
+/// `Error::LimitExceeded` is returned at the field which claimed them.
+pub fn parse_frame_descriptor_with_limits(
+    buffer: &[u8],
+    limits: &Limits,
+) -> Result<FrameDescriptor, Error> {
+    let prefix = view::<Prefix>(buffer)?;
+    if prefix.magic.get().inner() != MAGIC {
+        return Err(Error::BadMagic { offset: 0 });
+    }
+
+    let flags = prefix.flags;
+    if flags >> 6 != VERSION {
+        return Err(Error::Unsupported { offset: 4 });
+    }
+    if flags & 0b10 != 0 {
+        return Err(Error::CorruptBlock { offset: 4 });
+    }
+    let block_descriptor = prefix.block_descriptor;
+    if block_descriptor & 0x8F != 0 {
+        return Err(Error::CorruptBlock { offset: 5 });
+    }
+    let block_max_size = match block_descriptor >> 4 {
+        4 => 64 << 10,
+        5 => 256 << 10,
+        6 => 1 << 20,
+        7 => 4 << 20,
+        _ => return Err(Error::CorruptBlock { offset: 5 }),
+    };
+
+    let mut position = Prefix::SIZE;
+    let content_size = if flags & 0b1000 != 0 {
+        let value = view::<Le<u64>>(&buffer[position..]).map_err(|e| e.rebase(position))?;
+        position += 8;
+        Option::Some(value.get().inner())
+    } else {
+        Option::None
+    };
+    let dictionary_id = if flags & 0b1 != 0 {
+        let value = view::<Le<u32>>(&buffer[position..]).map_err(|e| e.rebase(position))?;
+        position += 4;
+        Option::Some(value.get().inner())
+    } else {
+        Option::None
+    };
+
+    // the header checksum covers the descriptor, but not the magic number
+    let stored = *view::<u8>(&buffer[position..]).map_err(|e| e.rebase(position))?;
+    let computed = (xxhash32_reference(0, &buffer[4..position]) >> 8) as u8;
+    if stored != computed {
+        return Err(Error::ChecksumMismatch {
+            offset: position,
+            expected: stored as u64,
+            actual: computed as u64,
+        });
+    }
+
+    // only a descriptor which passed its checksum is held to the limits
+    limits.check_window(WINDOW_SIZE as u64, 5)?;
+    limits.check_memory((WINDOW_SIZE + block_max_size) as u64, 5)?;
+    if let Option::Some(size) = content_size {
+        limits.check_output(size, Prefix::SIZE)?;
+    }
+
+    Ok(FrameDescriptor {
+        block_independence: flags & 0b10_0000 != 0,
+        block_checksum: flags & 0b1_0000 != 0,
+        content_checksum: flags & 0b100 != 0,
+        content_size,
+        dictionary_id,
+        block_max_size,
+        header_length: position + 1,
+    })
+}
+
+/// the number of bytes a match copies beyond its encoded length
+const MIN_MATCH: usize = 4;
+
+/// decompress_block decodes one LZ4 block from `input` into
+/// `output[start..]`, returning the end of what it wrote.
+///
+/// `output[..start]` is the history matches may reference, which is the
+/// previous 64KiB of output for linked blocks and empty for independent
+/// ones. A block which would write past the end of `output` returns
+/// `Error::OutputFull`, and offsets in errors are into `input`.
+pub fn decompress_block(input: &[u8], output: &mut [u8], start: usize) -> Result<usize, Error> {
+    let mut position = 0usize;
+    let mut written = start;
+    loop {
+        let token = *input
+            .get(position)
+            .ok_or(Error::Truncated { offset: position })?;
+        position += 1;
+
+        let literals = read_length(input, &mut position, (token >> 4) as usize)?;
+        if input.len() - position < literals {
+            return Err(Error::Truncated {
+                offset: input.len(),
+            });
+        }
+        if output.len() - written < literals {
+            return Err(Error::OutputFull { offset: written });
+        }
+        output[written..written + literals].copy_from_slice(&input[position..position + literals]);
+        position += literals;
+        written += literals;
+
+        // the last sequence is only literals
+        if position == input.len() {
+            return Ok(written);
+        }
+
+        if input.len() - position < 2 {
+            return Err(Error::Truncated {
+                offset: input.len(),
+            });
+        }
+        let offset = input[position] as usize | (input[position + 1] as usize) << 8;
+        if offset == 0 || offset > written {
+            return Err(Error::CorruptBlock { offset: position });
+        }
+        position += 2;
+
+        let length = read_length(input, &mut position, (token & 0x0F) as usize)? + MIN_MATCH;
+        if output.len() - written < length {
+            return Err(Error::OutputFull { offset: written });
+        }
+        if offset >= length {
+            let (history, rest) = output.split_at_mut(written);
+            rest[..length].copy_from_slice(&history[written - offset..written - offset + length]);
+        } else {
+            // the match overlaps what it writes, so repeats a short run
+            for index in written..written + length {
+                output[index] = output[index - offset];
+            }
+        }
+        written += length;
+    }
+}
+
+/// reads the remainder of a length whose 4 bit field in the token was
+/// `nibble`, where 15 means 255 valued bytes follow until one is not
+#[inline]
+fn read_length(input: &[u8], position: &mut usize, nibble: usize) -> Result<usize, Error> {
+    let mut length = nibble;
+    if nibble == 15 {
+        loop {
+            let byte = *input
+                .get(*position)
+                .ok_or(Error::Truncated { offset: *position })?;
+            *position += 1;
+            length = length
+                .checked_add(byte as usize)
+                .ok_or(Error::CorruptBlock {
+                    offset: *position - 1,
+                })?;
+            if byte != 255 {
+                break;
+            }
+        }
+    }
+    Ok(length)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::feature_macros::error::Error;
+    use super::super::feature_macros::limits::Limits;
+    use super::super::xxhash::bits32::xxhash32_reference;
+    use super::{
+        decompress_block, parse_frame_descriptor, parse_frame_descriptor_with_limits,
+        FrameDescriptor,
+    };
+
+    #[test]
+    fn parses_descriptor() {
+        // `lz4` defaults
+        let dut = [0x04, 0x22, 0x4D, 0x18, 0x64, 0x40, 0xA7, 0x0F, 0x00];
+        assert_eq!(
+            parse_frame_descriptor(&dut),
+            Ok(FrameDescriptor {
+                block_independence: true,
+                block_checksum: false,
+                content_checksum: true,
+                content_size: None,
+                dictionary_id: None,
+                block_max_size: 64 << 10,
+                header_length: 7,
+            })
+        );
+
+        // `lz4 --content-size` on a 24 byte file
+        let dut = [
+            0x04, 0x22, 0x4D, 0x18, 0x6C, 0x40, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x35, 0x0F,
+        ];
+        let descriptor = parse_frame_descriptor(&dut).unwrap();
+        assert_eq!(descriptor.content_size, Some(24));
+        assert_eq!(descriptor.header_length, 15);
+    }
+
+    #[test]
+    fn parses_dictionary_id() {
+        let mut dut = [
+            0x04, 0x22, 0x4D, 0x18, 0x51, 0x70, 0x78, 0x56, 0x34, 0x12, 0x00,
+        ];
+        dut[10] = (xxhash32_reference(0, &dut[4..10]) >> 8) as u8;
+        let descriptor = parse_frame_descriptor(&dut).unwrap();
+        assert!(!descriptor.block_independence);
+        assert!(descriptor.block_checksum);
+        assert_eq!(descriptor.dictionary_id, Some(0x12345678));
+        assert_eq!(descriptor.block_max_size, 4 << 20);
+        assert_eq!(descriptor.header_length, 11);
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        let dut = [0x04, 0x22, 0x4D, 0x18, 0x64, 0x40, 0xA7];
+        assert!(parse_frame_descriptor(&dut).is_ok());
+
+        let mut bad = dut;
+        bad[0] = 0x05;
+        assert_eq!(
+            parse_frame_descriptor(&bad),
+            Err(Error::BadMagic { offset: 0 })
+        );
+
+        let mut bad = dut;
+        bad[4] = 0xA4;
+        assert_eq!(
+            parse_frame_descriptor(&bad),
+            Err(Error::Unsupported { offset: 4 })
+        );
+
+        let mut bad = dut;
+        bad[4] = 0x66;
+        assert_eq!(
+            parse_frame_descriptor(&bad),
+            Err(Error::CorruptBlock { offset: 4 })
+        );
+
+        let mut bad = dut;
+        bad[5] = 0x30;
+        assert_eq!(
+            parse_frame_descriptor(&bad),
+            Err(Error::CorruptBlock { offset: 5 })
+        );
+
+        let mut bad = dut;
+        bad[6] = 0xA8;
+        assert_eq!(
+            parse_frame_descriptor(&bad),
+            Err(Error::ChecksumMismatch {
+                offset: 6,
+                expected: 0xA8,
+                actual: 0xA7,
+            })
+        );
+    }
+
+    #[test]
+    fn enforces_limits() {
+        // a valid header claiming 2^60 bytes of content, in 4MiB blocks
+        let mut dut = [
+            0x04, 0x22, 0x4D, 0x18, 0x6C, 0x70, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+            0x00,
+        ];
+        dut[14] = (xxhash32_reference(0, &dut[4..14]) >> 8) as u8;
+        assert_eq!(
+            parse_frame_descriptor(&dut),
+            Err(Error::LimitExceeded { offset: 6 })
+        );
+        let descriptor = parse_frame_descriptor_with_limits(&dut, &Limits::unlimited()).unwrap();
+        assert_eq!(descriptor.content_size, Some(1 << 60));
+
+        // the 4MiB block and 64KiB window do not fit in 1MiB
+        let limits = Limits {
+            max_output: u64::MAX,
+            max_memory: 1 << 20,
+            ..Limits::default()
+        };
+        assert_eq!(
+            parse_frame_descriptor_with_limits(&dut, &limits),
+            Err(Error::LimitExceeded { offset: 5 })
+        );
+        let limits = Limits {
+            max_window: 1 << 15,
+            ..Limits::unlimited()
+        };
+        assert_eq!(
+            parse_frame_descriptor_with_limits(&dut, &limits),
+            Err(Error::LimitExceeded { offset: 5 })
+        );
+
+        // a corrupt header is reported as such, not held to the limits
+        dut[14] ^= 1;
+        match parse_frame_descriptor(&dut) {
+            Err(Error::ChecksumMismatch { offset: 14, .. }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_truncation() {
+        let dut = [
+            0x04, 0x22, 0x4D, 0x18, 0x6C, 0x40, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x35,
+        ];
+        for length in 0..dut.len() {
+            assert_eq!(
+                parse_frame_descriptor(&dut[..length]),
+                Err(Error::Truncated { offset: length })
+            );
+        }
+        assert!(parse_frame_descriptor(&dut).is_ok());
+    }
+
+    /// `lz4 -BD` on a 68 byte line of text, the frame's only block
+    const BLOCK: [u8; 45] = [
+        0x6F, 0x68, 0x65, 0x6C, 0x6C, 0x6F, 0x20, 0x06, 0x00, 0x04, 0xF5, 0x0A, 0x2C, 0x20, 0x74,
+        0x68, 0x69, 0x73, 0x20, 0x69, 0x73, 0x20, 0x6C, 0x7A, 0x34, 0x20, 0x62, 0x6C, 0x6F, 0x63,
+        0x6B, 0x20, 0x64, 0x61, 0x74, 0x61, 0x2C, 0x31, 0x00, 0x50, 0x6C, 0x6C, 0x6F, 0x21, 0x0A,
+    ];
+    const TEXT: &[u8] = b"hello hello hello hello hello, this is lz4 block data, hello hello!\n";
+
+    #[test]
+    fn decompresses_block() {
+        let mut output = [0u8; 128];
+        assert_eq!(decompress_block(&BLOCK, &mut output, 0), Ok(TEXT.len()));
+        assert_eq!(&output[..TEXT.len()], TEXT);
+
+        // a block which exactly fills the output
+        let mut output = [0u8; 68];
+        assert_eq!(decompress_block(&BLOCK, &mut output, 0), Ok(68));
+        let mut output = [0u8; 67];
+        assert_eq!(
+            decompress_block(&BLOCK, &mut output, 0),
+            Err(Error::OutputFull { offset: 63 })
+        );
+    }
+
+    #[test]
+    fn references_history() {
+        // a match of 8 at offset 4, reaching back before `start`
+        let dut = [0x04, 0x04, 0x00, 0x00];
+        let mut output = *b"abcd\0\0\0\0\0\0\0\0";
+        assert_eq!(decompress_block(&dut[..], &mut output, 4), Ok(12));
+        assert_eq!(&output, b"abcdabcdabcd");
+
+        let mut output = [0u8; 12];
+        assert_eq!(
+            decompress_block(&dut[..], &mut output, 0),
+            Err(Error::CorruptBlock { offset: 1 })
+        );
+    }
+
+    #[test]
+    fn rejects_corrupt_block() {
+        let mut output = [0u8; 128];
+        // a zero offset
+        assert_eq!(
+            decompress_block(&[0x10, b'a', 0x00, 0x00, 0x00], &mut output, 0),
+            Err(Error::CorruptBlock { offset: 2 })
+        );
+        assert_eq!(
+            decompress_block(&BLOCK[..8], &mut output, 0),
+            Err(Error::Truncated { offset: 8 })
+        );
+        // a block cut just after some literals is still well formed, it
+        // only decodes to less, the frame's sizes and checksums catch that
+        for length in 0..BLOCK.len() {
+            assert_ne!(
+                decompress_block(&BLOCK[..length], &mut output, 0),
+                Ok(TEXT.len())
+            );
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use super::feature_macros::error::Error;
+use super::feature_macros::numbers::{Num, PrimativeNumber};
+
+/// the most a chunk of the framing format may decompress to
+pub const MAX_CHUNK_LENGTH: usize = 1 << 16;
+
+/// the most `length` bytes may compress to, so the largest block a
+/// decoder should accept for that much output
+#[inline]
+pub fn max_compressed_length(length: usize) -> usize {
+    32 + length + length / 6
+}
+
+/// the framing format stores checksums rotated and offset, so that a
+/// checksum of data which contains checksums is not degenerate
+#[inline]
+pub fn mask_checksum(crc: u32) -> u32 {
+    crc.rotate_right(15).wrapping_add(0xA282EAD8)
+}
+
+/// uncompressed_length reads the varint at the start of a raw snappy
+/// block, returning the decompressed length and the varint's width.
+pub fn uncompressed_length(input: &[u8]) -> Result<(usize, usize), Error> {
+    // the length is a `u32` so the varint is at most 5 bytes
+    let mut length = 0u64;
+    for position in 0..5 {
+        let byte = *input
+            .get(position)
+            .ok_or(Error::Truncated { offset: position })?;
+        length |= ((byte & 0x7F) as u64) << (7 * position);
+        if byte & 0x80 == 0 {
+            if length > <Num<u32> as PrimativeNumber>::max() as u64 {
+                return Err(Error::CorruptBlock { offset: position });
+            }
+            return Ok((length as usize, position + 1));
+        }
+    }
+    Err(Error::CorruptBlock { offset: 4 })
+}
+
+/// decompress decodes a raw snappy block into the start of `output`,
+/// returning the decompressed length.
+///
+/// `output` must hold at least `uncompressed_length`, otherwise this
+/// returns `Error::OutputFull` before decoding anything. The elements
+/// must produce exactly that length and use all of `input`.
+pub fn decompress(input: &[u8], output: &mut [u8]) -> Result<usize, Error> {
+    let (length, mut position) = uncompressed_length(input)?;
+    if output.len() < length {
+        return Err(Error::OutputFull { offset: 0 });
+    }
+    let output = &mut output[..length];
+    let mut written = 0usize;
+    while position < input.len() {
+        let tag = input[position];
+        let (width, count) = match tag & 0x03 {
+            0 => match tag >> 2 {
+                x @ 0..=59 => (0, (x as usize) + 1),
+                // 60..=63 store the length in the following 1 to 4 bytes
+                x => {
+                    let width = (x - 59) as usize;
+                    (width, read_le(input, position + 1, width)? as usize + 1)
+                }
+            },
+            1 => (1, 4 + ((tag >> 2) & 0x07) as usize),
+            2 => (2, ((tag >> 2) as usize) + 1),
+            _ => (4, ((tag >> 2) as usize) + 1),
+        };
+        if count > length - written {
+            return Err(Error::CorruptBlock { offset: position });
+        }
+
+        if tag & 0x03 == 0 {
+            let start = position + 1 + width;
+            if input.len() - start < count {
+                return Err(Error::Truncated {
+                    offset: input.len(),
+                });
+            }
+            output[written..written + count].copy_from_slice(&input[start..start + count]);
+            position = start + count;
+        } else {
+            let offset = read_le(input, position + 1, width)? as usize;
+            let offset = if tag & 0x03 == 1 {
+                ((tag as usize >> 5) << 8) | offset
+            } else {
+                offset
+            };
+            if offset == 0 || offset > written {
+                return Err(Error::CorruptBlock { offset: position });
+            }
+            if offset >= count {
+                let (history, rest) = output.split_at_mut(written);
+                rest[..count].copy_from_slice(&history[written - offset..written - offset + count]);
+            } else {
+                // the copy overlaps what it writes, so repeats a short run
+                for index in written..written + count {
+                    output[index] = output[index - offset];
+                }
+            }
+            position += 1 + width;
+        }
+        written += count;
+    }
+    if written != length {
+        return Err(Error::Truncated {
+            offset: input.len(),
+        });
+    }
+    Ok(length)
+}
+
+/// reads a little endian value of `width` bytes at `position`
+#[inline]
+fn read_le(input: &[u8], position: usize, width: usize) -> Result<u32, Error> {
+    match input.get(position..position + width) {
+        Option::Some(bytes) => Ok(bytes
+            .iter()
+            .rev()
+            .fold(0u32, |acc, byte| (acc << 8) | (*byte as u32))),
+        Option::None => Err(Error::Truncated {
+            offset: input.len(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::checksum::crc32c;
+    use super::super::feature_macros::error::Error;
+    use super::{decompress, mask_checksum, uncompressed_length};
+
+    #[test]
+    fn decompresses_elements() {
+        let mut output = [0u8; 512];
+
+        // a literal and an overlapping 1 byte offset copy
+        let dut = b"\x0c\x04ab\x19\x02";
+        assert_eq!(decompress(dut, &mut output), Ok(12));
+        assert_eq!(&output[..12], b"abababababab");
+
+        // a 1 byte extended literal, then 2 and 4 byte offset copies
+        let mut dut = [0u8; 115];
+        dut[..4].copy_from_slice(&[0xC8, 0x01, 0xF0, 99]);
+        for (index, byte) in dut[4..104].iter_mut().enumerate() {
+            *byte = (index % 26) as u8 + b'a';
+        }
+        dut[104..].copy_from_slice(&[0x62, 0x64, 0x00, 0x8B, 0x32, 0, 0, 0, 0x9E, 0x32, 0x00]);
+        assert_eq!(decompress(&dut, &mut output), Ok(200));
+        assert_eq!(&output[..100], &dut[4..104]);
+        assert_eq!(&output[100..125], &output[..25]);
+        assert_eq!(&output[125..160], &output[75..110]);
+        assert_eq!(&output[160..200], &output[110..150]);
+    }
+
+    #[test]
+    fn reads_length() {
+        assert_eq!(uncompressed_length(b"\x05"), Ok((5, 1)));
+        assert_eq!(
+            uncompressed_length(b"\xFF\xFF\xFF\xFF\x0F"),
+            Ok((u32::MAX as usize, 5))
+        );
+        assert_eq!(
+            uncompressed_length(b"\xFF\xFF\xFF\xFF\x10"),
+            Err(Error::CorruptBlock { offset: 4 })
+        );
+        assert_eq!(
+            uncompressed_length(b"\xFF\xFF\xFF\xFF\x80"),
+            Err(Error::CorruptBlock { offset: 4 })
+        );
+        assert_eq!(
+            uncompressed_length(b"\x80\x80"),
+            Err(Error::Truncated { offset: 2 })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        let mut output = [0u8; 16];
+        // too small an output
+        assert_eq!(
+            decompress(b"\x0c\x04ab\x19\x02", &mut output[..11]),
+            Err(Error::OutputFull { offset: 0 })
+        );
+        // a copy from before the start
+        assert_eq!(
+            decompress(b"\x0c\x04ab\x19\x03", &mut output),
+            Err(Error::CorruptBlock { offset: 4 })
+        );
+        // a zero offset
+        assert_eq!(
+            decompress(b"\x0c\x04ab\x19\x00", &mut output),
+            Err(Error::CorruptBlock { offset: 4 })
+        );
+        // more than the declared length
+        assert_eq!(
+            decompress(b"\x02\x10hello", &mut output),
+            Err(Error::CorruptBlock { offset: 1 })
+        );
+        // less than the declared length
+        assert_eq!(
+            decompress(b"\x06\x10hello", &mut output),
+            Err(Error::Truncated { offset: 7 })
+        );
+        assert_eq!(
+            decompress(b"\x05\x10hel", &mut output),
+            Err(Error::Truncated { offset: 5 })
+        );
+    }
+
+    #[test]
+    fn masks_checksum() {
+        assert_eq!(mask_checksum(crc32c(0, b"")), 0xA282EAD8);
+        assert_eq!(mask_checksum(1 << 15), 0xA282EAD9);
+    }
+}
@@ -0,0 +1,292 @@
+use super::feature_macros::endian::{view, Le};
+use super::feature_macros::error::Error;
+use super::feature_macros::limits::Limits;
+use super::feature_macros::numbers::PrimativeNumber;
+
+/// zstd frame magic number
+const MAGIC: u32 = 0xFD2FB528u32;
+
+/// the largest a block may decompress to, smaller windows limit it further
+const BLOCK_MAX_SIZE: u64 = 128 << 10;
+
+view! {
+    /// the fixed width start of a zstd frame
+    struct Prefix {
+        magic: Le<u32>,
+        descriptor: u8,
+    }
+}
+
+/// FrameHeader is the header of a zstd frame.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct FrameHeader {
+    /// the minimum buffer a decoder needs to hold back references
+    pub window_size: u64,
+    /// the decompressed size of the frame, when it was recorded
+    pub content_size: Option<u64>,
+    /// the dictionary the frame was compressed with, if any
+    pub dictionary_id: Option<u32>,
+    /// the frame ends with the low 32 bits of an XXH64 of its contents
+    pub content_checksum: bool,
+    /// the frame is decoded into a single buffer of `content_size`
+    pub single_segment: bool,
+    /// the length of the header (including the magic number) in bytes
+    pub header_length: usize,
+}
+
+/// parse_frame_header reads the header at the start of a zstd frame.
+///
+/// Skippable frames are not zstd frames, they are reported as
+/// `Error::BadMagic`. The header is checked against `Limits::default()`.
+#[inline]
+pub fn parse_frame_header(buffer: &[u8]) -> Result<FrameHeader, Error> {
+    parse_frame_header_with_limits(buffer, &Limits::default())
+}
+
+/// parse_frame_header_with_limits is `parse_frame_header`, which also
+/// checks the frame against `limits`.
+///
+/// The window must fit in `max_window`, the window and a block in
+/// `max_memory`, and a recorded content size in `max_output`, otherwise
+/// `Error::LimitExceeded` is returned at the field which claimed them.
+pub fn parse_frame_header_with_limits(
+    buffer: &[u8],
+    limits: &Limits,
+) -> Result<FrameHeader, Error> {
+    let prefix = view::<Prefix>(buffer)?;
+    if prefix.magic.get().inner() != MAGIC {
+        return Err(Error::BadMagic { offset: 0 });
+    }
+
+    let descriptor = prefix.descriptor;
+    if descriptor & 0b1000 != 0 {
+        return Err(Error::CorruptBlock { offset: 4 });
+    }
+    let single_segment = descriptor & 0b10_0000 != 0;
+    let content_checksum = descriptor & 0b100 != 0;
+
+    let mut position = Prefix::SIZE;
+    let window_size = if single_segment {
+        Option::None
+    } else {
+        let window_descriptor = read_width(buffer, position, 1)?;
+        position += 1;
+        let exponent = window_descriptor >> 3;
+        let mantissa = window_descriptor & 0b111;
+        let base = 1u64 << (10 + exponent);
+        Option::Some(base + (base / 8) * mantissa)
+    };
+
+    let dictionary_id = match descriptor & 0b11 {
+        0 => 0,
+        flag => {
+            let width = 1 << (flag - 1);
+            let value = read_width(buffer, position, width)?;
+            position += width;
+            value as u32
+        }
+    };
+
+    let content_position = position;
+    let content_size = match (descriptor >> 6, single_segment) {
+        (0, false) => Option::None,
+        (flag, _) => {
+            let width = 1 << flag;
+            let value = read_width(buffer, position, width)?;
+            position += width;
+            // the 2 byte form is offset, as 1 byte covers smaller sizes
+            Option::Some(if width == 2 { value + 256 } else { value })
+        }
+    };
+
+    // single segment frames always record their size, which is the window
+    let window_position = if single_segment {
+        content_position
+    } else {
+        Prefix::SIZE
+    };
+    let window_size = window_size.or(content_size).unwrap_or(0);
+    limits.check_window(window_size, window_position)?;
+    limits.check_memory(
+        window_size.saturating_add(window_size.min(BLOCK_MAX_SIZE)),
+        window_position,
+    )?;
+    if let Option::Some(size) = content_size {
+        limits.check_output(size, content_position)?;
+    }
+
+    Ok(FrameHeader {
+        window_size,
+        content_size,
+        dictionary_id: if dictionary_id == 0 {
+            Option::None
+        } else {
+            Option::Some(dictionary_id)
+        },
+        content_checksum,
+        single_segment,
+        header_length: position,
+    })
+}
+
+/// reads a little endian field of 1, 2, 4 or 8 bytes at `position`
+#[inline]
+fn read_width(buffer: &[u8], position: usize, width: usize) -> Result<u64, Error> {
+    let buffer = &buffer[position..];
+    let value = match width {
+        1 => view::<u8>(buffer).map(|value| *value as u64),
+        2 => view::<Le<u16>>(buffer).map(|value| value.get().inner() as u64),
+        4 => view::<Le<u32>>(buffer).map(|value| value.get().inner() as u64),
+        _ => {
+            debug_assert_eq!(width, 8);
+            view::<Le<u64>>(buffer).map(|value| value.get().inner())
+        }
+    };
+    value.map_err(|e| e.rebase(position))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::feature_macros::error::Error;
+    use super::super::feature_macros::limits::Limits;
+    use super::{parse_frame_header, parse_frame_header_with_limits, FrameHeader};
+
+    #[test]
+    fn parses_single_segment() {
+        // `zstd` on a 24 byte file
+        let dut = [0x28, 0xB5, 0x2F, 0xFD, 0x24, 0x18, 0x6D, 0x00];
+        assert_eq!(
+            parse_frame_header(&dut),
+            Ok(FrameHeader {
+                window_size: 24,
+                content_size: Some(24),
+                dictionary_id: None,
+                content_checksum: true,
+                single_segment: true,
+                header_length: 6,
+            })
+        );
+
+        // `zstd` on a 300000 byte file
+        let dut = [0x28, 0xB5, 0x2F, 0xFD, 0xA4, 0xE0, 0x93, 0x04, 0x00];
+        let header = parse_frame_header(&dut).unwrap();
+        assert_eq!(header.content_size, Some(300000));
+        assert_eq!(header.window_size, 300000);
+        assert_eq!(header.header_length, 9);
+    }
+
+    #[test]
+    fn parses_window_descriptor() {
+        // `zstd --no-check` reading a pipe
+        let dut = [0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x58, 0x19, 0x00];
+        assert_eq!(
+            parse_frame_header(&dut),
+            Ok(FrameHeader {
+                window_size: 2 << 20,
+                content_size: None,
+                dictionary_id: None,
+                content_checksum: false,
+                single_segment: false,
+                header_length: 6,
+            })
+        );
+
+        // mantissa adds eighths of the base, and the 2 byte size is offset
+        let dut = [0x28, 0xB5, 0x2F, 0xFD, 0x42, 0x03, 0x34, 0x12, 0x00, 0x01];
+        let header = parse_frame_header(&dut).unwrap();
+        assert_eq!(header.window_size, 1024 + 3 * 128);
+        assert_eq!(header.dictionary_id, Some(0x1234));
+        assert_eq!(header.content_size, Some(256 + 256));
+        assert_eq!(header.header_length, 10);
+    }
+
+    #[test]
+    fn parses_wide_fields() {
+        let dut = [
+            0x28, 0xB5, 0x2F, 0xFD, 0xE3, 0x78, 0x56, 0x34, 0x12, 0x08, 0x07, 0x06, 0x05, 0x04,
+            0x03, 0x02, 0x01,
+        ];
+        let header = parse_frame_header_with_limits(&dut, &Limits::unlimited()).unwrap();
+        assert!(header.single_segment);
+        assert_eq!(header.dictionary_id, Some(0x12345678));
+        assert_eq!(header.content_size, Some(0x0102030405060708));
+        assert_eq!(header.window_size, 0x0102030405060708);
+        assert_eq!(header.header_length, 17);
+
+        // a dictionary id of zero means there is none
+        let dut = [0x28, 0xB5, 0x2F, 0xFD, 0x21, 0x00, 0x05];
+        assert_eq!(parse_frame_header(&dut).unwrap().dictionary_id, None);
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        assert_eq!(
+            parse_frame_header(&[0x50, 0x2A, 0x4D, 0x18, 0x00, 0x00]),
+            Err(Error::BadMagic { offset: 0 })
+        );
+        assert_eq!(
+            parse_frame_header(&[0x28, 0xB5, 0x2F, 0xFD, 0x08, 0x58]),
+            Err(Error::CorruptBlock { offset: 4 })
+        );
+    }
+
+    #[test]
+    fn enforces_limits() {
+        // a 3.75TB window, the largest a descriptor can claim
+        let dut = [0x28, 0xB5, 0x2F, 0xFD, 0x00, 0xFF];
+        assert_eq!(
+            parse_frame_header(&dut),
+            Err(Error::LimitExceeded { offset: 5 })
+        );
+        let header = parse_frame_header_with_limits(&dut, &Limits::unlimited()).unwrap();
+        assert_eq!(header.window_size, (1 << 41) + 7 * (1 << 38));
+
+        // a single segment frame's window is its 2^60 byte content size
+        let dut = [
+            0x28, 0xB5, 0x2F, 0xFD, 0xE0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+        ];
+        assert_eq!(
+            parse_frame_header(&dut),
+            Err(Error::LimitExceeded { offset: 5 })
+        );
+        // a small window, but 2^60 bytes of content
+        let dut = [
+            0x28, 0xB5, 0x2F, 0xFD, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+        ];
+        assert_eq!(
+            parse_frame_header(&dut),
+            Err(Error::LimitExceeded { offset: 6 })
+        );
+
+        // a 2MiB window and its 128KiB block need more than 2MiB
+        let dut = [0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x58];
+        let limits = Limits {
+            max_memory: 2 << 20,
+            ..Limits::default()
+        };
+        assert_eq!(
+            parse_frame_header_with_limits(&dut, &limits),
+            Err(Error::LimitExceeded { offset: 5 })
+        );
+        let limits = Limits {
+            max_memory: (2 << 20) + (128 << 10),
+            ..Limits::default()
+        };
+        assert!(parse_frame_header_with_limits(&dut, &limits).is_ok());
+    }
+
+    #[test]
+    fn reports_truncation() {
+        let dut = [
+            0x28, 0xB5, 0x2F, 0xFD, 0x82, 0x58, 0x34, 0x12, 0x00, 0x00, 0x01, 0x00,
+        ];
+        assert!(parse_frame_header(&dut).is_ok());
+        for length in 0..dut.len() {
+            assert_eq!(
+                parse_frame_header(&dut[..length]),
+                Err(Error::Truncated { offset: length })
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::io::Read;
+
+use feature_macros::error::Error;
+use feature_macros::limits::Limits;
+use formats::AnyDecoder;
+
+/// a reader which returns at most `most` bytes at a time
+struct Trickle<'a> {
+    data: &'a [u8],
+    most: usize,
+}
+impl<'a> Read for Trickle<'a> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let count = buffer.len().min(self.data.len()).min(self.most);
+        buffer[..count].copy_from_slice(&self.data[..count]);
+        self.data = &self.data[count..];
+        Ok(count)
+    }
+}
+
+/// reads all of `decoder` through a buffer of `size` bytes
+fn read_all<R: Read>(mut decoder: AnyDecoder<R>, size: usize) -> (Vec<u8>, Option<Error>) {
+    let mut output = Vec::new();
+    let mut buffer = vec![0u8; size];
+    loop {
+        match decoder.read(&mut buffer) {
+            Ok(0) => return (output, None),
+            Ok(count) => output.extend_from_slice(&buffer[..count]),
+            Err(error) => {
+                let error = *error.get_ref().unwrap().downcast_ref::<Error>().unwrap();
+                // errors are sticky
+                assert!(decoder.read(&mut buffer).is_err());
+                return (output, Some(error));
+            }
+        }
+    }
+}
+
+// Decoding an arbitrary stream must never panic or exceed its limits,
+// and must not depend on how the reader splits the input. Output is
+// delivered before a trailing checksum is verified, so after an error
+// all that is required is that one output is a prefix of the other.
+fuzz_target!(|input: (u8, u8, &[u8])| {
+    let (most, size, data) = input;
+    let limits = Limits {
+        max_output: 1 << 20,
+        max_memory: 1 << 24,
+        ..Limits::default()
+    };
+    let (whole, error) = read_all(AnyDecoder::with_limits(data, &limits), 1 << 16);
+    let trickle = Trickle {
+        data,
+        most: most as usize + 1,
+    };
+    let (trickled, trickled_error) =
+        read_all(AnyDecoder::with_limits(trickle, &limits), size as usize + 1);
+    assert!(whole.len() as u64 <= limits.max_output);
+    assert!(trickled.len() as u64 <= limits.max_output);
+    match (error, trickled_error) {
+        (None, None) => assert_eq!(whole, trickled),
+        // where in the input an output limit is reached does depend on it
+        (Some(Error::LimitExceeded { .. }), Some(Error::LimitExceeded { .. })) => {}
+        (error, trickled_error) => assert_eq!(error, trickled_error),
+    }
+    let shorter = whole.len().min(trickled.len());
+    assert_eq!(whole[..shorter], trickled[..shorter]);
+});
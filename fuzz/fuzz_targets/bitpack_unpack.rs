@@ -0,0 +1,37 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use intcodec::bitpack::{pack, packed_len, unpack};
+use intcodec::{Integer, BLOCK_LEN};
+
+// Unpacking arbitrary words at any width must never panic or claim more
+// words than it was given. Every bit of a packed block is used, so
+// packing what was unpacked must give back exactly the words consumed.
+fn check<T: Integer>(input: &[T], bits: u32) {
+    let mut values = [T::default(); BLOCK_LEN];
+    let consumed = match unpack(input, bits, &mut values) {
+        Ok(consumed) => consumed,
+        Err(_) => {
+            assert!(bits > T::BITS || input.len() < packed_len::<T>(bits));
+            return;
+        }
+    };
+    assert_eq!(consumed, packed_len::<T>(bits));
+
+    // words past the block are left alone
+    let mut packed = vec![T::default(); BLOCK_LEN + 1];
+    let sentinel = input.first().cloned().unwrap_or_default();
+    for word in packed.iter_mut() {
+        *word = sentinel;
+    }
+    assert_eq!(pack(&values, bits, &mut packed), Ok(consumed));
+    assert_eq!(&packed[..consumed], &input[..consumed]);
+    assert!(packed[consumed..].iter().all(|word| *word == sentinel));
+}
+
+fuzz_target!(|input: (u8, Vec<u32>, Vec<u64>)| {
+    let (bits, words32, words64) = input;
+    let bits = bits as u32 % 66;
+    check(&words32, bits);
+    check(&words64, bits);
+});
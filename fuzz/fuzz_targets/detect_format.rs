@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+// Sniffing arbitrary input must never panic or read out of bounds.
+fuzz_target!(|data: &[u8]| {
+    let _ = formats::detect_format(data);
+});
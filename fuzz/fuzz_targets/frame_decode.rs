@@ -0,0 +1,31 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use intcodec::{frame, Integer, BLOCK_LEN};
+
+// Decoding arbitrary words must never panic or claim more words than it
+// was given, and whatever does decode must survive a round trip.
+fn check<T: Integer>(input: &[T], length: usize) {
+    let mut values = vec![T::default(); length];
+    let consumed = match frame::decode(input, &mut values) {
+        Ok(consumed) => consumed,
+        Err(_) => return,
+    };
+    assert!(consumed <= input.len());
+
+    let mut encoded = vec![T::default(); 2 * BLOCK_LEN];
+    let written = frame::encode(&values, &mut encoded).unwrap();
+    let mut decoded = vec![T::default(); length];
+    assert_eq!(
+        frame::decode(&encoded[..written], &mut decoded),
+        Ok(written)
+    );
+    assert_eq!(decoded, values);
+}
+
+fuzz_target!(|input: (u8, Vec<u32>, Vec<u64>)| {
+    let (length, words32, words64) = input;
+    let length = length as usize % (BLOCK_LEN + 1);
+    check(&words32, length);
+    check(&words64, length);
+});
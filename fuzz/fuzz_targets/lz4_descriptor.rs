@@ -0,0 +1,37 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use feature_macros::error::Error;
+use feature_macros::limits::Limits;
+use formats::lz4::{parse_frame_descriptor, parse_frame_descriptor_with_limits};
+
+// Parsing an arbitrary header must never panic or claim more bytes than
+// it was given, a descriptor which parses must be within the limits, and
+// tighter limits may only turn success into `LimitExceeded`.
+fuzz_target!(|input: (u16, &[u8])| {
+    let (memory, data) = input;
+    let descriptor = match parse_frame_descriptor_with_limits(data, &Limits::unlimited()) {
+        Ok(descriptor) => descriptor,
+        Err(error) => {
+            assert_eq!(parse_frame_descriptor(data), Err(error));
+            return;
+        }
+    };
+    assert!(descriptor.header_length <= data.len());
+    assert!(descriptor.header_length >= 7);
+
+    let limits = Limits {
+        max_output: 1 << 20,
+        max_memory: (memory as u64) << 8,
+        ..Limits::default()
+    };
+    match parse_frame_descriptor_with_limits(data, &limits) {
+        Ok(limited) => {
+            assert_eq!(limited, descriptor);
+            assert!(descriptor.content_size.unwrap_or(0) <= limits.max_output);
+            assert!((descriptor.block_max_size as u64) < limits.max_memory);
+        }
+        Err(Error::LimitExceeded { offset }) => assert!(offset < descriptor.header_length),
+        Err(error) => panic!("{:?} only with limits", error),
+    }
+});
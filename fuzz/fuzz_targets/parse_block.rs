@@ -0,0 +1,38 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use timeseries::{encode_block, parse_block};
+
+// Parsing arbitrary input must never panic or claim more bytes than it
+// was given, and a block which parses must survive a round trip.
+fuzz_target!(|data: &[u8]| {
+    let (block, length) = match parse_block(data) {
+        Ok(parsed) => parsed,
+        Err(_) => return,
+    };
+    assert!(length <= data.len());
+
+    let mut timestamps = Vec::new();
+    let mut values = Vec::new();
+    for point in block.points() {
+        match point {
+            Ok((timestamp, value)) => {
+                timestamps.push(timestamp);
+                values.push(value);
+            }
+            Err(_) => return,
+        }
+    }
+
+    // no point takes more than 20 bytes, the header and checksum 22
+    let mut output = vec![0u8; 22 + 20 * timestamps.len()];
+    let written = encode_block(&timestamps, &values, block.codec, &mut output).unwrap();
+    let (reparsed, length) = parse_block(&output[..written]).unwrap();
+    assert_eq!(length, written);
+    assert_eq!(reparsed.count, timestamps.len());
+    for (point, (timestamp, value)) in reparsed.points().zip(timestamps.iter().zip(values.iter())) {
+        let (decoded_timestamp, decoded_value) = point.unwrap();
+        assert_eq!(decoded_timestamp, *timestamp);
+        assert_eq!(decoded_value.to_bits(), value.to_bits());
+    }
+});
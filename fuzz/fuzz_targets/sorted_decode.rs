@@ -0,0 +1,33 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use intcodec::{sorted, Integer, BLOCK_LEN};
+
+// Decoding arbitrary words must never panic or claim more words than it
+// was given, and whatever does decode must survive a round trip.
+fn check<T: Integer>(input: &[T], length: usize) {
+    let mut values = vec![T::default(); length];
+    let consumed = match sorted::decode(input, &mut values) {
+        Ok(consumed) => consumed,
+        Err(_) => return,
+    };
+    assert!(consumed <= input.len());
+
+    let blocks = length / BLOCK_LEN + 1;
+    let mut encoded = vec![T::default(); blocks * 2 * BLOCK_LEN];
+    let written = sorted::encode(&values, &mut encoded).unwrap();
+    let mut decoded = vec![T::default(); length];
+    assert_eq!(
+        sorted::decode(&encoded[..written], &mut decoded),
+        Ok(written)
+    );
+    assert_eq!(decoded, values);
+}
+
+fuzz_target!(|input: (u16, Vec<u32>, Vec<u64>)| {
+    let (length, words32, words64) = input;
+    // a few blocks, so the deltas carried between them are covered
+    let length = length as usize % (4 * BLOCK_LEN + 1);
+    check(&words32, length);
+    check(&words64, length);
+});
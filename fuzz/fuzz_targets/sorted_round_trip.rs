@@ -0,0 +1,28 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use intcodec::{sorted, Integer, BLOCK_LEN};
+
+// Any list must round trip, sorted or not, and consume exactly what was
+// written. Sorted lists are what the format is for, so both are tried.
+fn check<T: Integer>(values: &mut [T]) {
+    for _ in 0..2 {
+        // no block is larger than its header and its values unpacked
+        let blocks = (values.len() + BLOCK_LEN - 1) / BLOCK_LEN;
+        let mut encoded = vec![T::default(); blocks * (1 + BLOCK_LEN)];
+        let written = sorted::encode(values, &mut encoded).unwrap();
+        let mut decoded = vec![T::default(); values.len()];
+        assert_eq!(
+            sorted::decode(&encoded[..written], &mut decoded),
+            Ok(written)
+        );
+        assert_eq!(&decoded[..], &values[..]);
+        values.sort();
+    }
+}
+
+fuzz_target!(|input: (Vec<u32>, Vec<u64>)| {
+    let (mut values32, mut values64) = input;
+    check(&mut values32);
+    check(&mut values64);
+});
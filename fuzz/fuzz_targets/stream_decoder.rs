@@ -0,0 +1,43 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use timeseries::{Decoder, Encoder, ValueCodec};
+
+// Decoding an arbitrary stream must never panic, must yield at most
+// `count` points and nothing after an error, and whatever does decode
+// must survive a round trip.
+fuzz_target!(|input: (u8, u16, &[u8])| {
+    let (id, count, data) = input;
+    let codec = match ValueCodec::from_id(id) {
+        Some(codec) => codec,
+        None => return,
+    };
+    let count = count as usize;
+
+    let mut points = Vec::new();
+    let mut decoder = Decoder::new(data, codec, count);
+    for point in &mut decoder {
+        match point {
+            Ok(point) => points.push(point),
+            Err(_) => break,
+        }
+    }
+    assert!(points.len() <= count);
+    assert!(decoder.next().is_none());
+
+    // no point takes more than 20 bytes
+    let mut output = vec![0u8; 20 * points.len()];
+    let length = {
+        let mut encoder = Encoder::new(&mut output, codec);
+        for (timestamp, value) in points.iter() {
+            encoder.push(*timestamp, *value).unwrap();
+        }
+        encoder.finish()
+    };
+    let decoder = Decoder::new(&output[..length], codec, points.len());
+    for (point, (timestamp, value)) in decoder.zip(points.iter()) {
+        let (decoded_timestamp, decoded_value) = point.unwrap();
+        assert_eq!(decoded_timestamp, *timestamp);
+        assert_eq!(decoded_value.to_bits(), value.to_bits());
+    }
+});
@@ -0,0 +1,43 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use timeseries::{encode_block, parse_block, Decoder, Encoder, ValueCodec};
+
+// Any points must round trip through both codecs, bit for bit, as a
+// bare stream and as a block.
+fuzz_target!(|points: Vec<(i64, u64)>| {
+    let timestamps: Vec<i64> = points.iter().map(|point| point.0).collect();
+    let values: Vec<f64> = points.iter().map(|point| f64::from_bits(point.1)).collect();
+    for codec in [ValueCodec::Gorilla, ValueCodec::Chimp].iter() {
+        // no point takes more than 19 bytes, a block's header and
+        // checksum another 18
+        let mut output = vec![0u8; 18 + 19 * points.len()];
+        let length = {
+            let mut encoder = Encoder::new(&mut output, *codec);
+            for (timestamp, value) in timestamps.iter().zip(values.iter()) {
+                encoder.push(*timestamp, *value).unwrap();
+            }
+            assert_eq!(encoder.count(), points.len());
+            encoder.finish()
+        };
+        let decoder = Decoder::new(&output[..length], *codec, points.len());
+        let mut count = 0;
+        for (point, expected) in decoder.zip(points.iter()) {
+            let (timestamp, value) = point.unwrap();
+            assert_eq!(timestamp, expected.0);
+            assert_eq!(value.to_bits(), expected.1);
+            count += 1;
+        }
+        assert_eq!(count, points.len());
+
+        let written = encode_block(&timestamps, &values, *codec, &mut output).unwrap();
+        let (block, consumed) = parse_block(&output[..written]).unwrap();
+        assert_eq!(consumed, written);
+        assert_eq!(block.count, points.len());
+        for (point, expected) in block.points().zip(points.iter()) {
+            let (timestamp, value) = point.unwrap();
+            assert_eq!(timestamp, expected.0);
+            assert_eq!(value.to_bits(), expected.1);
+        }
+    }
+});
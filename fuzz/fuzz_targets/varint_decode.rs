@@ -0,0 +1,120 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use feature_macros::error::Error;
+use feature_macros::limits::Limits;
+use feature_macros::numbers::Num;
+use feature_macros::varint::{
+    decode_leb128, decode_leb128_length, decode_prefix, decode_prefix_length, decode_sleb128,
+    encode_leb128, encode_prefix, encode_sleb128, stream_vbyte_decode, stream_vbyte_encode,
+    SignedVarint, Varint,
+};
+
+// Decoding arbitrary bytes must never panic or claim more bytes than it
+// was given, and whatever decodes must re-encode (minimally, so in no
+// more bytes) to the same value.
+fn check<N: Varint>(data: &[u8]) {
+    let mut buffer = [0u8; 24];
+    if let Ok((value, length)) = decode_leb128::<N>(data) {
+        assert!(length <= data.len());
+        let written = encode_leb128(value, &mut buffer).unwrap();
+        assert!(written <= length);
+        let (decoded, read) = decode_leb128::<N>(&buffer[..written]).unwrap();
+        assert!(decoded == value && read == written);
+    }
+    if let Ok((value, length)) = decode_prefix::<N>(data) {
+        assert!(length <= data.len());
+        let written = encode_prefix(value, &mut buffer).unwrap();
+        assert!(written <= length);
+        let (decoded, read) = decode_prefix::<N>(&buffer[..written]).unwrap();
+        assert!(decoded == value && read == written);
+    }
+}
+
+fn check_signed<S: SignedVarint>(data: &[u8]) {
+    check::<S>(data);
+    let mut buffer = [0u8; 24];
+    if let Ok((value, length)) = decode_sleb128::<S>(data) {
+        assert!(length <= data.len());
+        let written = encode_sleb128(value, &mut buffer).unwrap();
+        assert!(written <= length);
+        let (decoded, read) = decode_sleb128::<S>(&buffer[..written]).unwrap();
+        assert!(decoded == value && read == written);
+    }
+}
+
+/// Stream VByte decoded one value at a time, what the dispatched
+/// (SSSE3 where the CPU has it) decoder must agree with
+fn stream_vbyte_reference(input: &[u8], output: &mut [u32]) -> Result<usize, Error> {
+    let control_length = (output.len() + 3) / 4;
+    if input.len() < control_length {
+        return Err(Error::Truncated {
+            offset: input.len(),
+        });
+    }
+    let mut position = control_length;
+    for (index, value) in output.iter_mut().enumerate() {
+        let width = ((input[index / 4] >> ((index % 4) * 2)) & 3) as usize + 1;
+        let bytes = input
+            .get(position..position + width)
+            .ok_or(Error::Truncated {
+                offset: input.len(),
+            })?;
+        *value = bytes
+            .iter()
+            .rev()
+            .fold(0u32, |acc, byte| (acc << 8) | (*byte as u32));
+        position += width;
+    }
+    Ok(position)
+}
+
+fuzz_target!(|input: (u16, &[u8])| {
+    let (count, data) = input;
+    check::<Num<u8>>(data);
+    check::<Num<u16>>(data);
+    check::<Num<u32>>(data);
+    check::<Num<u64>>(data);
+    check::<Num<u128>>(data);
+    check_signed::<Num<i8>>(data);
+    check_signed::<Num<i16>>(data);
+    check_signed::<Num<i32>>(data);
+    check_signed::<Num<i64>>(data);
+    check_signed::<Num<i128>>(data);
+
+    // a length must be within the limits, whatever its element size
+    let limits = Limits {
+        max_output: 1 << 16,
+        ..Limits::default()
+    };
+    let size = count as usize % 17;
+    for result in [
+        decode_leb128_length(data, size, &limits),
+        decode_prefix_length(data, size, &limits),
+    ]
+    .iter()
+    {
+        if let Ok((length, read)) = *result {
+            assert!(read <= data.len());
+            assert!((length * size) as u64 <= limits.max_output);
+        }
+    }
+
+    let count = count as usize % 1024;
+    let mut values = vec![0u32; count];
+    let mut expected = vec![0u32; count];
+    let result = stream_vbyte_decode(data, &mut values);
+    assert_eq!(result, stream_vbyte_reference(data, &mut expected));
+    if let Ok(length) = result {
+        assert!(length <= data.len());
+        assert_eq!(values, expected);
+        let mut encoded = vec![0u8; length];
+        let written = stream_vbyte_encode(&values, &mut encoded).unwrap();
+        let mut decoded = vec![0u32; count];
+        assert_eq!(
+            stream_vbyte_decode(&encoded[..written], &mut decoded),
+            Ok(written)
+        );
+        assert_eq!(decoded, values);
+    }
+});
@@ -0,0 +1,40 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use feature_macros::numbers::Num;
+use feature_macros::varint::{
+    decode_leb128, decode_prefix, decode_sleb128, encode_leb128, encode_prefix, encode_sleb128,
+    leb128_len, prefix_len, sleb128_len, stream_vbyte_decode, stream_vbyte_encode,
+    stream_vbyte_len, zigzag_decode, zigzag_encode,
+};
+
+// Every value must round trip through every encoding, in exactly the
+// number of bytes the `*_len` functions report.
+fuzz_target!(|input: (u64, i64, Vec<u32>)| {
+    let (unsigned, signed, values) = input;
+    let mut buffer = [0u8; 16];
+
+    let value = Num::from(unsigned);
+    let length = encode_leb128(value, &mut buffer).unwrap();
+    assert_eq!(length, leb128_len(value));
+    let (decoded, read) = decode_leb128::<Num<u64>>(&buffer[..length]).unwrap();
+    assert!(decoded == value && read == length);
+    let length = encode_prefix(value, &mut buffer).unwrap();
+    assert_eq!(length, prefix_len(value));
+    let (decoded, read) = decode_prefix::<Num<u64>>(&buffer[..length]).unwrap();
+    assert!(decoded == value && read == length);
+
+    let value = Num::from(signed);
+    let length = encode_sleb128(value, &mut buffer).unwrap();
+    assert_eq!(length, sleb128_len(value));
+    let (decoded, read) = decode_sleb128::<Num<i64>>(&buffer[..length]).unwrap();
+    assert!(decoded == value && read == length);
+    assert!(zigzag_decode::<Num<i64>>(zigzag_encode(value)) == value);
+
+    let mut encoded = vec![0u8; stream_vbyte_len(&values)];
+    let length = stream_vbyte_encode(&values, &mut encoded).unwrap();
+    assert_eq!(length, encoded.len());
+    let mut decoded = vec![0u32; values.len()];
+    assert_eq!(stream_vbyte_decode(&encoded, &mut decoded), Ok(length));
+    assert_eq!(decoded, values);
+});
@@ -0,0 +1,30 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::hash::Hasher;
+use xxhash::bits32::{xxhash32_reference, XXHash32};
+use xxhash::bits64::{xxhash64_reference, XXHash64};
+
+// The streaming hashers must produce the same result as the one shot
+// reference functions no matter how the input is split across writes.
+fuzz_target!(|input: (u64, Vec<u16>, &[u8])| {
+    let (seed, splits, data) = input;
+
+    let mut hasher32 = XXHash32::with_seed(seed as u32);
+    let mut hasher64 = XXHash64::with_seed(seed);
+    let mut remaining = data;
+    for split in splits {
+        let (head, tail) = remaining.split_at((split as usize).min(remaining.len()));
+        hasher32.write(head);
+        hasher64.write(head);
+        remaining = tail;
+    }
+    hasher32.write(remaining);
+    hasher64.write(remaining);
+
+    assert_eq!(
+        hasher32.finish() as u32,
+        xxhash32_reference(seed as u32, data)
+    );
+    assert_eq!(hasher64.finish(), xxhash64_reference(seed, data));
+});
@@ -0,0 +1,37 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use feature_macros::error::Error;
+use feature_macros::limits::Limits;
+use formats::zstd::{parse_frame_header, parse_frame_header_with_limits};
+
+// Parsing an arbitrary header must never panic or claim more bytes than
+// it was given, a header which parses must be within the limits, and
+// tighter limits may only turn success into `LimitExceeded`.
+fuzz_target!(|input: (u8, &[u8])| {
+    let (window_log, data) = input;
+    let header = match parse_frame_header_with_limits(data, &Limits::unlimited()) {
+        Ok(header) => header,
+        Err(error) => {
+            assert_eq!(parse_frame_header(data), Err(error));
+            return;
+        }
+    };
+    assert!(header.header_length <= data.len());
+    assert!(header.header_length >= 5);
+
+    let limits = Limits {
+        max_output: 1 << 20,
+        max_window: 1u64 << (window_log % 42),
+        ..Limits::default()
+    };
+    match parse_frame_header_with_limits(data, &limits) {
+        Ok(limited) => {
+            assert_eq!(limited, header);
+            assert!(header.window_size <= limits.max_window);
+            assert!(header.content_size.unwrap_or(0) <= limits.max_output);
+        }
+        Err(Error::LimitExceeded { offset }) => assert!(offset < header.header_length),
+        Err(error) => panic!("{:?} only with limits", error),
+    }
+});
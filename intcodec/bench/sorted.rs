@@ -0,0 +1,40 @@
+#[macro_use]
+extern crate criterion;
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+extern crate intcodec;
+use intcodec::sorted::{decode, encode};
+
+/// ascending IDs whose gaps need about `bits`
+fn ids(count: usize, bits: u32) -> Vec<u64> {
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut id = 0u64;
+    (0..count)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            id += state >> (64 - bits);
+            id
+        })
+        .collect()
+}
+
+fn bench_sorted(c: &mut Criterion) {
+    let mut group = c.benchmark_group("intcodec_sorted_u64_gap_bits");
+    let count = 64 * 1024;
+    group.throughput(Throughput::Elements(count as u64));
+    for bits in [4, 12, 24u32].iter() {
+        let values = ids(count, *bits);
+        let mut encoded = vec![0u64; count + count / 64];
+        let length = encode(&values, &mut encoded).unwrap();
+        encoded.truncate(length);
+        group.bench_with_input(BenchmarkId::from_parameter(bits), &encoded, |b, input| {
+            let mut output = vec![0u64; count];
+            b.iter(|| {
+                let _ = black_box(decode(input, &mut output));
+            });
+        });
+    }
+}
+criterion_group!(benches, bench_sorted);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+#[macro_use]
+extern crate criterion;
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+extern crate intcodec;
+use intcodec::bitpack::{pack, unpack};
+use intcodec::{frame, pfor, BLOCK_LEN};
+
+/// a block of values needing `bits`, from a fixed xorshift sequence
+fn block(bits: u32) -> [u32; BLOCK_LEN] {
+    let mut output = [0u32; BLOCK_LEN];
+    let mut state = 0x9E37_79B9u32;
+    for value in output.iter_mut() {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        *value = if bits == 0 { 0 } else { state >> (32 - bits) };
+    }
+    output
+}
+
+fn bench_unpack(c: &mut Criterion) {
+    let mut group = c.benchmark_group("intcodec_unpack_u32_bits");
+    group.throughput(Throughput::Elements(BLOCK_LEN as u64));
+    for bits in [1, 4, 8, 13, 20, 32u32].iter() {
+        let mut packed = [0u32; BLOCK_LEN];
+        let length = pack(&block(*bits), *bits, &mut packed).unwrap();
+        let input: &[u32] = &packed[..length];
+        group.bench_with_input(BenchmarkId::from_parameter(bits), &input, |b, input| {
+            let mut output = [0u32; BLOCK_LEN];
+            b.iter(|| {
+                let _ = black_box(unpack(input, *bits, &mut output));
+            });
+        });
+    }
+}
+
+fn bench_frame_of_reference(c: &mut Criterion) {
+    let mut group = c.benchmark_group("intcodec_decode_u32_codec");
+    group.throughput(Throughput::Elements(BLOCK_LEN as u64));
+
+    // offsets of 12 bits from a large reference
+    let mut values = block(12);
+    for value in values.iter_mut() {
+        *value += 0x4000_0000;
+    }
+    let mut encoded = [0u32; 2 + BLOCK_LEN];
+    let length = frame::encode(&values, &mut encoded).unwrap();
+    let input: &[u32] = &encoded[..length];
+    group.bench_with_input(BenchmarkId::new("frame", 12), &input, |b, input| {
+        let mut output = [0u32; BLOCK_LEN];
+        b.iter(|| {
+            let _ = black_box(frame::decode(input, &mut output));
+        });
+    });
+
+    // 8 bit values with an outlier every 16
+    let mut values = block(8);
+    for value in values.iter_mut().step_by(16) {
+        *value |= 1 << 28;
+    }
+    let mut encoded = [0u32; 1 + 2 * BLOCK_LEN];
+    let length = pfor::encode(&values, &mut encoded).unwrap();
+    let input: &[u32] = &encoded[..length];
+    group.bench_with_input(BenchmarkId::new("pfor", 8), &input, |b, input| {
+        let mut output = [0u32; BLOCK_LEN];
+        b.iter(|| {
+            let _ = black_box(pfor::decode(input, &mut output));
+        });
+    });
+}
+criterion_group!(benches, bench_unpack, bench_frame_of_reference);
+criterion_main!(benches);
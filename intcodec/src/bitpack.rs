@@ -0,0 +1,678 @@
+#[cfg(not(feature = "std"))]
+use core::fmt::Debug;
+#[cfg(not(feature = "std"))]
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use std::fmt::Debug;
+#[cfg(feature = "std")]
+use std::hash::Hash;
+
+#[cfg(all(not(feature = "std"), target_arch = "x86"))]
+use core::arch::x86 as arch;
+#[cfg(all(not(feature = "std"), target_arch = "x86_64"))]
+use core::arch::x86_64 as arch;
+#[cfg(all(feature = "std", target_arch = "x86"))]
+use std::arch::x86 as arch;
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+use std::arch::x86_64 as arch;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use super::feature_macros::cpu::{has, Feature};
+use super::feature_macros::error::Error;
+use super::feature_macros::mem::{size_of, size_of_val};
+use super::feature_macros::numbers::{Num, PrimativeNumber};
+use super::feature_macros::varint::Varint;
+
+/// number of values in a block
+pub const BLOCK_LEN: usize = 128;
+
+/// width of the vectors the layout is designed around
+const VECTOR_BITS: u32 = 128;
+
+/// Integer is implemented for the unsigned types which may be packed.
+///
+/// Blocks use a vertical layout: value `i` belongs to lane
+/// `i % lanes`, and each lane is packed into every `lanes`th word, with
+/// `lanes` chosen so a row of words fills a 128 bit vector. Every lane
+/// then shifts by the same amount, so unpacking a row is a handful of
+/// vector instructions, and each lane's bits fill whole words.
+///
+/// On x86 `pack` and `unpack` use SSE2 kernels, or AVX2 kernels which
+/// handle two rows at once, selected at runtime with `cpu::has`.
+pub trait Integer: Copy + Default + PartialEq + Eq + PartialOrd + Ord + Hash + Debug {
+    /// `Num<Self>`, which the transforms do their arithmetic in
+    type Num: Varint<Unsigned = Self::Num> + PrimativeNumber<Primative = Self>;
+
+    /// width in bits
+    const BITS: u32;
+
+    /// packs the low `bits` of each of `values` into exactly the first
+    /// `packed_len(bits)` words of `output`, leaving any after untouched.
+    fn pack(values: &[Self; BLOCK_LEN], bits: u32, output: &mut [Self]);
+
+    /// inverse of `pack`, `input` must hold `packed_len(bits)` words
+    fn unpack(input: &[Self], bits: u32, output: &mut [Self; BLOCK_LEN]);
+}
+
+/// number of words a block packed at `bits` takes
+#[inline(always)]
+pub fn packed_len<T: Integer>(bits: u32) -> usize {
+    bits as usize * BLOCK_LEN / T::BITS as usize
+}
+
+/// number of bits the largest of `values` needs
+#[inline]
+pub fn bits_needed<T: Integer>(values: &[T]) -> u32 {
+    let any = values
+        .iter()
+        .fold(T::Num::default(), |acc, value| acc | T::Num::from(*value));
+    T::BITS - any.leading_zeros()
+}
+
+/// pack writes a block of values at `bits` each, returning the number of
+/// words written.
+///
+/// Only the low `bits` of each value are stored. Returns
+/// `Error::OutputFull` when `output` cannot hold the block, and
+/// `Error::LimitExceeded` when `bits` is wider than `T`.
+#[inline]
+pub fn pack<T: Integer>(
+    values: &[T; BLOCK_LEN],
+    bits: u32,
+    output: &mut [T],
+) -> Result<usize, Error> {
+    if bits > T::BITS {
+        return Err(Error::LimitExceeded { offset: 0 });
+    }
+    let length = packed_len::<T>(bits);
+    if output.len() < length {
+        return Err(Error::OutputFull { offset: 0 });
+    }
+    T::pack(values, bits, &mut output[..length]);
+    Ok(length)
+}
+
+/// unpack reads a block written by `pack`, returning the number of words
+/// consumed.
+///
+/// Error offsets are in bytes, as if the words were stored little endian.
+#[inline]
+pub fn unpack<T: Integer>(
+    input: &[T],
+    bits: u32,
+    output: &mut [T; BLOCK_LEN],
+) -> Result<usize, Error> {
+    if bits > T::BITS {
+        return Err(Error::CorruptBlock { offset: 0 });
+    }
+    let length = packed_len::<T>(bits);
+    if input.len() < length {
+        return Err(Error::Truncated {
+            offset: size_of_val(input),
+        });
+    }
+    T::unpack(&input[..length], bits, output);
+    Ok(length)
+}
+
+/// portable form of `Integer::pack`, and the reference the kernels are
+/// tested against
+#[inline]
+fn pack_rows<T: Integer>(values: &[T; BLOCK_LEN], bits: u32, output: &mut [T]) {
+    let width = T::BITS;
+    let lanes = (VECTOR_BITS / width) as usize;
+    for word in output[..packed_len::<T>(bits)].iter_mut() {
+        *word = T::default();
+    }
+    if bits == 0 {
+        return;
+    }
+    let mask = !T::Num::default() >> (width - bits);
+    for (row, chunk) in values.chunks(lanes).enumerate() {
+        let offset = row as u32 * bits;
+        let start = (offset / width) as usize * lanes;
+        let shift = offset % width;
+        let (words, next) = output[start..].split_at_mut(lanes);
+        for (word, value) in words.iter_mut().zip(chunk.iter()) {
+            *word = (T::Num::from(*word) | ((T::Num::from(*value) & mask) << shift)).inner();
+        }
+        // the values straddle two words
+        if shift + bits > width {
+            for (word, value) in next[..lanes].iter_mut().zip(chunk.iter()) {
+                let high = (T::Num::from(*value) & mask) >> (width - shift);
+                *word = (T::Num::from(*word) | high).inner();
+            }
+        }
+    }
+}
+
+/// portable form of `Integer::unpack`, see `pack_rows`
+#[inline]
+fn unpack_rows<T: Integer>(input: &[T], bits: u32, output: &mut [T; BLOCK_LEN]) {
+    let width = T::BITS;
+    let lanes = (VECTOR_BITS / width) as usize;
+    if bits == 0 {
+        for value in output.iter_mut() {
+            *value = T::default();
+        }
+        return;
+    }
+    let mask = !T::Num::default() >> (width - bits);
+    for (row, chunk) in output.chunks_mut(lanes).enumerate() {
+        let offset = row as u32 * bits;
+        let start = (offset / width) as usize * lanes;
+        let shift = offset % width;
+        let words = &input[start..start + lanes];
+        if shift + bits > width {
+            let next = &input[start + lanes..start + 2 * lanes];
+            for ((value, word), high) in chunk.iter_mut().zip(words.iter()).zip(next.iter()) {
+                let low = T::Num::from(*word) >> shift;
+                let high = T::Num::from(*high) << (width - shift);
+                *value = ((low | high) & mask).inner();
+            }
+        } else {
+            for (value, word) in chunk.iter_mut().zip(words.iter()) {
+                *value = ((T::Num::from(*word) >> shift) & mask).inner();
+            }
+        }
+    }
+}
+
+/// SSE2 and AVX2 forms of `pack_rows` and `unpack_rows`.
+///
+/// The shift changes from row to row, so the shifts take their count
+/// from a register (`_mm_srl_epi32`) or a vector of counts
+/// (`_mm256_srlv_epi32`). A count of the full width yields zero, so the
+/// second word of a row which does not straddle contributes nothing.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+macro_rules! x86_kernels {
+    (
+        $kind: ident, $pack_sse2: ident, $unpack_sse2: ident, $pack_avx2: ident,
+        $unpack_avx2: ident, $set1: ident, $srl: ident, $sll: ident, $srlv: ident,
+        $sllv: ident, $counts: ident
+    ) => {
+        /// SSE2 form of `pack_rows`, one row at a time
+        #[target_feature(enable = "sse2")]
+        unsafe fn $pack_sse2(values: &[$kind; BLOCK_LEN], bits: u32, output: &mut [$kind]) {
+            const WIDTH: u32 = <$kind as Integer>::BITS;
+            const LANES: usize = (VECTOR_BITS / WIDTH) as usize;
+            for word in output[..packed_len::<$kind>(bits)].iter_mut() {
+                *word = 0;
+            }
+            if bits == 0 {
+                return;
+            }
+            let mask = arch::$set1(($kind::MAX >> (WIDTH - bits)) as _);
+            let words = output.as_mut_ptr();
+            for row in 0..BLOCK_LEN / LANES {
+                let offset = row as u32 * bits;
+                let start = (offset / WIDTH) as usize * LANES;
+                let shift = offset % WIDTH;
+                debug_assert!(start + LANES <= output.len());
+                let value = arch::_mm_and_si128(
+                    arch::_mm_loadu_si128(values.as_ptr().add(row * LANES) as *const arch::__m128i),
+                    mask,
+                );
+                let low = arch::$sll(value, arch::_mm_cvtsi32_si128(shift as i32));
+                or_into(words.add(start), low);
+                // the values straddle two words
+                if shift + bits > WIDTH {
+                    debug_assert!(start + 2 * LANES <= output.len());
+                    let high = arch::$srl(value, arch::_mm_cvtsi32_si128((WIDTH - shift) as i32));
+                    or_into(words.add(start + LANES), high);
+                }
+            }
+        }
+
+        /// SSE2 form of `unpack_rows`, one row at a time
+        #[target_feature(enable = "sse2")]
+        unsafe fn $unpack_sse2(input: &[$kind], bits: u32, output: &mut [$kind; BLOCK_LEN]) {
+            const WIDTH: u32 = <$kind as Integer>::BITS;
+            const LANES: usize = (VECTOR_BITS / WIDTH) as usize;
+            if bits == 0 {
+                for value in output.iter_mut() {
+                    *value = 0;
+                }
+                return;
+            }
+            let mask = arch::$set1(($kind::MAX >> (WIDTH - bits)) as _);
+            for row in 0..BLOCK_LEN / LANES {
+                let offset = row as u32 * bits;
+                let start = (offset / WIDTH) as usize * LANES;
+                let shift = offset % WIDTH;
+                debug_assert!(start + LANES <= input.len());
+                let words = input.as_ptr();
+                let mut value = arch::$srl(
+                    load(words.add(start)),
+                    arch::_mm_cvtsi32_si128(shift as i32),
+                );
+                // the values straddle two words
+                if shift + bits > WIDTH {
+                    debug_assert!(start + 2 * LANES <= input.len());
+                    let next = load(words.add(start + LANES));
+                    let high = arch::$sll(next, arch::_mm_cvtsi32_si128((WIDTH - shift) as i32));
+                    value = arch::_mm_or_si128(value, high);
+                }
+                arch::_mm_storeu_si128(
+                    output.as_mut_ptr().add(row * LANES) as *mut arch::__m128i,
+                    arch::_mm_and_si128(value, mask),
+                );
+            }
+        }
+
+        /// AVX2 form of `pack_rows`, two rows at a time with a shift
+        /// count per row
+        #[target_feature(enable = "avx2")]
+        unsafe fn $pack_avx2(values: &[$kind; BLOCK_LEN], bits: u32, output: &mut [$kind]) {
+            const WIDTH: u32 = <$kind as Integer>::BITS;
+            const LANES: usize = (VECTOR_BITS / WIDTH) as usize;
+            for word in output[..packed_len::<$kind>(bits)].iter_mut() {
+                *word = 0;
+            }
+            if bits == 0 {
+                return;
+            }
+            let mask =
+                arch::_mm256_broadcastsi128_si256(arch::$set1(($kind::MAX >> (WIDTH - bits)) as _));
+            let words = output.as_mut_ptr();
+            for pair in 0..BLOCK_LEN / LANES / 2 {
+                let row = pair * 2;
+                let offset = [row as u32 * bits, (row as u32 + 1) * bits];
+                let start = [
+                    (offset[0] / WIDTH) as usize * LANES,
+                    (offset[1] / WIDTH) as usize * LANES,
+                ];
+                let shift = [offset[0] % WIDTH, offset[1] % WIDTH];
+                debug_assert!(start[1] + 2 * LANES <= output.len() || shift[1] + bits <= WIDTH);
+                let value = arch::_mm256_and_si256(
+                    arch::_mm256_loadu_si256(
+                        values.as_ptr().add(row * LANES) as *const arch::__m256i
+                    ),
+                    mask,
+                );
+                let low = arch::$sllv(value, $counts(shift[0], shift[1]));
+                // a row which does not straddle shifts its values out
+                let high = arch::$srlv(value, $counts(WIDTH - shift[0], WIDTH - shift[1]));
+                or_into(words.add(start[0]), arch::_mm256_castsi256_si128(low));
+                or_into(words.add(start[1]), arch::_mm256_extracti128_si256(low, 1));
+                if shift[0] + bits > WIDTH {
+                    or_into(
+                        words.add(start[0] + LANES),
+                        arch::_mm256_castsi256_si128(high),
+                    );
+                }
+                if shift[1] + bits > WIDTH {
+                    or_into(
+                        words.add(start[1] + LANES),
+                        arch::_mm256_extracti128_si256(high, 1),
+                    );
+                }
+            }
+        }
+
+        /// AVX2 form of `unpack_rows`, two rows at a time with a shift
+        /// count per row
+        #[target_feature(enable = "avx2")]
+        unsafe fn $unpack_avx2(input: &[$kind], bits: u32, output: &mut [$kind; BLOCK_LEN]) {
+            const WIDTH: u32 = <$kind as Integer>::BITS;
+            const LANES: usize = (VECTOR_BITS / WIDTH) as usize;
+            if bits == 0 {
+                for value in output.iter_mut() {
+                    *value = 0;
+                }
+                return;
+            }
+            let mask =
+                arch::_mm256_broadcastsi128_si256(arch::$set1(($kind::MAX >> (WIDTH - bits)) as _));
+            let words = input.as_ptr();
+            for pair in 0..BLOCK_LEN / LANES / 2 {
+                let row = pair * 2;
+                let offset = [row as u32 * bits, (row as u32 + 1) * bits];
+                let start = [
+                    (offset[0] / WIDTH) as usize * LANES,
+                    (offset[1] / WIDTH) as usize * LANES,
+                ];
+                let shift = [offset[0] % WIDTH, offset[1] % WIDTH];
+                let rows =
+                    arch::_mm256_set_m128i(load(words.add(start[1])), load(words.add(start[0])));
+                let mut value = arch::$srlv(rows, $counts(shift[0], shift[1]));
+                let straddles = [shift[0] + bits > WIDTH, shift[1] + bits > WIDTH];
+                if straddles[0] || straddles[1] {
+                    let zero = arch::_mm_setzero_si128();
+                    let next = arch::_mm256_set_m128i(
+                        if straddles[1] {
+                            load(words.add(start[1] + LANES))
+                        } else {
+                            zero
+                        },
+                        if straddles[0] {
+                            load(words.add(start[0] + LANES))
+                        } else {
+                            zero
+                        },
+                    );
+                    let high = arch::$sllv(next, $counts(WIDTH - shift[0], WIDTH - shift[1]));
+                    value = arch::_mm256_or_si256(value, high);
+                }
+                arch::_mm256_storeu_si256(
+                    output.as_mut_ptr().add(row * LANES) as *mut arch::__m256i,
+                    arch::_mm256_and_si256(value, mask),
+                );
+            }
+        }
+    };
+}
+
+/// ORs `value` into the row of words at `word`
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline]
+#[target_feature(enable = "sse2")]
+unsafe fn or_into<T>(word: *mut T, value: arch::__m128i) {
+    let word = word as *mut arch::__m128i;
+    arch::_mm_storeu_si128(word, arch::_mm_or_si128(arch::_mm_loadu_si128(word), value));
+}
+
+/// loads the row of words at `word`
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline]
+#[target_feature(enable = "sse2")]
+unsafe fn load<T>(word: *const T) -> arch::__m128i {
+    arch::_mm_loadu_si128(word as *const arch::__m128i)
+}
+
+/// the per lane shift counts of two rows, for `_mm256_srlv_epi32`
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn counts_u32(first: u32, second: u32) -> arch::__m256i {
+    let (first, second) = (first as i32, second as i32);
+    arch::_mm256_setr_epi32(first, first, first, first, second, second, second, second)
+}
+
+/// the per lane shift counts of two rows, for `_mm256_srlv_epi64`
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn counts_u64(first: u32, second: u32) -> arch::__m256i {
+    let (first, second) = (first as i64, second as i64);
+    arch::_mm256_setr_epi64x(first, first, second, second)
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+x86_kernels!(
+    u32,
+    pack_sse2_u32,
+    unpack_sse2_u32,
+    pack_avx2_u32,
+    unpack_avx2_u32,
+    _mm_set1_epi32,
+    _mm_srl_epi32,
+    _mm_sll_epi32,
+    _mm256_srlv_epi32,
+    _mm256_sllv_epi32,
+    counts_u32
+);
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+x86_kernels!(
+    u64,
+    pack_sse2_u64,
+    unpack_sse2_u64,
+    pack_avx2_u64,
+    unpack_avx2_u64,
+    _mm_set1_epi64x,
+    _mm_srl_epi64,
+    _mm_sll_epi64,
+    _mm256_srlv_epi64,
+    _mm256_sllv_epi64,
+    counts_u64
+);
+
+macro_rules! implement_integer {
+    ($kind: ident, $pack_sse2: ident, $unpack_sse2: ident, $pack_avx2: ident, $unpack_avx2: ident) => {
+        impl Integer for $kind {
+            type Num = Num<$kind>;
+
+            const BITS: u32 = (size_of::<$kind>() * 8) as u32;
+
+            #[inline]
+            fn pack(values: &[$kind; BLOCK_LEN], bits: u32, output: &mut [$kind]) {
+                assert!(bits <= Self::BITS && output.len() >= packed_len::<$kind>(bits));
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                {
+                    // the CPU was just checked for each kernel's features
+                    if has(Feature::Avx2) {
+                        return unsafe { $pack_avx2(values, bits, output) };
+                    }
+                    if has(Feature::Sse2) {
+                        return unsafe { $pack_sse2(values, bits, output) };
+                    }
+                }
+                pack_rows(values, bits, output)
+            }
+
+            #[inline]
+            fn unpack(input: &[$kind], bits: u32, output: &mut [$kind; BLOCK_LEN]) {
+                assert!(bits <= Self::BITS && input.len() >= packed_len::<$kind>(bits));
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                {
+                    if has(Feature::Avx2) {
+                        return unsafe { $unpack_avx2(input, bits, output) };
+                    }
+                    if has(Feature::Sse2) {
+                        return unsafe { $unpack_sse2(input, bits, output) };
+                    }
+                }
+                unpack_rows(input, bits, output)
+            }
+        }
+    };
+}
+
+implement_integer!(
+    u32,
+    pack_sse2_u32,
+    unpack_sse2_u32,
+    pack_avx2_u32,
+    unpack_avx2_u32
+);
+implement_integer!(
+    u64,
+    pack_sse2_u64,
+    unpack_sse2_u64,
+    pack_avx2_u64,
+    unpack_avx2_u64
+);
+
+#[cfg(test)]
+mod test {
+
+    use super::super::feature_macros::error::Error;
+    use super::super::feature_macros::numbers::PrimativeNumber;
+    use super::super::feature_macros::varint::Varint;
+    use super::{
+        bits_needed, pack, pack_rows, packed_len, unpack, unpack_rows, Integer, BLOCK_LEN,
+    };
+
+    /// a block needing exactly `bits`, from a fixed xorshift sequence
+    fn block<T: Integer>(bits: u32) -> [T; BLOCK_LEN] {
+        let mut output = [T::default(); BLOCK_LEN];
+        if bits == 0 {
+            return output;
+        }
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        for value in output.iter_mut() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            *value = (T::Num::from_low_u64(state) >> (T::BITS - bits)).inner();
+        }
+        output[BLOCK_LEN / 3] = (!T::Num::default() >> (T::BITS - bits)).inner();
+        output
+    }
+
+    fn round_trips<T: Integer>() {
+        let mut packed = [T::default(); BLOCK_LEN];
+        let mut portable = [T::default(); BLOCK_LEN];
+        for bits in 0..=T::BITS {
+            let values = block::<T>(bits);
+            assert_eq!(bits_needed(&values[..]), bits);
+
+            let length = pack(&values, bits, &mut packed).unwrap();
+            assert_eq!(length, packed_len::<T>(bits));
+            // the dispatched kernel must match the portable one
+            pack_rows(&values, bits, &mut portable[..length]);
+            assert_eq!(&packed[..length], &portable[..length]);
+
+            let mut output = [T::default(); BLOCK_LEN];
+            assert_eq!(unpack(&packed[..length], bits, &mut output), Ok(length));
+            assert_eq!(&output[..], &values[..]);
+            let mut output = [T::default(); BLOCK_LEN];
+            unpack_rows(&packed[..length], bits, &mut output);
+            assert_eq!(&output[..], &values[..]);
+        }
+    }
+
+    /// checks a pack and unpack kernel against the portable forms,
+    /// including that bits above the width are discarded
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    fn check_kernels<T: Integer>(
+        pack_kernel: unsafe fn(&[T; BLOCK_LEN], u32, &mut [T]),
+        unpack_kernel: unsafe fn(&[T], u32, &mut [T; BLOCK_LEN]),
+    ) {
+        let values = block::<T>(T::BITS);
+        for bits in 0..=T::BITS {
+            let length = packed_len::<T>(bits);
+            let mut expected = [T::default(); BLOCK_LEN];
+            pack_rows(&values, bits, &mut expected[..length]);
+            // stale words must be cleared, and those past the block kept
+            let stale = (!T::Num::default()).inner();
+            let mut packed = [stale; BLOCK_LEN];
+            unsafe { pack_kernel(&values, bits, &mut packed) };
+            assert_eq!(&packed[..length], &expected[..length], "bits {}", bits);
+            assert!(
+                packed[length..].iter().all(|word| *word == stale),
+                "bits {}",
+                bits
+            );
+
+            let mut expected = [T::default(); BLOCK_LEN];
+            unpack_rows(&packed[..length], bits, &mut expected);
+            let mut output = [(!T::Num::default()).inner(); BLOCK_LEN];
+            unsafe { unpack_kernel(&packed[..length], bits, &mut output) };
+            assert_eq!(&output[..], &expected[..], "bits {}", bits);
+        }
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn x86_kernels_match_portable() {
+        use super::super::feature_macros::cpu::{has, Feature};
+        use super::{
+            pack_avx2_u32, pack_avx2_u64, pack_sse2_u32, pack_sse2_u64, unpack_avx2_u32,
+            unpack_avx2_u64, unpack_sse2_u32, unpack_sse2_u64,
+        };
+
+        if has(Feature::Sse2) {
+            check_kernels::<u32>(pack_sse2_u32, unpack_sse2_u32);
+            check_kernels::<u64>(pack_sse2_u64, unpack_sse2_u64);
+        }
+        if has(Feature::Avx2) {
+            check_kernels::<u32>(pack_avx2_u32, unpack_avx2_u32);
+            check_kernels::<u64>(pack_avx2_u64, unpack_avx2_u64);
+        }
+    }
+
+    #[test]
+    fn portable_kernels_discard_high_bits() {
+        let values = block::<u32>(32);
+        let mut packed = [0u32; BLOCK_LEN];
+        for bits in 0..=32 {
+            let length = pack(&values, bits, &mut packed).unwrap();
+            let mut output = [0u32; BLOCK_LEN];
+            unpack_rows(&packed[..length], bits, &mut output);
+            let mask = ((1u64 << bits) - 1) as u32;
+            for (value, expected) in output.iter().zip(values.iter()) {
+                assert_eq!(*value, *expected & mask);
+            }
+            pack_rows(&values, bits, &mut packed[..length]);
+            let mut output = [0u32; BLOCK_LEN];
+            unpack_rows(&packed[..length], bits, &mut output);
+            for (value, expected) in output.iter().zip(values.iter()) {
+                assert_eq!(*value, *expected & mask);
+            }
+        }
+    }
+
+    fn leaves_words_past_the_block<T: Integer>() {
+        let values = block::<T>(T::BITS);
+        let stale = (!T::Num::default()).inner();
+        for bits in 0..=T::BITS {
+            let length = packed_len::<T>(bits);
+            let mut packed = [stale; BLOCK_LEN];
+            T::pack(&values, bits, &mut packed);
+            assert!(
+                packed[length..].iter().all(|word| *word == stale),
+                "bits {}",
+                bits
+            );
+            let mut packed = [stale; BLOCK_LEN];
+            pack_rows(&values, bits, &mut packed);
+            assert!(
+                packed[length..].iter().all(|word| *word == stale),
+                "bits {}",
+                bits
+            );
+        }
+    }
+
+    #[test]
+    fn pack_leaves_words_past_the_block() {
+        leaves_words_past_the_block::<u32>();
+        leaves_words_past_the_block::<u64>();
+    }
+
+    #[test]
+    fn round_trips_u32() {
+        round_trips::<u32>();
+    }
+
+    #[test]
+    fn round_trips_u64() {
+        round_trips::<u64>();
+    }
+
+    #[test]
+    fn packs_lanes_vertically() {
+        let mut values = [0u32; BLOCK_LEN];
+        for (index, value) in values.iter_mut().enumerate() {
+            *value = (index % 4 == 1) as u32;
+        }
+        let mut packed = [0u32; 4];
+        assert_eq!(pack(&values, 1, &mut packed), Ok(4));
+        assert_eq!(packed, [0, u32::MAX, 0, 0]);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let values = [0u32; BLOCK_LEN];
+        let mut packed = [0u32; BLOCK_LEN];
+        assert_eq!(
+            pack(&values, 33, &mut packed),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        assert_eq!(
+            pack(&values, 3, &mut packed[..11]),
+            Err(Error::OutputFull { offset: 0 })
+        );
+
+        let mut output = [0u32; BLOCK_LEN];
+        assert_eq!(
+            unpack(&packed[..11], 3, &mut output),
+            Err(Error::Truncated { offset: 44 })
+        );
+        assert_eq!(
+            unpack(&packed, 33, &mut output),
+            Err(Error::CorruptBlock { offset: 0 })
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use super::feature_macros::numbers::PrimativeNumber;
+use super::Integer;
+
+/// encode replaces each value with its difference from the one before,
+/// `previous` is taken as the value before the first.
+///
+/// Sorted input produces small values. Differences wrap, so unsorted
+/// input still round trips, but its descending steps become large.
+#[inline]
+pub fn encode<T: Integer>(values: &mut [T], previous: T) {
+    let mut previous = T::Num::from(previous);
+    for value in values.iter_mut() {
+        let current = T::Num::from(*value);
+        *value = current.wrapping_sub(previous.inner()).inner();
+        previous = current;
+    }
+}
+
+/// inverse of `encode`, a running sum starting from `previous`
+#[inline]
+pub fn decode<T: Integer>(values: &mut [T], previous: T) {
+    let mut previous = T::Num::from(previous);
+    for value in values.iter_mut() {
+        previous = previous.wrapping_add(*value);
+        *value = previous.inner();
+    }
+}
+
+/// encode2 is delta-of-delta coding, `encode` applied twice. Evenly
+/// spaced values (such as timestamps) become runs of zeros.
+///
+/// `previous` is the value before the first, and `previous_delta` the
+/// difference between it and the one before that.
+#[inline]
+pub fn encode2<T: Integer>(values: &mut [T], previous: T, previous_delta: T) {
+    encode(values, previous);
+    encode(values, previous_delta);
+}
+
+/// inverse of `encode2`
+#[inline]
+pub fn decode2<T: Integer>(values: &mut [T], previous: T, previous_delta: T) {
+    decode(values, previous_delta);
+    decode(values, previous);
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{decode, decode2, encode, encode2};
+
+    #[test]
+    fn deltas_round_trip() {
+        let original = [3u32, 7, 7, 20, 1000, 2, u32::MAX, 0];
+        let mut values = original;
+        encode(&mut values, 1);
+        assert_eq!(
+            values,
+            [2, 4, 0, 13, 980, 2u32.wrapping_sub(1000), u32::MAX - 2, 1]
+        );
+        decode(&mut values, 1);
+        assert_eq!(values, original);
+    }
+
+    #[test]
+    fn delta_of_delta_flattens_even_spacing() {
+        let original = [1000u64, 1010, 1020, 1030, 1041, 1051];
+        let mut values = original;
+        encode2(&mut values, 990, 10);
+        assert_eq!(values, [0, 0, 0, 0, 1, u64::MAX]);
+        decode2(&mut values, 990, 10);
+        assert_eq!(values, original);
+
+        // chaining blocks gives the same result as one pass
+        let mut whole = original;
+        encode2(&mut whole, 0, 0);
+        let mut split = original;
+        encode2(&mut split[..3], 0, 0);
+        encode2(&mut split[3..], original[2], original[2] - original[1]);
+        assert_eq!(split, whole);
+    }
+}
@@ -0,0 +1,182 @@
+use super::bitpack::{bits_needed, packed_len};
+use super::feature_macros::error::Error;
+use super::feature_macros::limits::Limits;
+use super::feature_macros::mem::{size_of, size_of_val};
+use super::feature_macros::numbers::PrimativeNumber;
+use super::feature_macros::varint::Varint;
+use super::{Integer, BLOCK_LEN};
+
+/// words ahead of the packed values, the reference and the bit width
+const HEADER_LEN: usize = 2;
+
+/// encode writes up to `BLOCK_LEN` values as their offset from the
+/// smallest of them, packed at the width of the largest offset.
+/// Returns the number of words written.
+///
+/// A short block is padded, so it takes as much space as a whole one.
+/// Returns `Error::OutputFull` without writing when `output` is too small.
+///
+/// # Panics
+///
+/// When `values` holds more than `BLOCK_LEN` values.
+pub fn encode<T: Integer>(values: &[T], output: &mut [T]) -> Result<usize, Error> {
+    assert!(values.len() <= BLOCK_LEN);
+    let reference = values.iter().min().cloned().unwrap_or_default();
+    let mut block = [T::default(); BLOCK_LEN];
+    for (offset, value) in block.iter_mut().zip(values.iter()) {
+        *offset = T::Num::from(*value).wrapping_sub(reference).inner();
+    }
+    let bits = bits_needed(&block[..]);
+    let length = HEADER_LEN + packed_len::<T>(bits);
+    if output.len() < length {
+        return Err(Error::OutputFull { offset: 0 });
+    }
+    output[0] = reference;
+    output[1] = T::Num::from_low_u64(bits as u64).inner();
+    T::pack(&block, bits, &mut output[HEADER_LEN..length]);
+    Ok(length)
+}
+
+/// decode reads `output.len()` values written by `encode`, returning
+/// the number of words consumed.
+///
+/// Error offsets are in bytes, as if the words were stored little endian.
+/// The block is checked against `Limits::default()`.
+///
+/// # Panics
+///
+/// When `output` holds more than `BLOCK_LEN` values.
+#[inline]
+pub fn decode<T: Integer>(input: &[T], output: &mut [T]) -> Result<usize, Error> {
+    decode_with_limits(input, output, &Limits::default())
+}
+
+/// decode_with_limits is `decode`, which also checks the size of
+/// `output` and its ratio to the block against `limits`.
+///
+/// # Panics
+///
+/// When `output` holds more than `BLOCK_LEN` values.
+pub fn decode_with_limits<T: Integer>(
+    input: &[T],
+    output: &mut [T],
+    limits: &Limits,
+) -> Result<usize, Error> {
+    assert!(output.len() <= BLOCK_LEN);
+    if input.len() < HEADER_LEN {
+        return Err(Error::Truncated {
+            offset: size_of_val(input),
+        });
+    }
+    let reference = T::Num::from(input[0]);
+    let bits = T::Num::from(input[1]).low_u64();
+    if bits > T::BITS as u64 {
+        return Err(Error::CorruptBlock {
+            offset: size_of::<T>(),
+        });
+    }
+    let length = HEADER_LEN + packed_len::<T>(bits as u32);
+    if input.len() < length {
+        return Err(Error::Truncated {
+            offset: size_of_val(input),
+        });
+    }
+    let size = size_of_val(output) as u64;
+    limits.check_output(size, 0)?;
+    limits.check_ratio(size, (length * size_of::<T>()) as u64, 0)?;
+    let mut block = [T::default(); BLOCK_LEN];
+    T::unpack(&input[HEADER_LEN..length], bits as u32, &mut block);
+    for (value, offset) in output.iter_mut().zip(block.iter()) {
+        *value = reference.wrapping_add(*offset).inner();
+    }
+    Ok(length)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::feature_macros::error::Error;
+    use super::super::feature_macros::limits::Limits;
+    use super::super::BLOCK_LEN;
+    use super::{decode, decode_with_limits, encode};
+
+    #[test]
+    fn stores_offsets_from_the_minimum() {
+        let values = [1_000_000u32, 1_000_007, 1_000_003];
+        let mut encoded = [0u32; 2 + 32];
+        // offsets of up to 7 take 3 bits
+        assert_eq!(encode(&values, &mut encoded), Ok(2 + 12));
+        assert_eq!(&encoded[..2], &[1_000_000, 3]);
+
+        let mut output = [0u32; 3];
+        assert_eq!(decode(&encoded, &mut output), Ok(14));
+        assert_eq!(output, values);
+    }
+
+    #[test]
+    fn round_trips_full_blocks() {
+        let mut values = [0u64; BLOCK_LEN];
+        for (index, value) in values.iter_mut().enumerate() {
+            *value = u64::MAX - (index as u64 * 0x0123_4567);
+        }
+        let mut encoded = [0u64; 2 + 64];
+        let length = encode(&values, &mut encoded).unwrap();
+        let mut output = [0u64; BLOCK_LEN];
+        assert_eq!(decode(&encoded[..length], &mut output), Ok(length));
+        assert_eq!(&output[..], &values[..]);
+
+        // equal values need no bits at all
+        assert_eq!(encode(&[9u64; 40], &mut encoded), Ok(2));
+        let mut output = [0u64; 40];
+        assert_eq!(decode(&encoded[..2], &mut output), Ok(2));
+        assert_eq!(&output[..], &[9u64; 40][..]);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut encoded = [0u32; 16];
+        assert_eq!(
+            encode(&[0u32, 0xFFFF], &mut encoded),
+            Err(Error::OutputFull { offset: 0 })
+        );
+        let length = encode(&[0u32, 7], &mut encoded).unwrap();
+        let mut output = [0u32; 2];
+        assert_eq!(
+            decode(&encoded[..length - 1], &mut output),
+            Err(Error::Truncated {
+                offset: (length - 1) * 4
+            })
+        );
+        encoded[1] = 40;
+        assert_eq!(
+            decode(&encoded, &mut output),
+            Err(Error::CorruptBlock { offset: 4 })
+        );
+    }
+
+    #[test]
+    fn enforces_limits() {
+        // equal values take a 2 word block, 64 bytes of values per word
+        let mut encoded = [0u32; 2];
+        assert_eq!(encode(&[9u32; BLOCK_LEN], &mut encoded), Ok(2));
+        let mut output = [0u32; BLOCK_LEN];
+        let limits = Limits {
+            max_ratio: 63,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(&encoded, &mut output, &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        let limits = Limits {
+            max_ratio: 64,
+            max_output: 511,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(&encoded, &mut output, &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        assert_eq!(decode(&encoded, &mut output), Ok(2));
+    }
+}
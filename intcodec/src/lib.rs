@@ -0,0 +1,23 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate feature_macros;
+
+/// bitpack packs blocks of 128 integers at a fixed bit width
+pub mod bitpack;
+pub use bitpack::{Integer, BLOCK_LEN};
+
+/// delta replaces values with the difference from the one before
+pub mod delta;
+
+/// frame is frame-of-reference coding, values are stored relative to
+/// the smallest in their block
+pub mod frame;
+
+/// pfor is patched frame-of-reference coding, outliers are stored apart
+/// so they do not widen the whole block
+pub mod pfor;
+
+/// sorted compresses ascending lists, such as ID lists
+pub mod sorted;
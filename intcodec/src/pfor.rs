@@ -0,0 +1,331 @@
+use super::bitpack::{bits_needed, packed_len};
+use super::feature_macros::error::Error;
+use super::feature_macros::limits::Limits;
+use super::feature_macros::mem::{size_of, size_of_val};
+use super::feature_macros::numbers::PrimativeNumber;
+use super::feature_macros::varint::Varint;
+use super::{Integer, BLOCK_LEN};
+
+/// bits a patch's position takes, enough to index a block
+const POSITION_BITS: u32 = 7;
+
+/// encode writes up to `BLOCK_LEN` values, packing the low bits of all
+/// of them at a width chosen to minimize the size, and storing the high
+/// bits of the few which do not fit as patches. Returns the number of
+/// words written.
+///
+/// The block is a header word (`bits | high_bits << 8 | patches << 16`),
+/// the packed low bits, then each patch's position and high bits packed
+/// back to back. A short block is padded, so its packed low bits take as
+/// much space as a whole one. Returns `Error::OutputFull` without
+/// writing when `output` is too small.
+///
+/// # Panics
+///
+/// When `values` holds more than `BLOCK_LEN` values.
+pub fn encode<T: Integer>(values: &[T], output: &mut [T]) -> Result<usize, Error> {
+    assert!(values.len() <= BLOCK_LEN);
+    let mut block = [T::default(); BLOCK_LEN];
+    block[..values.len()].copy_from_slice(values);
+    let widest = bits_needed(&block[..]);
+    let (bits, patches) = choose_width(&block, widest);
+    let high_bits = widest - bits;
+
+    let packed = packed_len::<T>(bits);
+    let length = 1 + packed + words_for::<T>(patches * (POSITION_BITS + high_bits) as usize);
+    if output.len() < length {
+        return Err(Error::OutputFull { offset: 0 });
+    }
+    let header = bits as u64 | (high_bits as u64) << 8 | (patches as u64) << 16;
+    output[0] = T::Num::from_low_u64(header).inner();
+
+    let mut writer = BitWriter::<T>::new(&mut output[1 + packed..length]);
+    if high_bits > 0 {
+        for (position, value) in block.iter_mut().enumerate() {
+            let high = T::Num::from(*value) >> bits;
+            if high != T::Num::default() {
+                writer.write(T::Num::from_low_u64(position as u64), POSITION_BITS);
+                writer.write(high, high_bits);
+                *value = (T::Num::from(*value) ^ (high << bits)).inner();
+            }
+        }
+    }
+    T::pack(&block, bits, &mut output[1..1 + packed]);
+    Ok(length)
+}
+
+/// decode reads `output.len()` values written by `encode`, returning the
+/// number of words consumed.
+///
+/// Error offsets are in bytes, as if the words were stored little endian.
+/// The block is checked against `Limits::default()`.
+///
+/// # Panics
+///
+/// When `output` holds more than `BLOCK_LEN` values.
+#[inline]
+pub fn decode<T: Integer>(input: &[T], output: &mut [T]) -> Result<usize, Error> {
+    decode_with_limits(input, output, &Limits::default())
+}
+
+/// decode_with_limits is `decode`, which also checks the size of
+/// `output` and its ratio to the block against `limits`.
+///
+/// # Panics
+///
+/// When `output` holds more than `BLOCK_LEN` values.
+pub fn decode_with_limits<T: Integer>(
+    input: &[T],
+    output: &mut [T],
+    limits: &Limits,
+) -> Result<usize, Error> {
+    assert!(output.len() <= BLOCK_LEN);
+    let header = match input.first() {
+        Option::Some(header) => T::Num::from(*header).low_u64(),
+        Option::None => return Err(Error::Truncated { offset: 0 }),
+    };
+    let bits = (header & 0xFF) as u32;
+    let high_bits = ((header >> 8) & 0xFF) as u32;
+    let patches = (header >> 16) as usize;
+    if bits > T::BITS || high_bits > T::BITS - bits || patches > BLOCK_LEN {
+        return Err(Error::CorruptBlock { offset: 0 });
+    }
+    // `encode` only writes patches with high bits, and they must fit
+    // above `bits`, which is impossible at the full width
+    if patches > 0 && high_bits == 0 {
+        return Err(Error::CorruptBlock { offset: 0 });
+    }
+
+    let packed = packed_len::<T>(bits);
+    let length = 1 + packed + words_for::<T>(patches * (POSITION_BITS + high_bits) as usize);
+    if input.len() < length {
+        return Err(Error::Truncated {
+            offset: size_of_val(input),
+        });
+    }
+    let size = size_of_val(output) as u64;
+    limits.check_output(size, 0)?;
+    limits.check_ratio(size, (length * size_of::<T>()) as u64, 0)?;
+    let mut block = [T::default(); BLOCK_LEN];
+    T::unpack(&input[1..1 + packed], bits, &mut block);
+
+    let mut reader = BitReader::<T>::new(&input[1 + packed..length]);
+    for _ in 0..patches {
+        let position = reader.read(POSITION_BITS).low_u64() as usize;
+        if position >= output.len() {
+            return Err(Error::CorruptBlock {
+                offset: (1 + packed) * size_of::<T>(),
+            });
+        }
+        let high = reader.read(high_bits) << bits;
+        block[position] = (T::Num::from(block[position]) | high).inner();
+    }
+    output.copy_from_slice(&block[..output.len()]);
+    Ok(length)
+}
+
+/// picks the packed width which minimizes the size of the block, and
+/// returns it with the number of values which need patching
+fn choose_width<T: Integer>(block: &[T; BLOCK_LEN], widest: u32) -> (u32, usize) {
+    // how many values need exactly `n` bits
+    let mut histogram = [0usize; 65];
+    for value in block.iter() {
+        histogram[(T::BITS - T::Num::from(*value).leading_zeros()) as usize] += 1;
+    }
+    let mut best = (widest, 0usize);
+    let mut best_cost = BLOCK_LEN * widest as usize;
+    let mut patches = 0usize;
+    for bits in (0..widest).rev() {
+        patches += histogram[bits as usize + 1];
+        let cost = BLOCK_LEN * bits as usize + patches * (POSITION_BITS + widest - bits) as usize;
+        if cost < best_cost {
+            best = (bits, patches);
+            best_cost = cost;
+        }
+    }
+    best
+}
+
+/// number of words `bits` bits take
+#[inline(always)]
+fn words_for<T: Integer>(bits: usize) -> usize {
+    let width = T::BITS as usize;
+    bits / width + usize::from(bits & (width - 1) != 0)
+}
+
+/// appends values of any width to zeroed words, least significant first
+struct BitWriter<'a, T: Integer + 'a> {
+    output: &'a mut [T],
+    position: usize,
+}
+impl<'a, T: Integer> BitWriter<'a, T> {
+    fn new(output: &'a mut [T]) -> BitWriter<'a, T> {
+        for word in output.iter_mut() {
+            *word = T::default();
+        }
+        BitWriter {
+            output,
+            position: 0,
+        }
+    }
+
+    /// `value` must fit within `bits`
+    fn write(&mut self, value: T::Num, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        let width = T::BITS as usize;
+        let index = self.position / width;
+        let shift = (self.position % width) as u32;
+        self.output[index] = (T::Num::from(self.output[index]) | (value << shift)).inner();
+        if shift + bits > T::BITS {
+            let next = T::Num::from(self.output[index + 1]);
+            self.output[index + 1] = (next | (value >> (T::BITS - shift))).inner();
+        }
+        self.position += bits as usize;
+    }
+}
+
+/// reads values written by `BitWriter`
+struct BitReader<'a, T: Integer + 'a> {
+    input: &'a [T],
+    position: usize,
+}
+impl<'a, T: Integer> BitReader<'a, T> {
+    fn new(input: &'a [T]) -> BitReader<'a, T> {
+        BitReader { input, position: 0 }
+    }
+
+    /// the caller ensures `input` holds the bits
+    fn read(&mut self, bits: u32) -> T::Num {
+        if bits == 0 {
+            return T::Num::default();
+        }
+        let width = T::BITS as usize;
+        let index = self.position / width;
+        let shift = (self.position % width) as u32;
+        let mut value = T::Num::from(self.input[index]) >> shift;
+        if shift + bits > T::BITS {
+            value = value | (T::Num::from(self.input[index + 1]) << (T::BITS - shift));
+        }
+        self.position += bits as usize;
+        value & (!T::Num::default() >> (T::BITS - bits))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::feature_macros::error::Error;
+    use super::super::feature_macros::limits::Limits;
+    use super::super::BLOCK_LEN;
+    use super::{decode, decode_with_limits, encode};
+
+    #[test]
+    fn patches_outliers() {
+        let mut values = [0u32; BLOCK_LEN];
+        for (index, value) in values.iter_mut().enumerate() {
+            *value = (index % 8) as u32;
+        }
+        values[5] = 1 << 30;
+        values[77] = u32::MAX;
+
+        let mut encoded = [0u32; 1 + BLOCK_LEN];
+        let length = encode(&values, &mut encoded).unwrap();
+        // 3 bit values, and 2 patches of 7 + 29 bits
+        assert_eq!(encoded[0], 3 | 29 << 8 | 2 << 16);
+        assert_eq!(length, 1 + 12 + 3);
+
+        let mut output = [0u32; BLOCK_LEN];
+        assert_eq!(decode(&encoded[..length], &mut output), Ok(length));
+        assert_eq!(&output[..], &values[..]);
+    }
+
+    #[test]
+    fn round_trips_short_and_uniform_blocks() {
+        let mut encoded = [0u64; 1 + BLOCK_LEN * 2];
+        for count in [0usize, 1, 5, 100, BLOCK_LEN].iter() {
+            let mut values = [0u64; BLOCK_LEN];
+            for (index, value) in values[..*count].iter_mut().enumerate() {
+                *value = (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (index % 64);
+            }
+            let length = encode(&values[..*count], &mut encoded).unwrap();
+            let mut output = [0u64; BLOCK_LEN];
+            assert_eq!(
+                decode(&encoded[..length], &mut output[..*count]),
+                Ok(length)
+            );
+            assert_eq!(&output[..*count], &values[..*count]);
+        }
+
+        // without outliers there are no patches
+        let length = encode(&[0xFFu64; BLOCK_LEN], &mut encoded).unwrap();
+        assert_eq!(encoded[0], 8);
+        assert_eq!(length, 1 + 16);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let mut values = [1u32; BLOCK_LEN];
+        values[100] = 1 << 20;
+        let mut encoded = [0u32; 1 + BLOCK_LEN];
+        let length = encode(&values, &mut encoded).unwrap();
+        assert_eq!(
+            encode(&values, &mut encoded[..length - 1]),
+            Err(Error::OutputFull { offset: 0 })
+        );
+
+        let mut output = [0u32; BLOCK_LEN];
+        assert_eq!(
+            decode(&encoded[..length - 1], &mut output),
+            Err(Error::Truncated {
+                offset: (length - 1) * 4
+            })
+        );
+        // the patch is beyond a shorter output
+        assert_eq!(
+            decode(&encoded[..length], &mut output[..50]),
+            Err(Error::CorruptBlock { offset: 4 * 5 })
+        );
+        assert_eq!(
+            decode(&[33u32], &mut output),
+            Err(Error::CorruptBlock { offset: 0 })
+        );
+        // patches without high bits, at the full width they could not
+        // be shifted into place
+        let mut malformed = [0u32; 1 + BLOCK_LEN + 2];
+        for bits in [5u32, 32].iter() {
+            malformed[0] = bits | 1 << 16;
+            assert_eq!(
+                decode(&malformed, &mut output),
+                Err(Error::CorruptBlock { offset: 0 })
+            );
+        }
+    }
+
+    #[test]
+    fn enforces_limits() {
+        // a header word claiming a whole block of zero width values
+        let mut output = [7u32; BLOCK_LEN];
+        let limits = Limits {
+            max_ratio: 127,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(&[0u32], &mut output, &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        // nothing was written
+        assert_eq!(&output[..], &[7u32; BLOCK_LEN][..]);
+        let limits = Limits {
+            max_output: 4 * BLOCK_LEN as u64 - 1,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(&[0u32], &mut output, &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        assert_eq!(decode(&[0u32], &mut output), Ok(1));
+        assert_eq!(&output[..], &[0u32; BLOCK_LEN][..]);
+    }
+}
@@ -0,0 +1,159 @@
+use super::delta;
+use super::feature_macros::error::Error;
+use super::feature_macros::limits::Limits;
+use super::feature_macros::mem::{size_of, size_of_val};
+use super::pfor;
+use super::{Integer, BLOCK_LEN};
+
+/// encode compresses an ascending list, such as a list of IDs, returning
+/// the number of words written.
+///
+/// Each block of `BLOCK_LEN` values is delta coded (continuing from the
+/// previous block) then `pfor` coded, so occasional large gaps do not
+/// widen a whole block. Unsorted input round trips, but compresses
+/// poorly. The number of values is not stored.
+pub fn encode<T: Integer>(values: &[T], output: &mut [T]) -> Result<usize, Error> {
+    let mut position = 0;
+    let mut previous = T::default();
+    for chunk in values.chunks(BLOCK_LEN) {
+        let mut block = [T::default(); BLOCK_LEN];
+        let block = &mut block[..chunk.len()];
+        block.copy_from_slice(chunk);
+        delta::encode(block, previous);
+        previous = chunk[chunk.len() - 1];
+        position += pfor::encode(block, &mut output[position..])
+            .map_err(|e| e.rebase(position * size_of::<T>()))?;
+    }
+    Ok(position)
+}
+
+/// decode reads `output.len()` values written by `encode`, returning the
+/// number of words consumed.
+///
+/// Error offsets are in bytes, as if the words were stored little endian.
+/// The list is checked against `Limits::default()`.
+#[inline]
+pub fn decode<T: Integer>(input: &[T], output: &mut [T]) -> Result<usize, Error> {
+    decode_with_limits(input, output, &Limits::default())
+}
+
+/// decode_with_limits is `decode`, which also checks the size of
+/// `output` against `limits`, and each block's ratio to its values.
+///
+/// As the number of values is not stored, `output.len()` is the claim
+/// being checked, before any block is decoded.
+pub fn decode_with_limits<T: Integer>(
+    input: &[T],
+    output: &mut [T],
+    limits: &Limits,
+) -> Result<usize, Error> {
+    limits.check_output(size_of_val(output) as u64, 0)?;
+    let mut position = 0;
+    let mut previous = T::default();
+    for chunk in output.chunks_mut(BLOCK_LEN) {
+        position += pfor::decode_with_limits(&input[position..], chunk, limits)
+            .map_err(|e| e.rebase(position * size_of::<T>()))?;
+        delta::decode(chunk, previous);
+        previous = chunk[chunk.len() - 1];
+    }
+    Ok(position)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::feature_macros::error::Error;
+    use super::super::feature_macros::limits::Limits;
+    use super::{decode, decode_with_limits, encode};
+
+    /// ascending IDs with mostly small gaps, and the odd large one
+    fn ids(output: &mut [u32]) {
+        let mut state = 0x1234_5678u32;
+        let mut id = 1_000_000u32;
+        for value in output.iter_mut() {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            id += if state & 127 == 0 {
+                state >> 12
+            } else {
+                state & 15
+            };
+            *value = id;
+        }
+    }
+
+    #[test]
+    fn compresses_id_lists() {
+        let mut values = [0u32; 1000];
+        ids(&mut values);
+        let mut encoded = [0u32; 1200];
+        let length = encode(&values, &mut encoded).unwrap();
+        // 4 bit gaps with a few patches, well under a word per value
+        assert!(length < values.len() / 6, "{}", length);
+
+        let mut output = [0u32; 1000];
+        assert_eq!(decode(&encoded[..length], &mut output), Ok(length));
+        assert_eq!(&output[..], &values[..]);
+        assert_eq!(encode::<u32>(&[], &mut encoded), Ok(0));
+    }
+
+    #[test]
+    fn offsets_cover_the_whole_input() {
+        let mut values = [0u64; 300];
+        for (index, value) in values.iter_mut().enumerate() {
+            *value = index as u64 * 3;
+        }
+        let mut encoded = [0u64; 400];
+        let length = encode(&values, &mut encoded).unwrap();
+        let mut output = [0u64; 300];
+        match decode(&encoded[..length - 1], &mut output) {
+            Err(Error::Truncated { offset }) => assert_eq!(offset, (length - 1) * 8),
+            other => panic!("{:?}", other),
+        }
+        match encode(&values, &mut encoded[..length - 1]) {
+            Err(Error::OutputFull { offset }) => assert!(offset > 0),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn enforces_limits() {
+        // zero width blocks of repeated IDs, a word per 128 values
+        let mut output = [0u32; 1000];
+        let limits = Limits {
+            max_output: 3999,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(&[0u32; 8], &mut output, &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        let limits = Limits {
+            max_ratio: 100,
+            ..Limits::default()
+        };
+        assert_eq!(
+            decode_with_limits(&[0u32; 8], &mut output, &limits),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+        // the last block is short, and within the ratio
+        let limits = Limits {
+            max_ratio: 128,
+            ..Limits::default()
+        };
+        assert_eq!(decode_with_limits(&[0u32; 8], &mut output, &limits), Ok(8));
+
+        // a later block is located by its offset
+        let mut encoded = [0u32; 8];
+        encoded[0] = 1;
+        let limits = Limits {
+            max_ratio: 120,
+            ..Limits::default()
+        };
+        match decode_with_limits(&encoded, &mut output, &limits) {
+            Err(Error::LimitExceeded { offset }) => assert_eq!(offset, 4 + 16),
+            other => panic!("{:?}", other),
+        }
+    }
+}
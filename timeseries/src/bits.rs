@@ -0,0 +1,208 @@
+use super::feature_macros::error::Error;
+
+/// BitWriter appends values of up to 64 bits to a byte buffer, most
+/// significant bit first.
+pub struct BitWriter<'a> {
+    output: &'a mut [u8],
+    position: usize,
+}
+impl<'a> BitWriter<'a> {
+    #[inline]
+    pub fn new(output: &'a mut [u8]) -> BitWriter<'a> {
+        BitWriter {
+            output,
+            position: 0,
+        }
+    }
+
+    /// number of bits written
+    #[inline(always)]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// number of bytes holding the bits written, the last may be partial
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        (self.position + 7) >> 3
+    }
+
+    /// nothing has been written
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.position == 0
+    }
+
+    /// discards everything written after `position`, so a value which
+    /// did not fit can be written again elsewhere. The discarded bits are
+    /// cleared, leaving the unused bits of the last byte zero.
+    #[inline]
+    pub fn rewind(&mut self, position: usize) {
+        debug_assert!(position <= self.position);
+        let end = self.len();
+        self.position = position;
+        let start = self.len();
+        for byte in self.output[start..end].iter_mut() {
+            *byte = 0;
+        }
+        let used = (position & 7) as u32;
+        if used != 0 {
+            self.output[start - 1] &= !(0xFFu8 >> used);
+        }
+    }
+
+    /// writes the low `bits` of `value`.
+    ///
+    /// Returns `Error::OutputFull` (with the offset of the byte which did
+    /// not fit) and writes nothing when there is not room for all of them.
+    #[inline]
+    pub fn write(&mut self, value: u64, bits: u32) -> Result<(), Error> {
+        debug_assert!(bits <= 64);
+        if self.position + bits as usize > self.output.len() * 8 {
+            return Err(Error::OutputFull {
+                offset: self.position >> 3,
+            });
+        }
+        let mut remaining = bits;
+        while remaining > 0 {
+            let index = self.position >> 3;
+            let used = (self.position & 7) as u32;
+            let take = (8 - used).min(remaining);
+            let chunk = ((value >> (remaining - take)) & ((1 << take) - 1)) as u8;
+            // bits past `position` may be set, the buffer need not be zeroed
+            let kept = self.output[index] & !(0xFFu8 >> used);
+            self.output[index] = kept | (chunk << (8 - used - take));
+            self.position += take as usize;
+            remaining -= take;
+        }
+        Ok(())
+    }
+}
+
+/// BitReader reads values written by `BitWriter`.
+pub struct BitReader<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+impl<'a> BitReader<'a> {
+    #[inline]
+    pub fn new(input: &'a [u8]) -> BitReader<'a> {
+        BitReader { input, position: 0 }
+    }
+
+    /// number of bits read
+    #[inline(always)]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// reads `bits` bits as the low bits of the result.
+    ///
+    /// Returns `Error::Truncated` when the input holds fewer.
+    #[inline]
+    pub fn read(&mut self, bits: u32) -> Result<u64, Error> {
+        debug_assert!(bits <= 64);
+        if self.position + bits as usize > self.input.len() * 8 {
+            return Err(Error::Truncated {
+                offset: self.input.len(),
+            });
+        }
+        let mut value = 0u64;
+        let mut remaining = bits;
+        while remaining > 0 {
+            let byte = self.input[self.position >> 3];
+            let used = (self.position & 7) as u32;
+            let take = (8 - used).min(remaining);
+            let chunk = (byte >> (8 - used - take)) & (0xFFu8 >> (8 - take));
+            // `take` may be 8, which a `u64` shift permits
+            value = (value << take) | chunk as u64;
+            self.position += take as usize;
+            remaining -= take;
+        }
+        Ok(value)
+    }
+
+    /// reads a single bit
+    #[inline(always)]
+    pub fn read_bit(&mut self) -> Result<bool, Error> {
+        self.read(1).map(|bit| bit != 0)
+    }
+
+    /// counts set bits before the first clear one, reading at most
+    /// `limit`, for unary prefixes
+    #[inline]
+    pub fn read_ones(&mut self, limit: u32) -> Result<u32, Error> {
+        let mut count = 0;
+        while count < limit && self.read_bit()? {
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::feature_macros::error::Error;
+    use super::{BitReader, BitWriter};
+
+    #[test]
+    fn round_trips_every_width() {
+        let mut buffer = [0u8; 600];
+        let mut writer = BitWriter::new(&mut buffer);
+        for bits in 0..=64u32 {
+            let value = 0xA5C3_96F0_1E2D_3C4Bu64 >> (64 - bits.max(1));
+            writer
+                .write(if bits == 0 { 0 } else { value }, bits)
+                .unwrap();
+        }
+        let written = writer.position();
+        assert_eq!(written, 64 * 65 / 2);
+        assert_eq!(writer.len(), 260);
+
+        let mut reader = BitReader::new(&buffer);
+        for bits in 0..=64u32 {
+            let value = 0xA5C3_96F0_1E2D_3C4Bu64 >> (64 - bits.max(1));
+            assert_eq!(reader.read(bits), Ok(if bits == 0 { 0 } else { value }));
+        }
+        assert_eq!(reader.position(), written);
+    }
+
+    #[test]
+    fn writes_most_significant_first() {
+        let mut buffer = [0xFFu8; 2];
+        {
+            let mut writer = BitWriter::new(&mut buffer);
+            writer.write(0b101, 3).unwrap();
+            writer.write(0b0_0001, 5).unwrap();
+            writer.write(0b11, 2).unwrap();
+            assert_eq!(writer.len(), 2);
+        }
+        assert_eq!(buffer[0], 0b1010_0001);
+        assert_eq!(buffer[1] >> 6, 0b11);
+
+        let mut reader = BitReader::new(&buffer);
+        assert_eq!(reader.read_ones(4), Ok(1));
+        assert_eq!(reader.read_ones(4), Ok(1));
+        assert_eq!(reader.read(6), Ok(0b00_0111));
+    }
+
+    #[test]
+    fn rewinds_and_reports_limits() {
+        let mut buffer = [0u8; 2];
+        {
+            let mut writer = BitWriter::new(&mut buffer);
+            writer.write(0xFFF, 12).unwrap();
+            writer.rewind(4);
+            writer.write(0, 4).unwrap();
+            assert_eq!(writer.write(0, 9), Err(Error::OutputFull { offset: 1 }));
+            assert_eq!(writer.position(), 8);
+        }
+        assert_eq!(buffer[0], 0xF0);
+
+        let mut reader = BitReader::new(&buffer);
+        reader.read(10).unwrap();
+        assert_eq!(reader.read(7), Err(Error::Truncated { offset: 2 }));
+        assert_eq!(reader.position(), 10);
+    }
+}
@@ -0,0 +1,331 @@
+#[cfg(not(feature = "std"))]
+use core::hash::Hasher;
+#[cfg(feature = "std")]
+use std::hash::Hasher;
+
+use super::feature_macros::endian::{view, view_mut, Le};
+use super::feature_macros::error::Error;
+use super::feature_macros::limits::Limits;
+use super::feature_macros::mem::size_of;
+use super::feature_macros::numbers::{Num, PrimativeNumber};
+use super::stream::{Decoder, Encoder};
+use super::value::ValueCodec;
+use super::xxhash::bits32::XXHash32;
+
+/// block magic number, `TSBK` when read as little endian bytes
+const MAGIC: u32 = 0x4B42_5354u32;
+
+/// the only block format version which has been defined
+const VERSION: u8 = 1;
+
+view! {
+    /// the start of a block, the payload follows it
+    struct Header {
+        magic: Le<u32>,
+        version: u8,
+        codec: u8,
+        count: Le<u32>,
+        payload_length: Le<u32>,
+    }
+}
+
+/// width of the XXH32 which follows the payload
+const CHECKSUM_LEN: usize = 4;
+
+/// Block is a parsed block, its checksum has been verified.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Block<'a> {
+    /// how the values were coded
+    pub codec: ValueCodec,
+    /// number of points
+    pub count: usize,
+    /// the encoded stream
+    pub payload: &'a [u8],
+}
+impl<'a> Block<'a> {
+    /// iterates over the block's points, held to `Limits::default()`
+    #[inline]
+    pub fn points(&self) -> Decoder<'a> {
+        Decoder::new(self.payload, self.codec, self.count)
+    }
+}
+
+/// XXH32 with a seed of 0
+#[inline]
+fn checksum(data: &[u8]) -> u32 {
+    let mut hasher = XXHash32::new();
+    hasher.write(data);
+    hasher.finish() as u32
+}
+
+/// encode_block writes `timestamps` and `values` as a single block,
+/// returning its length in bytes.
+///
+/// The block is a header, the stream an `Encoder` writes, and an XXH32 of
+/// both. Returns `Error::OutputFull` when `output` cannot hold it, and
+/// `Error::LimitExceeded` when the slices differ in length or hold more
+/// points than a header can count.
+pub fn encode_block(
+    timestamps: &[i64],
+    values: &[f64],
+    codec: ValueCodec,
+    output: &mut [u8],
+) -> Result<usize, Error> {
+    if timestamps.len() != values.len() || timestamps.len() > u32::MAX as usize {
+        return Err(Error::LimitExceeded { offset: 0 });
+    }
+    if output.len() < Header::SIZE {
+        return Err(Error::OutputFull {
+            offset: output.len(),
+        });
+    }
+    let payload_length = {
+        let mut encoder = Encoder::new(&mut output[Header::SIZE..], codec);
+        for (timestamp, value) in timestamps.iter().zip(values.iter()) {
+            encoder
+                .push(*timestamp, *value)
+                .map_err(|e| e.rebase(Header::SIZE))?;
+        }
+        encoder.finish()
+    };
+    let end = Header::SIZE + payload_length;
+    if output.len() < end + CHECKSUM_LEN {
+        return Err(Error::OutputFull { offset: end });
+    }
+
+    {
+        let header = view_mut::<Header>(output)?;
+        header.magic.set(Num::from(MAGIC));
+        header.version = VERSION;
+        header.codec = codec.id();
+        header.count.set(Num::from(timestamps.len() as u32));
+        header.payload_length.set(Num::from(payload_length as u32));
+    }
+    let sum = checksum(&output[..end]);
+    view_mut::<Le<u32>>(&mut output[end..])?.set(Num::from(sum));
+    Ok(end + CHECKSUM_LEN)
+}
+
+/// parse_block reads the block at the start of `input`, verifying its
+/// checksum, and returns it with its length in bytes.
+///
+/// The block is checked against `Limits::default()`, as `Block::points`
+/// is.
+#[inline]
+pub fn parse_block(input: &[u8]) -> Result<(Block<'_>, usize), Error> {
+    parse_block_with_limits(input, &Limits::default())
+}
+
+/// parse_block_with_limits is `parse_block`, which also checks the
+/// block's count against `limits`.
+///
+/// The points, as `(i64, f64)`, must fit in `max_output` and be within
+/// `max_ratio` of the payload, otherwise `Error::LimitExceeded` is
+/// returned at the count. Iterate a block parsed with looser limits than
+/// the default through `Decoder::with_limits`.
+pub fn parse_block_with_limits<'a>(
+    input: &'a [u8],
+    limits: &Limits,
+) -> Result<(Block<'a>, usize), Error> {
+    let header = view::<Header>(input)?;
+    if header.magic.get().inner() != MAGIC {
+        return Err(Error::BadMagic { offset: 0 });
+    }
+    if header.version != VERSION {
+        return Err(Error::Unsupported { offset: 4 });
+    }
+    let codec = match ValueCodec::from_id(header.codec) {
+        Option::Some(codec) => codec,
+        Option::None => return Err(Error::Unsupported { offset: 5 }),
+    };
+
+    // a 32 bit length plus the header can overflow a 32 bit `usize`
+    let end = match Header::SIZE.checked_add(header.payload_length.get().inner() as usize) {
+        Option::Some(end) => end,
+        Option::None => return Err(Error::CorruptBlock { offset: 10 }),
+    };
+    if input.len() < end {
+        return Err(Error::Truncated {
+            offset: input.len(),
+        });
+    }
+    let stored = view::<Le<u32>>(&input[end..])
+        .map_err(|e| e.rebase(end))?
+        .get()
+        .inner();
+    let computed = checksum(&input[..end]);
+    if stored != computed {
+        return Err(Error::ChecksumMismatch {
+            offset: end,
+            expected: stored as u64,
+            actual: computed as u64,
+        });
+    }
+
+    // only a header which passed its checksum is held to the limits
+    let count = header.count.get().inner();
+    let size = count as u64 * size_of::<(i64, f64)>() as u64;
+    limits.check_output(size, 6)?;
+    limits.check_ratio(size, (end - Header::SIZE) as u64, 6)?;
+
+    let block = Block {
+        codec,
+        count: count as usize,
+        payload: &input[Header::SIZE..end],
+    };
+    Ok((block, end + CHECKSUM_LEN))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::feature_macros::endian::{view_mut, Le};
+    use super::super::feature_macros::error::Error;
+    use super::super::feature_macros::limits::Limits;
+    use super::super::feature_macros::numbers::Num;
+    use super::super::value::ValueCodec;
+    use super::{checksum, encode_block, parse_block, parse_block_with_limits, Header};
+
+    const TIMESTAMPS: [i64; 8] = [0, 60, 120, 180, 240, 300, 361, 420];
+    const VALUES: [f64; 8] = [
+        0.5,
+        0.5,
+        0.75,
+        -0.0,
+        f64::NAN,
+        f64::NEG_INFINITY,
+        f64::INFINITY,
+        1.0e-310,
+    ];
+
+    fn encoded(codec: ValueCodec, output: &mut [u8]) -> usize {
+        encode_block(&TIMESTAMPS, &VALUES, codec, output).unwrap()
+    }
+
+    #[test]
+    fn round_trips_blocks() {
+        for codec in [ValueCodec::Gorilla, ValueCodec::Chimp].iter() {
+            let mut buffer = [0u8; 256];
+            let length = encoded(*codec, &mut buffer);
+            assert_eq!(&buffer[..4], b"TSBK");
+
+            let (block, consumed) = parse_block(&buffer).unwrap();
+            assert_eq!(consumed, length);
+            assert_eq!(block.codec, *codec);
+            assert_eq!(block.count, 8);
+            assert_eq!(block.points().count(), 8);
+            for (point, expected) in block.points().zip(TIMESTAMPS.iter().zip(VALUES.iter())) {
+                let (timestamp, value) = point.unwrap();
+                assert_eq!(timestamp, *expected.0);
+                assert_eq!(value.to_bits(), expected.1.to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn detects_corruption() {
+        let mut buffer = [0u8; 256];
+        let length = encoded(ValueCodec::Chimp, &mut buffer);
+        for position in Header::SIZE..length - 4 {
+            let mut bad = buffer;
+            bad[position] ^= 0x10;
+            match parse_block(&bad[..length]) {
+                Err(Error::ChecksumMismatch { offset, .. }) => assert_eq!(offset, length - 4),
+                other => panic!("flip at {} gave {:?}", position, other),
+            }
+        }
+
+        let mut bad = buffer;
+        bad[0] = b'X';
+        assert_eq!(parse_block(&bad), Err(Error::BadMagic { offset: 0 }));
+        let mut bad = buffer;
+        bad[4] = 2;
+        assert_eq!(parse_block(&bad), Err(Error::Unsupported { offset: 4 }));
+        let mut bad = buffer;
+        bad[5] = 9;
+        assert_eq!(parse_block(&bad), Err(Error::Unsupported { offset: 5 }));
+    }
+
+    #[test]
+    fn reports_truncation() {
+        let mut buffer = [0u8; 256];
+        let length = encoded(ValueCodec::Gorilla, &mut buffer);
+        for short in 0..length {
+            assert_eq!(
+                parse_block(&buffer[..short]),
+                Err(Error::Truncated { offset: short })
+            );
+        }
+        for short in 0..length {
+            let mut output = [0u8; 256];
+            assert!(match encode_block(
+                &TIMESTAMPS,
+                &VALUES,
+                ValueCodec::Gorilla,
+                &mut output[..short]
+            ) {
+                Err(Error::OutputFull { offset }) => offset <= short,
+                _ => false,
+            });
+        }
+        let mut output = [0u8; 256];
+        assert_eq!(
+            encode_block(&TIMESTAMPS, &VALUES[..7], ValueCodec::Gorilla, &mut output),
+            Err(Error::LimitExceeded { offset: 0 })
+        );
+    }
+
+    /// `encoded`, with the count replaced and the checksum fixed up
+    fn recounted(count: u32, output: &mut [u8]) -> usize {
+        let length = encoded(ValueCodec::Gorilla, output);
+        output[6..10].copy_from_slice(&count.to_le_bytes());
+        let end = length - 4;
+        let sum = checksum(&output[..end]);
+        view_mut::<Le<u32>>(&mut output[end..])
+            .unwrap()
+            .set(Num::from(sum));
+        length
+    }
+
+    #[test]
+    fn enforces_limits() {
+        // a valid block claiming the most points a header can
+        let mut buffer = [0u8; 256];
+        let length = recounted(u32::MAX, &mut buffer);
+        assert_eq!(
+            parse_block(&buffer[..length]),
+            Err(Error::LimitExceeded { offset: 6 })
+        );
+        let (block, _) = parse_block_with_limits(&buffer[..length], &Limits::unlimited()).unwrap();
+        assert_eq!(block.count, u32::MAX as usize);
+
+        // 8 points take 128 bytes, from a payload of a few dozen
+        let length = recounted(8, &mut buffer);
+        let payload = length - Header::SIZE - 4;
+        let limits = Limits {
+            max_output: 127,
+            ..Limits::default()
+        };
+        assert_eq!(
+            parse_block_with_limits(&buffer[..length], &limits),
+            Err(Error::LimitExceeded { offset: 6 })
+        );
+        let limits = Limits {
+            max_ratio: 128 / payload as u64 - 1,
+            ..Limits::default()
+        };
+        assert_eq!(
+            parse_block_with_limits(&buffer[..length], &limits),
+            Err(Error::LimitExceeded { offset: 6 })
+        );
+        assert!(parse_block(&buffer[..length]).is_ok());
+
+        // the count is only trusted once the checksum is
+        buffer[6] = 0xFF;
+        buffer[9] = 0xFF;
+        match parse_block(&buffer[..length]) {
+            Err(Error::ChecksumMismatch { .. }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[allow(unused_imports)]
+#[macro_use]
+extern crate feature_macros;
+extern crate xxhash;
+
+/// bits reads and writes values of any width, most significant first
+pub mod bits;
+
+/// timestamp is delta-of-delta coding of timestamps
+pub mod timestamp;
+
+/// value is XOR coding of `f64` values, as Gorilla and Chimp
+pub mod value;
+pub use value::ValueCodec;
+
+/// stream encodes and decodes timestamp/value pairs
+pub mod stream;
+pub use stream::{Decoder, Encoder};
+
+/// block wraps a stream with a header and checksum
+pub mod block;
+pub use block::{encode_block, parse_block, Block};
@@ -0,0 +1,285 @@
+use super::bits::{BitReader, BitWriter};
+use super::feature_macros::error::Error;
+use super::feature_macros::limits::Limits;
+use super::feature_macros::mem::size_of;
+use super::timestamp::TimestampState;
+use super::value::{ValueCodec, ValueState};
+
+/// Encoder appends timestamp/value pairs to a caller supplied buffer.
+///
+/// A point is either written whole or not at all, so after
+/// `Error::OutputFull` the buffer still holds every earlier point and
+/// `finish` may be called.
+pub struct Encoder<'a> {
+    writer: BitWriter<'a>,
+    timestamps: TimestampState,
+    values: ValueState,
+    count: usize,
+}
+impl<'a> Encoder<'a> {
+    #[inline]
+    pub fn new(output: &'a mut [u8], codec: ValueCodec) -> Encoder<'a> {
+        Encoder {
+            writer: BitWriter::new(output),
+            timestamps: TimestampState::new(),
+            values: ValueState::new(codec),
+            count: 0,
+        }
+    }
+
+    /// appends a point
+    pub fn push(&mut self, timestamp: i64, value: f64) -> Result<(), Error> {
+        let mark = self.writer.position();
+        let (timestamps, values) = (self.timestamps, self.values);
+        let result = self
+            .timestamps
+            .encode(&mut self.writer, timestamp)
+            .and_then(|()| self.values.encode(&mut self.writer, value));
+        match result {
+            Ok(()) => {
+                self.count += 1;
+                Ok(())
+            }
+            Err(e) => {
+                self.writer.rewind(mark);
+                self.timestamps = timestamps;
+                self.values = values;
+                Err(e)
+            }
+        }
+    }
+
+    /// number of points written
+    #[inline(always)]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// number of bytes written, including the partial last byte
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.writer.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.writer.is_empty()
+    }
+
+    /// returns the number of bytes written, the unused bits of the last
+    /// byte are zero even after `Error::OutputFull`
+    #[inline]
+    pub fn finish(self) -> usize {
+        self.writer.len()
+    }
+}
+
+/// Decoder iterates over the `count` points an `Encoder` wrote.
+///
+/// Iteration stops after the first error. A `count` whose points would
+/// exceed the decoder's `Limits` yields `Error::LimitExceeded` before any
+/// point is decoded, so it is never trusted to size an allocation.
+pub struct Decoder<'a> {
+    reader: BitReader<'a>,
+    timestamps: TimestampState,
+    values: ValueState,
+    remaining: usize,
+    limit: usize,
+}
+impl<'a> Decoder<'a> {
+    /// a decoder held to `Limits::default()`
+    #[inline]
+    pub fn new(input: &'a [u8], codec: ValueCodec, count: usize) -> Decoder<'a> {
+        Decoder::with_limits(input, codec, count, &Limits::default())
+    }
+
+    /// a decoder whose points, as `(i64, f64)`, may take at most
+    /// `max_output` bytes and `max_ratio` times the length of `input`
+    #[inline]
+    pub fn with_limits(
+        input: &'a [u8],
+        codec: ValueCodec,
+        count: usize,
+        limits: &Limits,
+    ) -> Decoder<'a> {
+        let bytes = limits
+            .max_output
+            .min(limits.max_ratio.saturating_mul(input.len() as u64));
+        let points = bytes / size_of::<(i64, f64)>() as u64;
+        Decoder {
+            reader: BitReader::new(input),
+            timestamps: TimestampState::new(),
+            values: ValueState::new(codec),
+            remaining: count,
+            limit: if points < count as u64 {
+                points as usize
+            } else {
+                count
+            },
+        }
+    }
+
+    /// number of points which have not been read
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn read(&mut self) -> Result<(i64, f64), Error> {
+        let timestamp = self.timestamps.decode(&mut self.reader)?;
+        let value = self.values.decode(&mut self.reader)?;
+        Ok((timestamp, value))
+    }
+}
+impl<'a> Iterator for Decoder<'a> {
+    type Item = Result<(i64, f64), Error>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return Option::None;
+        }
+        // only possible before the first point, `remaining` only falls
+        if self.remaining > self.limit {
+            self.remaining = 0;
+            return Option::Some(Err(Error::LimitExceeded { offset: 0 }));
+        }
+        let point = self.read();
+        self.remaining = if point.is_ok() { self.remaining - 1 } else { 0 };
+        Option::Some(point)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.remaining > self.limit {
+            (1, Option::Some(1))
+        } else {
+            (0, Option::Some(self.remaining))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::feature_macros::error::Error;
+    use super::super::feature_macros::limits::Limits;
+    use super::super::value::ValueCodec;
+    use super::{Decoder, Encoder};
+
+    /// a minute of one second samples of a slowly drifting gauge
+    fn series(index: usize) -> (i64, f64) {
+        let timestamp = 1_600_000_000 + index as i64;
+        let value = 20.0 + (index / 4) as f64 * 0.25;
+        (timestamp, value)
+    }
+
+    #[test]
+    fn round_trips_points() {
+        for codec in [ValueCodec::Gorilla, ValueCodec::Chimp].iter() {
+            let mut buffer = [0u8; 1024];
+            let mut encoder = Encoder::new(&mut buffer, *codec);
+            for index in 0..60 {
+                let (timestamp, value) = series(index);
+                encoder.push(timestamp, value).unwrap();
+            }
+            assert_eq!(encoder.count(), 60);
+            let length = encoder.finish();
+            // 16 bytes for each of the raw first pairs is most of it
+            assert!(length < 16 + 60 * 2, "{:?} took {} bytes", codec, length);
+
+            let decoder = Decoder::new(&buffer[..length], *codec, 60);
+            for (index, point) in decoder.enumerate() {
+                assert_eq!(point, Ok(series(index)));
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_whole_points_when_full() {
+        let mut buffer = [0u8; 24];
+        let length = {
+            let mut encoder = Encoder::new(&mut buffer, ValueCodec::Gorilla);
+            encoder.push(0, 1.5).unwrap();
+            encoder.push(10, 2.5).unwrap();
+            let full = encoder.push(-1_000_000, -3.0e100);
+            assert_eq!(full, Err(Error::OutputFull { offset: 20 }));
+            assert_eq!(encoder.count(), 2);
+            encoder.finish()
+        };
+        // the failed point leaves nothing behind, not even trailing bits
+        let mut expected = [0u8; 24];
+        {
+            let mut encoder = Encoder::new(&mut expected, ValueCodec::Gorilla);
+            encoder.push(0, 1.5).unwrap();
+            encoder.push(10, 2.5).unwrap();
+            assert_eq!(encoder.finish(), length);
+        }
+        assert_eq!(buffer, expected);
+        let mut decoder = Decoder::new(&buffer[..length], ValueCodec::Gorilla, 2);
+        assert_eq!(decoder.next(), Some(Ok((0, 1.5))));
+        assert_eq!(decoder.next(), Some(Ok((10, 2.5))));
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    fn stops_after_truncation() {
+        let mut buffer = [0u8; 64];
+        let length = {
+            let mut encoder = Encoder::new(&mut buffer, ValueCodec::Chimp);
+            encoder.push(5, 0.1).unwrap();
+            encoder.push(6, 0.2).unwrap();
+            encoder.finish()
+        };
+        let mut decoder = Decoder::new(&buffer[..length - 1], ValueCodec::Chimp, 3);
+        assert_eq!(decoder.next(), Some(Ok((5, 0.1))));
+        assert_eq!(
+            decoder.next(),
+            Some(Err(Error::Truncated { offset: length - 1 }))
+        );
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    fn limits_the_count() {
+        let mut buffer = [0u8; 64];
+        let length = {
+            let mut encoder = Encoder::new(&mut buffer, ValueCodec::Gorilla);
+            encoder.push(5, 0.1).unwrap();
+            encoder.push(6, 0.2).unwrap();
+            encoder.finish()
+        };
+        // a count claiming the most points a block header can
+        let mut decoder = Decoder::new(&buffer[..length], ValueCodec::Gorilla, u32::MAX as usize);
+        assert_eq!(decoder.size_hint(), (1, Some(1)));
+        assert_eq!(
+            decoder.next(),
+            Some(Err(Error::LimitExceeded { offset: 0 }))
+        );
+        assert_eq!(decoder.next(), None);
+
+        // two points take 32 bytes
+        let limits = Limits {
+            max_output: 31,
+            ..Limits::default()
+        };
+        let mut decoder = Decoder::with_limits(&buffer[..length], ValueCodec::Gorilla, 2, &limits);
+        assert_eq!(
+            decoder.next(),
+            Some(Err(Error::LimitExceeded { offset: 0 }))
+        );
+        let limits = Limits {
+            max_output: 32,
+            ..Limits::default()
+        };
+        let decoder = Decoder::with_limits(&buffer[..length], ValueCodec::Gorilla, 2, &limits);
+        assert_eq!(decoder.size_hint(), (0, Some(2)));
+        assert_eq!(decoder.count(), 2);
+        // nothing decodes to nothing
+        let mut decoder = Decoder::new(&[], ValueCodec::Gorilla, 1);
+        assert_eq!(
+            decoder.next(),
+            Some(Err(Error::LimitExceeded { offset: 0 }))
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use super::bits::{BitReader, BitWriter};
+use super::feature_macros::error::Error;
+use super::feature_macros::numbers::{Num, PrimativeNumber};
+use super::feature_macros::varint::{zigzag_decode, zigzag_encode};
+
+/// unary prefix length and payload width of each delta-of-delta bucket,
+/// after the `0` used for an unchanged delta. The widths follow Gorilla,
+/// but hold zigzag values so the ranges are symmetric.
+const BUCKETS: [u32; 4] = [7, 9, 12, 64];
+
+/// TimestampState is the delta-of-delta coder's state, shared by the
+/// encoder and decoder.
+///
+/// The first timestamp is stored whole. Every later one is stored as the
+/// change in the difference between timestamps, so a regular interval
+/// takes a single bit per point.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct TimestampState {
+    previous: i64,
+    previous_delta: i64,
+    started: bool,
+}
+impl TimestampState {
+    #[inline]
+    pub fn new() -> TimestampState {
+        TimestampState::default()
+    }
+
+    /// writes `timestamp`, the state is only updated when it fits
+    pub fn encode(&mut self, writer: &mut BitWriter, timestamp: i64) -> Result<(), Error> {
+        if !self.started {
+            writer.write(timestamp as u64, 64)?;
+            self.previous = timestamp;
+            self.started = true;
+            return Ok(());
+        }
+        let delta = timestamp.wrapping_sub(self.previous);
+        let zigzag = zigzag_encode(Num::from(delta.wrapping_sub(self.previous_delta))).inner();
+        if zigzag == 0 {
+            writer.write(0, 1)?;
+        } else {
+            let bucket = BUCKETS
+                .iter()
+                .position(|bits| *bits == 64 || zigzag >> *bits == 0)
+                .unwrap_or(BUCKETS.len() - 1);
+            // `bucket + 1` ones, then a zero unless it is the last bucket
+            let prefix_bits = (bucket as u32 + 2).min(BUCKETS.len() as u32);
+            let prefix = ((1u64 << prefix_bits) - 1) & !u64::from(bucket + 1 < BUCKETS.len());
+            writer.write(prefix, prefix_bits)?;
+            writer.write(zigzag, BUCKETS[bucket])?;
+        }
+        self.previous = timestamp;
+        self.previous_delta = delta;
+        Ok(())
+    }
+
+    /// reads the next timestamp
+    pub fn decode(&mut self, reader: &mut BitReader) -> Result<i64, Error> {
+        if !self.started {
+            self.previous = reader.read(64)? as i64;
+            self.started = true;
+            return Ok(self.previous);
+        }
+        let ones = reader.read_ones(BUCKETS.len() as u32)? as usize;
+        let delta_of_delta = if ones == 0 {
+            0
+        } else {
+            let zigzag = reader.read(BUCKETS[ones - 1])?;
+            zigzag_decode::<Num<i64>>(Num::from(zigzag)).inner()
+        };
+        self.previous_delta = self.previous_delta.wrapping_add(delta_of_delta);
+        self.previous = self.previous.wrapping_add(self.previous_delta);
+        Ok(self.previous)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::bits::{BitReader, BitWriter};
+    use super::TimestampState;
+
+    fn round_trip(timestamps: &[i64]) -> usize {
+        let mut buffer = [0u8; 1024];
+        let written = {
+            let mut writer = BitWriter::new(&mut buffer);
+            let mut state = TimestampState::new();
+            for timestamp in timestamps {
+                state.encode(&mut writer, *timestamp).unwrap();
+            }
+            writer.position()
+        };
+        let mut reader = BitReader::new(&buffer);
+        let mut state = TimestampState::new();
+        for timestamp in timestamps {
+            assert_eq!(state.decode(&mut reader), Ok(*timestamp));
+        }
+        assert_eq!(reader.position(), written);
+        written
+    }
+
+    #[test]
+    fn regular_intervals_take_one_bit() {
+        let mut timestamps = [0i64; 100];
+        for (index, timestamp) in timestamps.iter_mut().enumerate() {
+            *timestamp = 1_600_000_000 + 60 * index as i64;
+        }
+        // the first whole, the first delta, then a bit each
+        assert_eq!(round_trip(&timestamps), 64 + (2 + 7) + 98);
+    }
+
+    #[test]
+    fn uses_every_bucket() {
+        let timestamps = [
+            1000,
+            1010,
+            1020,
+            1019,
+            1070,
+            1370,
+            1370,
+            3000,
+            i64::MIN,
+            i64::MAX,
+            -5,
+        ];
+        // 64, 2+7, 1, 2+7, 2+7, 3+9, 4+12, 4+12, then 4+64 for the extremes
+        assert_eq!(
+            round_trip(&timestamps),
+            64 + 9 + 1 + 9 + 9 + 12 + 16 + 16 + 68 + 68 + 68
+        );
+    }
+}
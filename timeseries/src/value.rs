@@ -0,0 +1,323 @@
+use super::bits::{BitReader, BitWriter};
+use super::feature_macros::error::Error;
+
+/// ValueCodec selects how each value's XOR with the one before it is
+/// stored.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ValueCodec {
+    /// Gorilla (Pelkonen et al. 2015) stores the meaningful bits of the
+    /// XOR, reusing the previous leading/trailing zero window when the
+    /// new one fits within it.
+    Gorilla,
+    /// Chimp (Liakos et al. 2022) rounds the leading zeros to one of 8
+    /// values and only trims trailing zeros when there are more than 6,
+    /// which suits real-world series where the trailing bits rarely
+    /// repeat.
+    Chimp,
+}
+impl ValueCodec {
+    /// the identifier stored in block headers
+    #[inline]
+    pub fn id(self) -> u8 {
+        match self {
+            ValueCodec::Gorilla => 1,
+            ValueCodec::Chimp => 2,
+        }
+    }
+
+    /// the codec with the identifier `id`
+    #[inline]
+    pub fn from_id(id: u8) -> Option<ValueCodec> {
+        match id {
+            1 => Option::Some(ValueCodec::Gorilla),
+            2 => Option::Some(ValueCodec::Chimp),
+            _ => Option::None,
+        }
+    }
+}
+
+/// leading zero counts Chimp can represent in 3 bits
+const CHIMP_LEADING: [u32; 8] = [0, 8, 12, 16, 18, 20, 22, 24];
+
+/// Chimp only trims trailing zeros when there are more than this many
+const CHIMP_TRAILING_THRESHOLD: u32 = 6;
+
+/// marks that no leading/trailing zero window has been stored
+const NO_WINDOW: u32 = 65;
+
+/// ValueState is the XOR coder's state, shared by the encoder and decoder.
+///
+/// The first value is stored whole, later ones as their XOR with the
+/// previous value, which for slowly changing series has long runs of
+/// leading and trailing zeros.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ValueState {
+    codec: ValueCodec,
+    previous: u64,
+    leading: u32,
+    trailing: u32,
+    started: bool,
+}
+impl ValueState {
+    #[inline]
+    pub fn new(codec: ValueCodec) -> ValueState {
+        ValueState {
+            codec,
+            previous: 0,
+            leading: NO_WINDOW,
+            trailing: 0,
+            started: false,
+        }
+    }
+
+    /// writes `value`, the state is only updated when it fits
+    pub fn encode(&mut self, writer: &mut BitWriter, value: f64) -> Result<(), Error> {
+        let bits = value.to_bits();
+        if !self.started {
+            writer.write(bits, 64)?;
+            self.started = true;
+        } else {
+            let xor = bits ^ self.previous;
+            let mut next = *self;
+            match self.codec {
+                ValueCodec::Gorilla => next.encode_gorilla(writer, xor)?,
+                ValueCodec::Chimp => next.encode_chimp(writer, xor)?,
+            }
+            *self = next;
+        }
+        self.previous = bits;
+        Ok(())
+    }
+
+    /// reads the next value
+    pub fn decode(&mut self, reader: &mut BitReader) -> Result<f64, Error> {
+        if !self.started {
+            self.previous = reader.read(64)?;
+            self.started = true;
+        } else {
+            let xor = match self.codec {
+                ValueCodec::Gorilla => self.decode_gorilla(reader)?,
+                ValueCodec::Chimp => self.decode_chimp(reader)?,
+            };
+            self.previous ^= xor;
+        }
+        Ok(f64::from_bits(self.previous))
+    }
+
+    /// `0` for an unchanged value, `10` and the bits within the stored
+    /// window, or `11`, 5 bits of leading zeros, 6 bits of length (`0`
+    /// meaning 64), and that many bits.
+    fn encode_gorilla(&mut self, writer: &mut BitWriter, xor: u64) -> Result<(), Error> {
+        if xor == 0 {
+            return writer.write(0, 1);
+        }
+        let leading = xor.leading_zeros().min(31);
+        let trailing = xor.trailing_zeros();
+        if self.leading != NO_WINDOW && leading >= self.leading && trailing >= self.trailing {
+            writer.write(0b10, 2)?;
+            return writer.write(xor >> self.trailing, 64 - self.leading - self.trailing);
+        }
+        let length = 64 - leading - trailing;
+        writer.write(0b11, 2)?;
+        writer.write(leading as u64, 5)?;
+        writer.write((length & 63) as u64, 6)?;
+        writer.write(xor >> trailing, length)?;
+        self.leading = leading;
+        self.trailing = trailing;
+        Ok(())
+    }
+
+    fn decode_gorilla(&mut self, reader: &mut BitReader) -> Result<u64, Error> {
+        if !reader.read_bit()? {
+            return Ok(0);
+        }
+        if !reader.read_bit()? {
+            if self.leading == NO_WINDOW {
+                return Err(Error::CorruptBlock {
+                    offset: reader.position() >> 3,
+                });
+            }
+            let length = 64 - self.leading - self.trailing;
+            return Ok(reader.read(length)? << self.trailing);
+        }
+        let leading = reader.read(5)? as u32;
+        let length = match reader.read(6)? as u32 {
+            0 => 64,
+            length => length,
+        };
+        if leading + length > 64 {
+            return Err(Error::CorruptBlock {
+                offset: reader.position() >> 3,
+            });
+        }
+        self.leading = leading;
+        self.trailing = 64 - leading - length;
+        Ok(reader.read(length)? << self.trailing)
+    }
+
+    /// `00` for an unchanged value, `01`, 3 bits of leading zeros, 6 bits
+    /// of length and the bits between the zeros when there are many
+    /// trailing zeros, otherwise everything after the leading zeros with
+    /// `10` when they match the previous count or `11` and 3 bits of them.
+    fn encode_chimp(&mut self, writer: &mut BitWriter, xor: u64) -> Result<(), Error> {
+        if xor == 0 {
+            writer.write(0b00, 2)?;
+            self.leading = NO_WINDOW;
+            return Ok(());
+        }
+        let code = CHIMP_LEADING
+            .iter()
+            .rposition(|leading| *leading <= xor.leading_zeros())
+            .unwrap_or(0);
+        let leading = CHIMP_LEADING[code];
+        let trailing = xor.trailing_zeros();
+        if trailing > CHIMP_TRAILING_THRESHOLD {
+            let length = 64 - leading - trailing;
+            writer.write(0b01, 2)?;
+            writer.write(code as u64, 3)?;
+            writer.write(length as u64, 6)?;
+            writer.write(xor >> trailing, length)?;
+            self.leading = NO_WINDOW;
+        } else if leading == self.leading {
+            writer.write(0b10, 2)?;
+            writer.write(xor, 64 - leading)?;
+        } else {
+            writer.write(0b11, 2)?;
+            writer.write(code as u64, 3)?;
+            writer.write(xor, 64 - leading)?;
+            self.leading = leading;
+        }
+        Ok(())
+    }
+
+    fn decode_chimp(&mut self, reader: &mut BitReader) -> Result<u64, Error> {
+        match reader.read(2)? {
+            0b00 => {
+                self.leading = NO_WINDOW;
+                Ok(0)
+            }
+            0b01 => {
+                let leading = CHIMP_LEADING[reader.read(3)? as usize];
+                let length = reader.read(6)? as u32;
+                if length == 0 || leading + length > 64 {
+                    return Err(Error::CorruptBlock {
+                        offset: reader.position() >> 3,
+                    });
+                }
+                self.leading = NO_WINDOW;
+                Ok(reader.read(length)? << (64 - leading - length))
+            }
+            0b10 => {
+                if self.leading == NO_WINDOW {
+                    return Err(Error::CorruptBlock {
+                        offset: reader.position() >> 3,
+                    });
+                }
+                reader.read(64 - self.leading)
+            }
+            _ => {
+                self.leading = CHIMP_LEADING[reader.read(3)? as usize];
+                reader.read(64 - self.leading)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::super::bits::{BitReader, BitWriter};
+    use super::super::feature_macros::error::Error;
+    use super::{ValueCodec, ValueState};
+
+    /// a slowly varying series, with repeats, jumps and special values
+    const SERIES: [f64; 16] = [
+        12.0,
+        12.0,
+        12.5,
+        12.75,
+        12.5,
+        13.0,
+        1.0e300,
+        -0.0,
+        0.0,
+        f64::INFINITY,
+        f64::NAN,
+        f64::MIN_POSITIVE,
+        1.1,
+        1.1,
+        1.0e-3,
+        -7.25,
+    ];
+
+    fn round_trip(codec: ValueCodec, values: &[f64]) -> usize {
+        let mut buffer = [0u8; 1024];
+        let written = {
+            let mut writer = BitWriter::new(&mut buffer);
+            let mut state = ValueState::new(codec);
+            for value in values {
+                state.encode(&mut writer, *value).unwrap();
+            }
+            writer.position()
+        };
+        let mut reader = BitReader::new(&buffer);
+        let mut state = ValueState::new(codec);
+        for value in values {
+            let decoded = state.decode(&mut reader).unwrap();
+            assert_eq!(decoded.to_bits(), value.to_bits());
+        }
+        assert_eq!(reader.position(), written);
+        written
+    }
+
+    #[test]
+    fn round_trips_special_values() {
+        round_trip(ValueCodec::Gorilla, &SERIES);
+        round_trip(ValueCodec::Chimp, &SERIES);
+    }
+
+    #[test]
+    fn repeats_take_few_bits() {
+        let values = [21.5f64; 10];
+        assert_eq!(round_trip(ValueCodec::Gorilla, &values), 64 + 9);
+        assert_eq!(round_trip(ValueCodec::Chimp, &values), 64 + 9 * 2);
+    }
+
+    #[test]
+    fn gorilla_reuses_the_window() {
+        // both XORs are the single bit 12.0 and 12.5 differ in
+        let bits = round_trip(ValueCodec::Gorilla, &[12.0, 12.5, 12.0]);
+        let first = 2 + 5 + 6 + 1;
+        let second = 2 + 1;
+        assert_eq!(bits, 64 + first + second);
+    }
+
+    #[test]
+    fn chimp_keeps_leading_zero_counts() {
+        // these differ from each other in their low bits only, so
+        // after the first their leading zero count is repeated
+        let values = [
+            1.0f64,
+            1.0000000000000002,
+            1.0000000000000004,
+            1.0000000000000007,
+        ];
+        let bits = round_trip(ValueCodec::Chimp, &values);
+        assert_eq!(bits, 64 + (2 + 3 + 40) + (2 + 40) + (2 + 40));
+    }
+
+    #[test]
+    fn rejects_window_reuse_without_a_window() {
+        // `10` before any window was stored
+        for codec in [ValueCodec::Gorilla, ValueCodec::Chimp].iter() {
+            let buffer = [0u8, 0, 0, 0, 0, 0, 0, 0, 0b1000_0000, 0];
+            let mut reader = BitReader::new(&buffer);
+            let mut state = ValueState::new(*codec);
+            assert_eq!(state.decode(&mut reader), Ok(0.0));
+            assert_eq!(
+                state.decode(&mut reader),
+                Err(Error::CorruptBlock { offset: 8 })
+            );
+        }
+    }
+}
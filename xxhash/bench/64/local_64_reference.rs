@@ -10,7 +10,7 @@ fn bench_local_64_reference(c: &mut Criterion) {
     let mut data = [0u8; 32786];
     getrandom(data.as_mut()).unwrap();
     let mut group = c.benchmark_group("xxhash64_internal_reference_size");
-    for size in vec![1, 4, 8, 16, 32usize] {
+    for size in [1, 4, 8, 16, 32usize] {
         let sized = size * 1024usize;
         let slice: &[u8] = &data[0..sized];
         group.throughput(Throughput::Bytes(sized as u64));
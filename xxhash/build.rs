@@ -0,0 +1,20 @@
+use std::env;
+
+/// re-emits the capabilities detected by `feature_macros/build.rs`, as its
+/// macros (such as `inconceivable!`) are expanded within this crate.
+fn main() {
+    for name in env::var("DEP_FEATURE_MACROS_KNOWN")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|name| !name.is_empty())
+    {
+        println!("cargo:rustc-check-cfg=cfg({})", name);
+    }
+    for name in env::var("DEP_FEATURE_MACROS_CAPABILITIES")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|name| !name.is_empty())
+    {
+        println!("cargo:rustc-cfg={}", name);
+    }
+}
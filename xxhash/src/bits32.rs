@@ -143,6 +143,10 @@ pub fn xxhash32_reference(seed: u32, buffer: &[u8]) -> u32 {
 }
 
 /// xxhash32_ffi is exposed for consumption by the FFI into C/C++ projects
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes.
 #[no_mangle]
 #[inline(never)]
 pub unsafe extern "C" fn xxhash32_ffi(seed: u32, ptr: *const u8, len: usize) -> u32 {
@@ -176,7 +180,7 @@ impl XXHash32 {
         XXHash32 {
             interior_length: 0,
             total_length: 0,
-            seed: Num::<u32>::from(seed),
+            seed,
             state: [
                 seed.wrapping_add(PRIME32_1).wrapping_add(PRIME32_2),
                 seed.wrapping_add(PRIME32_2),
@@ -202,11 +206,7 @@ impl XXHash32 {
                 .wrapping_add(PRIME32_5)
                 .wrapping_add(self.total_length as u32)
         };
-        xxh32_finalize(
-            Num::<u32>::from(hash),
-            &self.interior_buffer[0..self.interior_length],
-        )
-        .inner()
+        xxh32_finalize(hash, &self.interior_buffer[0..self.interior_length]).inner()
     }
 
     /// all the conditional branching.
@@ -232,18 +232,18 @@ impl XXHash32 {
     }
 
     #[inline]
-    fn consume<'a>(&mut self, arg: &'a [u8]) {
-        for remaining_data in self.maybe_consume(arg) {
+    fn consume(&mut self, arg: &[u8]) {
+        if let Some(remaining_data) = self.maybe_consume(arg) {
             for chunk in remaining_data.chunks(16) {
                 if chunk.len() == 16 {
                     self.state[0] =
-                        xxh32_round(self.state[0], Num::<u32>::read_value_le(&chunk[0..4]));
+                        xxh32_round(self.state[0], Num::<u32>::read_value_le_at(chunk, 0));
                     self.state[1] =
-                        xxh32_round(self.state[1], Num::<u32>::read_value_le(&chunk[4..8]));
+                        xxh32_round(self.state[1], Num::<u32>::read_value_le_at(chunk, 4));
                     self.state[2] =
-                        xxh32_round(self.state[2], Num::<u32>::read_value_le(&chunk[8..12]));
+                        xxh32_round(self.state[2], Num::<u32>::read_value_le_at(chunk, 8));
                     self.state[3] =
-                        xxh32_round(self.state[3], Num::<u32>::read_value_le(&chunk[12..16]));
+                        xxh32_round(self.state[3], Num::<u32>::read_value_le_at(chunk, 12));
                     self.total_length += 16;
                 } else {
                     self.copy_into_internal(chunk);
@@ -265,28 +265,17 @@ impl XXHash32 {
 
         let start = self.interior_length;
         let term = start + slice.len();
-        (&mut self.interior_buffer[start..term]).copy_from_slice(slice);
+        self.interior_buffer[start..term].copy_from_slice(slice);
 
         self.interior_length += slice.len();
         self.total_length += slice.len();
 
         if self.interior_length == 16 {
-            self.state[0] = xxh32_round(
-                self.state[0],
-                Num::<u32>::read_value_le(&self.interior_buffer[0..4]),
-            );
-            self.state[1] = xxh32_round(
-                self.state[1],
-                Num::<u32>::read_value_le(&self.interior_buffer[4..8]),
-            );
-            self.state[2] = xxh32_round(
-                self.state[2],
-                Num::<u32>::read_value_le(&self.interior_buffer[8..12]),
-            );
-            self.state[3] = xxh32_round(
-                self.state[3],
-                Num::<u32>::read_value_le(&self.interior_buffer[12..16]),
-            );
+            let buffer = &self.interior_buffer;
+            self.state[0] = xxh32_round(self.state[0], Num::<u32>::read_value_le_at(buffer, 0));
+            self.state[1] = xxh32_round(self.state[1], Num::<u32>::read_value_le_at(buffer, 4));
+            self.state[2] = xxh32_round(self.state[2], Num::<u32>::read_value_le_at(buffer, 8));
+            self.state[3] = xxh32_round(self.state[3], Num::<u32>::read_value_le_at(buffer, 12));
             self.interior_length = 0;
         }
     }
@@ -304,7 +293,6 @@ impl XXHash32 {
 impl Hasher for XXHash32 {
     #[inline(never)]
     fn write(&mut self, data: &[u8]) {
-        if !self.is_empty() {}
         self.consume(data);
     }
 
@@ -325,6 +313,9 @@ mod test {
     #[cfg(feature = "std")]
     use std::hash::Hasher;
 
+    #[cfg(not(feature = "std"))]
+    use std::vec::Vec;
+
     /// This test mostly exists to ensure that our implementation is correct
     /// we're using a 3^rd party crate as a reference.
     #[test]
@@ -1,5 +1,6 @@
 use super::feature_macros::intrinsics::{hint_likely, memcp};
 use super::feature_macros::numbers::{Num, PrimativeNumber};
+use super::feature_macros::prefetch::prefetch_ahead;
 
 #[cfg(not(feature = "std"))]
 use core::hash::Hasher;
@@ -17,6 +18,9 @@ const PRIME64_3: u64 = 1609587929392839161u64;
 const PRIME64_4: u64 = 9650029242287828579u64;
 const PRIME64_5: u64 = 2870177450012600261u64;
 
+/// how far ahead of the current stripe `consume` prefetches
+const PREFETCH_DISTANCE: usize = 384;
+
 #[inline(always)]
 fn xxh64_round<A, B>(seed: A, input: B) -> Num<u64>
 where
@@ -139,7 +143,7 @@ fn xxh64_reference(seed: Num<u64>, buffer: &[u8]) -> Num<u64> {
                 .wrapping_add(output[1].rotate_left(7))
                 .wrapping_add(output[2].rotate_left(12))
                 .wrapping_add(output[3].rotate_left(18)),
-            |fold_state, new| xxh64_merge_round(fold_state, new),
+            xxh64_merge_round,
         )
     } else {
         seed.wrapping_add(PRIME64_5)
@@ -174,7 +178,7 @@ impl XXHash64 {
         XXHash64 {
             interior_length: 0,
             total_length: 0,
-            seed: Num::<u64>::from(seed),
+            seed,
             state: [
                 seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
                 seed.wrapping_add(PRIME64_2),
@@ -187,7 +191,7 @@ impl XXHash64 {
 
     #[inline]
     fn total_length(&self) -> usize {
-        (self.total_length + self.interior_length) as usize
+        self.total_length + self.interior_length
     }
 
     fn perform_finish(&self) -> u64 {
@@ -208,15 +212,11 @@ impl XXHash64 {
             self.seed.wrapping_add(PRIME64_5)
         };
         let hash = hash.wrapping_add(self.total_length() as u64);
-        xxh64_finalize(
-            Num::<u64>::from(hash),
-            &self.interior_buffer[0..self.interior_length],
-        )
-        .inner()
+        xxh64_finalize(hash, &self.interior_buffer[0..self.interior_length]).inner()
     }
 
     #[inline]
-    fn consume<'a>(&mut self, arg: &'a [u8]) {
+    fn consume(&mut self, arg: &[u8]) {
         let mut arg = arg;
 
         // check if we need to do slow path stuff
@@ -227,9 +227,7 @@ impl XXHash64 {
                 unsafe {
                     memcp(
                         arg.as_ptr(),
-                        self.interior_buffer
-                            .as_mut_ptr()
-                            .offset(self.interior_length as isize),
+                        self.interior_buffer.as_mut_ptr().add(self.interior_length),
                         arg.len(),
                     )
                 };
@@ -244,31 +242,20 @@ impl XXHash64 {
                 arg = unsafe {
                     memcp(
                         arg.as_ptr(),
-                        self.interior_buffer
-                            .as_mut_ptr()
-                            .offset(self.interior_length as isize),
-                        chop_off as usize,
+                        self.interior_buffer.as_mut_ptr().add(self.interior_length),
+                        chop_off,
                     );
-                    from_raw_parts(arg.as_ptr().offset(chop_off as isize), arg.len() - chop_off)
+                    from_raw_parts(arg.as_ptr().add(chop_off), arg.len() - chop_off)
                 };
 
                 // cleanup internal state
-                self.state[0] = xxh64_round(
-                    self.state[0].clone(),
-                    Num::<u64>::read_value_le(&self.interior_buffer[0..8]),
-                );
-                self.state[1] = xxh64_round(
-                    self.state[1].clone(),
-                    Num::<u64>::read_value_le(&self.interior_buffer[8..16]),
-                );
-                self.state[2] = xxh64_round(
-                    self.state[2].clone(),
-                    Num::<u64>::read_value_le(&self.interior_buffer[16..24]),
-                );
-                self.state[3] = xxh64_round(
-                    self.state[3].clone(),
-                    Num::<u64>::read_value_le(&self.interior_buffer[24..32]),
-                );
+                let buffer = &self.interior_buffer;
+                self.state[0] = xxh64_round(self.state[0], Num::<u64>::read_value_le_at(buffer, 0));
+                self.state[1] = xxh64_round(self.state[1], Num::<u64>::read_value_le_at(buffer, 8));
+                self.state[2] =
+                    xxh64_round(self.state[2], Num::<u64>::read_value_le_at(buffer, 16));
+                self.state[3] =
+                    xxh64_round(self.state[3], Num::<u64>::read_value_le_at(buffer, 24));
                 self.total_length += 32;
                 self.interior_length = 0;
 
@@ -279,22 +266,25 @@ impl XXHash64 {
         let last_align = arg.len() & (<Num<usize> as PrimativeNumber>::max() - 31);
         let (nicely_aligned, messy) = arg.split_at(last_align);
 
-        if nicely_aligned.len() > 0 {
+        if !nicely_aligned.is_empty() {
             let mut v1 = self.state[0];
             let mut v2 = self.state[1];
             let mut v3 = self.state[2];
             let mut v4 = self.state[3];
-            let mut processed = 0;
+            let mut remaining = nicely_aligned;
 
-            for chunk in nicely_aligned.chunks(32) {
+            while !remaining.is_empty() {
+                // this function may do nothing depending on the feature flag
+                prefetch_ahead(remaining, PREFETCH_DISTANCE);
+                let (chunk, rest) = remaining.split_at(32);
                 let [n1, n2, n3, n4] = dereference_32(chunk);
                 v1 = xxh64_round(v1, n1);
                 v2 = xxh64_round(v2, n2);
                 v3 = xxh64_round(v3, n3);
                 v4 = xxh64_round(v4, n4);
-                processed += 1;
+                remaining = rest;
             }
-            self.total_length += processed * 32;
+            self.total_length += nicely_aligned.len();
             self.state[0] = v1;
             self.state[1] = v2;
             self.state[2] = v3;
@@ -334,6 +324,8 @@ fn dereference_32(arg: &[u8]) -> [u64; 4] {
 
     debug_assert!(arg.len() == 32);
 
+    // `[u64; 4]` has no alignment guarantee within `arg`, so this must
+    // always be an unaligned read.
     #[allow(unused_mut)]
     let mut output: [u64; 4] =
         unsafe { read_unaligned::<[u64; 4]>(arg.as_ptr() as *const [u64; 4]) };
@@ -341,24 +333,28 @@ fn dereference_32(arg: &[u8]) -> [u64; 4] {
     #[cfg(target_endian = "big")]
     {
         for ptr in output.iter_mut() {
-            *ptr = ptr.clone().byte_swap();
+            *ptr = u64::from_le(*ptr);
         }
     }
     output
 }
 
 #[cfg(test)]
+#[allow(clippy::mixed_case_hex_literals)]
 mod test {
 
     use super::super::getrandom::getrandom;
     use super::super::twox_hash::XxHash64;
-    use super::{xxhash64_reference, XXHash64};
+    use super::{dereference_32, xxhash64_reference, XXHash64};
 
     #[cfg(not(feature = "std"))]
     use core::hash::Hasher;
     #[cfg(feature = "std")]
     use std::hash::Hasher;
 
+    #[cfg(not(feature = "std"))]
+    use std::vec::Vec;
+
     #[test]
     fn xxh64_sanity_test() {
         let mut seed = [0u8; 8];
@@ -393,6 +389,32 @@ mod test {
         assert_eq!(local_output, hasher3.finish());
     }
 
+    #[test]
+    fn dereference_32_is_alignment_independent() {
+        let data = (0..64).collect::<Vec<u8>>();
+        for offset in 0..32 {
+            let chunk = &data[offset..offset + 32];
+            let expected = [
+                u64::from_le_bytes([
+                    chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7],
+                ]),
+                u64::from_le_bytes([
+                    chunk[8], chunk[9], chunk[10], chunk[11], chunk[12], chunk[13], chunk[14],
+                    chunk[15],
+                ]),
+                u64::from_le_bytes([
+                    chunk[16], chunk[17], chunk[18], chunk[19], chunk[20], chunk[21], chunk[22],
+                    chunk[23],
+                ]),
+                u64::from_le_bytes([
+                    chunk[24], chunk[25], chunk[26], chunk[27], chunk[28], chunk[29], chunk[30],
+                    chunk[31],
+                ]),
+            ];
+            assert_eq!(dereference_32(chunk), expected);
+        }
+    }
+
     #[test]
     fn xxh64_empty_test() {
         let seed = 0;
@@ -4,6 +4,9 @@
 #[macro_use]
 extern crate feature_macros;
 
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
 #[cfg(test)]
 extern crate getrandom;
 #[cfg(test)]
@@ -11,3 +14,19 @@ extern crate twox_hash;
 
 pub mod bits32;
 pub mod bits64;
+
+#[cfg(test)]
+mod property;
+
+#[cfg(test)]
+mod test {
+
+    /// `build.rs` must forward the capabilities `feature_macros` detected,
+    /// otherwise `inconceivable!` silently loses `ub_unreachable`.
+    #[test]
+    #[allow(clippy::assertions_on_constants)]
+    fn sees_feature_macros_capabilities() {
+        assert!(cfg!(HAS_UNREACHABLE_UNCHECKED));
+        assert!(cfg!(HAS_U128));
+    }
+}
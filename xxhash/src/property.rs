@@ -0,0 +1,245 @@
+/*
+ * Property tests for the hashers.
+ *
+ * The generator is a small in-tree SplitMix64 so the suite has no
+ * dependencies beyond what `bits32::test`/`bits64::test` already use,
+ * and so it builds under every `std`/`no_std` feature combination.
+ *
+ * Each failing assertion reports the generator seed, passing that
+ * seed to `Gen::with_seed` will reproduce the exact case.
+ */
+
+use super::bits32::{xxhash32_reference, XXHash32};
+use super::bits64::{xxhash64_reference, XXHash64};
+use super::getrandom::getrandom;
+use super::twox_hash::{XxHash32, XxHash64};
+
+#[cfg(not(feature = "std"))]
+use core::hash::Hasher;
+#[cfg(feature = "std")]
+use std::hash::Hasher;
+
+#[cfg(not(feature = "std"))]
+use std::vec::Vec;
+
+/// number of random cases each property is checked against
+#[cfg(not(miri))]
+const CASES: usize = 256;
+#[cfg(miri)]
+const CASES: usize = 4;
+
+/// longest input generated for the arbitrary length properties
+#[cfg(not(miri))]
+const MAX_LENGTH: usize = 10_000;
+#[cfg(miri)]
+const MAX_LENGTH: usize = 512;
+
+/// every length up to and including this is checked exhaustively
+#[cfg(not(miri))]
+const EXHAUSTIVE_LENGTH: usize = 1024;
+#[cfg(miri)]
+const EXHAUSTIVE_LENGTH: usize = 80;
+
+/// SplitMix64, see: http://xoshiro.di.unimi.it/splitmix64.c
+struct Gen {
+    seed: u64,
+    state: u64,
+}
+impl Gen {
+    fn from_entropy() -> Gen {
+        let mut seed = [0u8; 8];
+        getrandom(seed.as_mut()).unwrap();
+        Gen::with_seed(u64::from_le_bytes(seed))
+    }
+
+    fn with_seed(seed: u64) -> Gen {
+        Gen { seed, state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15u64);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9u64);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EBu64);
+        z ^ (z >> 31)
+    }
+
+    /// returns a value within `0..=max`
+    fn up_to(&mut self, max: usize) -> usize {
+        (self.next_u64() % (max as u64 + 1)) as usize
+    }
+
+    fn bytes(&mut self, length: usize) -> Vec<u8> {
+        let mut output = Vec::with_capacity(length + 8);
+        while output.len() < length {
+            output.extend_from_slice(&self.next_u64().to_le_bytes());
+        }
+        output.truncate(length);
+        output
+    }
+
+    /// splits `length` into a random number of consecutive chunk
+    /// lengths which sum to `length`. Empty chunks are allowed.
+    fn partition(&mut self, length: usize) -> Vec<usize> {
+        let mut output = Vec::new();
+        let mut remaining = length;
+        // bias towards small writes, so the internal buffering is hit
+        let max_chunk = match self.up_to(3) {
+            0 => 1,
+            1 => 16,
+            2 => 64,
+            _ => length,
+        };
+        while remaining > 0 {
+            let chunk = self.up_to(max_chunk.min(remaining));
+            output.push(chunk);
+            remaining -= chunk;
+        }
+        output
+    }
+}
+
+fn streaming32(seed: u32, data: &[u8], partition: &[usize]) -> u32 {
+    let mut hasher = XXHash32::with_seed(seed);
+    let mut remaining = data;
+    for length in partition {
+        let (head, tail) = remaining.split_at(*length);
+        hasher.write(head);
+        remaining = tail;
+    }
+    debug_assert!(remaining.is_empty());
+    hasher.finish() as u32
+}
+
+fn streaming64(seed: u64, data: &[u8], partition: &[usize]) -> u64 {
+    let mut hasher = XXHash64::with_seed(seed);
+    let mut remaining = data;
+    for length in partition {
+        let (head, tail) = remaining.split_at(*length);
+        hasher.write(head);
+        remaining = tail;
+    }
+    debug_assert!(remaining.is_empty());
+    hasher.finish()
+}
+
+fn upstream32(seed: u32, data: &[u8]) -> u32 {
+    let mut hasher = XxHash32::with_seed(seed);
+    hasher.write(data);
+    hasher.finish() as u32
+}
+
+fn upstream64(seed: u64, data: &[u8]) -> u64 {
+    let mut hasher = XxHash64::with_seed(seed);
+    hasher.write(data);
+    hasher.finish()
+}
+
+#[test]
+fn xxh32_split_write_invariance() {
+    let mut gen = Gen::from_entropy();
+    for _ in 0..CASES {
+        let seed = gen.next_u64() as u32;
+        let length = gen.up_to(MAX_LENGTH);
+        let data = gen.bytes(length);
+        let partition = gen.partition(length);
+
+        let expected = upstream32(seed, &data);
+        assert_eq!(
+            expected,
+            xxhash32_reference(seed, &data),
+            "reference mismatch, generator seed: {:#x}",
+            gen.seed
+        );
+        assert_eq!(
+            expected,
+            streaming32(seed, &data, &partition),
+            "streaming mismatch, generator seed: {:#x}",
+            gen.seed
+        );
+    }
+}
+
+#[test]
+fn xxh64_split_write_invariance() {
+    let mut gen = Gen::from_entropy();
+    for _ in 0..CASES {
+        let seed = gen.next_u64();
+        let length = gen.up_to(MAX_LENGTH);
+        let data = gen.bytes(length);
+        let partition = gen.partition(length);
+
+        let expected = upstream64(seed, &data);
+        assert_eq!(
+            expected,
+            xxhash64_reference(seed, &data),
+            "reference mismatch, generator seed: {:#x}",
+            gen.seed
+        );
+        assert_eq!(
+            expected,
+            streaming64(seed, &data, &partition),
+            "streaming mismatch, generator seed: {:#x}",
+            gen.seed
+        );
+    }
+}
+
+#[test]
+fn xxh32_exhaustive_lengths() {
+    let mut gen = Gen::from_entropy();
+    let seed = gen.next_u64() as u32;
+    let data = gen.bytes(EXHAUSTIVE_LENGTH);
+    for length in 0..=EXHAUSTIVE_LENGTH {
+        let dut = &data[0..length];
+        let expected = xxhash32_reference(seed, dut);
+        assert_eq!(
+            expected,
+            upstream32(seed, dut),
+            "length {}, generator seed: {:#x}",
+            length,
+            gen.seed
+        );
+        assert_eq!(
+            expected,
+            streaming32(seed, dut, &[length]),
+            "length {}, generator seed: {:#x}",
+            length,
+            gen.seed
+        );
+    }
+}
+
+#[test]
+fn xxh64_exhaustive_lengths() {
+    let mut gen = Gen::from_entropy();
+    let seed = gen.next_u64();
+    let data = gen.bytes(EXHAUSTIVE_LENGTH);
+    for length in 0..=EXHAUSTIVE_LENGTH {
+        let dut = &data[0..length];
+        let expected = xxhash64_reference(seed, dut);
+        assert_eq!(
+            expected,
+            upstream64(seed, dut),
+            "length {}, generator seed: {:#x}",
+            length,
+            gen.seed
+        );
+        assert_eq!(
+            expected,
+            streaming64(seed, dut, &[length]),
+            "length {}, generator seed: {:#x}",
+            length,
+            gen.seed
+        );
+    }
+}
+
+#[test]
+fn generator_is_deterministic() {
+    let mut a = Gen::with_seed(0x1234);
+    let mut b = Gen::with_seed(0x1234);
+    assert_eq!(a.bytes(100), b.bytes(100));
+    assert_eq!(a.partition(1000), b.partition(1000));
+    assert_eq!(a.partition(1000).iter().sum::<usize>(), 1000);
+}